@@ -2,7 +2,7 @@ use anyhow::{anyhow, Result};
 
 use super::EnumConversionError;
 use crate::fst::CFst;
-use crate::{get, wrap, RUSTFST_FFI_RESULT};
+use crate::{get, wrap, CLabel, CStateId, RUSTFST_FFI_RESULT};
 
 use ffi_convert::*;
 use rustfst::algorithms::determinize::{
@@ -42,13 +42,33 @@ impl CReprOf<DeterminizeType> for CDeterminizeType {
     }
 }
 
-#[derive(AsRust, CReprOf, CDrop, RawPointerConverter)]
-#[target_type(DeterminizeConfig)]
+#[derive(RawPointerConverter)]
 pub struct CDeterminizeConfig {
     delta: f32,
     det_type: CDeterminizeType,
+    weight_threshold: f32,
+    state_threshold: Option<CStateId>,
+    subsequential_label: CLabel,
+}
+
+impl AsRust<DeterminizeConfig<TropicalWeight>> for CDeterminizeConfig {
+    fn as_rust(&self) -> Result<DeterminizeConfig<TropicalWeight>, AsRustError> {
+        Ok(DeterminizeConfig::new(self.delta, self.det_type.as_rust()?)
+            .with_weight_threshold(TropicalWeight::new(self.weight_threshold))
+            .with_state_threshold(self.state_threshold)
+            .with_subsequential_label(self.subsequential_label))
+    }
 }
 
+impl CDrop for CDeterminizeConfig {
+    fn do_drop(&mut self) -> Result<(), CDropError> {
+        Ok(())
+    }
+}
+
+/// Creates a determinization config. A `weight_threshold` of `inf` disables the weight
+/// pruning and a null `state_threshold` disables the state pruning.
+///
 /// # Safety
 ///
 /// The pointers should be valid.
@@ -56,12 +76,23 @@ pub struct CDeterminizeConfig {
 pub unsafe extern "C" fn fst_determinize_config_new(
     delta: libc::c_float,
     det_type: libc::size_t,
+    weight_threshold: libc::c_float,
+    state_threshold: *const CStateId,
+    subsequential_label: CLabel,
     config: *mut *const CDeterminizeConfig,
 ) -> RUSTFST_FFI_RESULT {
     wrap(|| {
+        let state_threshold = if state_threshold.is_null() {
+            None
+        } else {
+            Some(unsafe { *state_threshold })
+        };
         let determinize_config = CDeterminizeConfig {
             delta,
             det_type: CDeterminizeType(det_type),
+            weight_threshold,
+            state_threshold,
+            subsequential_label,
         };
         unsafe { *config = determinize_config.into_raw_pointer() };
         Ok(())
@@ -38,20 +38,38 @@ class DeterminizeConfig:
     Struct containing the parameters controlling the determinization algorithm.
     """
 
-    def __init__(self, det_type: DeterminizeType, delta: Optional[float] = None):
+    def __init__(
+        self,
+        det_type: DeterminizeType,
+        delta: Optional[float] = None,
+        weight_threshold: Optional[float] = None,
+        state_threshold: Optional[int] = None,
+        subsequential_label: int = 0,
+    ):
         """
         Creates the configuration object.
         Args:
             det_type: Type of determinization to perform.
             delta:
+            weight_threshold: Pruning weight threshold. None disables the weight pruning.
+            state_threshold: Maximum number of states in the output. None for no limit.
+            subsequential_label: Label used for the transitions to the superfinal state
+                created when determinizing a transducer.
         """
         if delta is None:
             delta = KDELTA
+        if weight_threshold is None:
+            weight_threshold = float("inf")
+        if state_threshold is not None:
+            state_threshold = ctypes.byref(ctypes.c_size_t(state_threshold))
 
         config = ctypes.pointer(ctypes.c_void_p())
         ret_code = lib.fst_determinize_config_new(
             ctypes.c_float(delta),
             ctypes.c_size_t(det_type.value),
+            ctypes.c_float(weight_threshold),
+            state_threshold,
+            ctypes.c_size_t(subsequential_label),
             ctypes.byref(config),
         )
         err_msg = "Error creating DeterminizeConfig"
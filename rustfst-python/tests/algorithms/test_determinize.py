@@ -61,3 +61,25 @@ def test_determinize_fst():
     det_fst = fst1.determinize(config)
 
     assert det_fst == expected_fst
+
+
+def test_determinize_fst_weight_threshold():
+    fst1 = VectorFst()
+
+    s1 = fst1.add_state()
+    s2 = fst1.add_state()
+    s3 = fst1.add_state()
+
+    fst1.set_start(s1)
+    fst1.set_final(s2, 0.0)
+    fst1.set_final(s3, 0.0)
+
+    fst1.add_tr(s1, Tr(1, 1, 1.0, s2))
+    fst1.add_tr(s1, Tr(2, 2, 5.0, s3))
+
+    config = DeterminizeConfig(
+        DeterminizeType.DETERMINIZE_FUNCTIONAL, weight_threshold=2.0
+    )
+    det_fst = fst1.determinize(config)
+
+    assert det_fst.num_states() == 2
@@ -0,0 +1,387 @@
+use std::cmp::Ordering;
+use std::collections::hash_map::Entry;
+use std::collections::{BinaryHeap, HashMap};
+
+use anyhow::Result;
+
+use crate::algorithms::shortest_path::natural_less;
+use crate::fst_properties::mutable_properties::shortest_path_properties;
+use crate::fst_properties::FstProperties;
+use crate::fst_traits::{Fst, MutableFst};
+use crate::semirings::{Semiring, SemiringProperties};
+use crate::{StateId, Tr, Trs};
+
+/// Configuration for [`astar_shortest_path_with_config`].
+#[derive(Debug, Clone, PartialOrd, PartialEq)]
+pub struct AStarConfig<W: Semiring> {
+    /// Pruning beam. The states whose estimated weight (distance from the start
+    /// times heuristic) is worse than the estimated weight of the start state times
+    /// the beam are not expanded. `None` disables the pruning.
+    pub beam: Option<W>,
+}
+
+impl<W: Semiring> Default for AStarConfig<W> {
+    fn default() -> Self {
+        Self { beam: None }
+    }
+}
+
+impl<W: Semiring> AStarConfig<W> {
+    pub fn new(beam: Option<W>) -> Self {
+        Self { beam }
+    }
+
+    pub fn with_beam(self, beam: Option<W>) -> Self {
+        Self { beam }
+    }
+}
+
+/// Element of the A* priority queue. `state` is `None` for the superfinal state,
+/// reached from a final state through its final weight.
+struct AStarHeapItem<W: Semiring> {
+    estimate: W,
+    distance: W,
+    state: Option<StateId>,
+    // Insertion order, used to break ties and get a deterministic output.
+    id: usize,
+}
+
+impl<W: Semiring> PartialEq for AStarHeapItem<W> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<W: Semiring> Eq for AStarHeapItem<W> {}
+
+impl<W: Semiring> PartialOrd for AStarHeapItem<W> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<W: Semiring> Ord for AStarHeapItem<W> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // BinaryHeap is a max-heap, the order is reversed to pop the best estimate first.
+        if natural_less(&self.estimate, &other.estimate).unwrap_or(false) {
+            Ordering::Greater
+        } else if natural_less(&other.estimate, &self.estimate).unwrap_or(false) {
+            Ordering::Less
+        } else {
+            other.id.cmp(&self.id)
+        }
+    }
+}
+
+/// Best distance from the start found so far for a state and the transition
+/// used to reach it.
+struct AStarEntry<W: Semiring> {
+    distance: W,
+    parent: Option<(StateId, Tr<W>)>,
+}
+
+/// Builds the linear FST of the path ending in `f_parent`. The states are numbered
+/// the same way as in `shortest_path` : from the final state to the start state.
+fn astar_backtrace<W, FO>(
+    entries: &HashMap<StateId, AStarEntry<W>>,
+    f_parent: StateId,
+    final_weight: W,
+) -> Result<FO>
+where
+    W: Semiring,
+    FO: MutableFst<W>,
+{
+    let mut ofst = FO::new();
+    let mut s_p = ofst.add_state();
+    ofst.set_final(s_p, final_weight)?;
+    let mut state = f_parent;
+    while let Some((prevstate, tr)) = &entries[&state].parent {
+        let d_p = s_p;
+        s_p = ofst.add_state();
+        let mut tr = tr.clone();
+        tr.nextstate = d_p;
+        ofst.add_tr(s_p, tr)?;
+        state = *prevstate;
+    }
+    ofst.set_start(s_p)?;
+    ofst.set_properties_with_mask(
+        shortest_path_properties(ofst.properties(), true),
+        FstProperties::all_properties(),
+    );
+    Ok(ofst)
+}
+
+/// Computes the shortest path of an FST with the A* algorithm, see
+/// [`astar_shortest_path`]. The states that fall outside of the beam are
+/// not expanded, which may lead to a sub-optimal or an empty result.
+pub fn astar_shortest_path_with_config<W, FI, FO, H>(
+    ifst: &FI,
+    heuristic: H,
+    config: AStarConfig<W>,
+) -> Result<FO>
+where
+    W: Semiring,
+    FI: Fst<W>,
+    FO: MutableFst<W>,
+    H: Fn(StateId) -> W,
+{
+    if !W::properties().contains(SemiringProperties::PATH | SemiringProperties::RIGHT_SEMIRING) {
+        bail!("AStarShortestPath: Weight needs to have the path property and be right distributive")
+    }
+    let mut ofst = FO::new();
+    ofst.set_symts_from_fst(ifst);
+    let start = match ifst.start() {
+        Some(s) => s,
+        None => return Ok(ofst),
+    };
+    let start_estimate = heuristic(start);
+    let limit = match &config.beam {
+        Some(beam) => Some(start_estimate.times(beam)?),
+        None => None,
+    };
+
+    let mut entries = HashMap::new();
+    entries.insert(
+        start,
+        AStarEntry {
+            distance: W::one(),
+            parent: None,
+        },
+    );
+    // Best final state found so far along with the weight of the path ending there.
+    let mut f_parent: Option<(StateId, W, W)> = None;
+    let mut heap = BinaryHeap::new();
+    let mut num_pushed = 0;
+    heap.push(AStarHeapItem {
+        estimate: start_estimate,
+        distance: W::one(),
+        state: Some(start),
+        id: num_pushed,
+    });
+    num_pushed += 1;
+
+    while let Some(AStarHeapItem {
+        distance, state, ..
+    }) = heap.pop()
+    {
+        let state = match state {
+            Some(s) => s,
+            None => break,
+        };
+        // Skip the stale items for which a better distance has since been found.
+        if natural_less(&entries[&state].distance, &distance)? {
+            continue;
+        }
+
+        if let Some(final_weight) = ifst.final_weight(state)? {
+            let weight = distance.times(&final_weight)?;
+            let improves = match &f_parent {
+                Some((_, w, _)) => natural_less(&weight, w)?,
+                None => true,
+            };
+            let in_beam = match &limit {
+                Some(l) => !natural_less(l, &weight)?,
+                None => true,
+            };
+            if improves && in_beam {
+                heap.push(AStarHeapItem {
+                    estimate: weight.clone(),
+                    distance: weight.clone(),
+                    state: None,
+                    id: num_pushed,
+                });
+                num_pushed += 1;
+                f_parent = Some((state, weight, final_weight));
+            }
+        }
+
+        for tr in ifst.get_trs(state)?.trs() {
+            let next_distance = distance.times(&tr.weight)?;
+            let estimate = next_distance.times(heuristic(tr.nextstate))?;
+            if let Some(l) = &limit {
+                if natural_less(l, &estimate)? {
+                    continue;
+                }
+            }
+            match entries.entry(tr.nextstate) {
+                Entry::Occupied(mut e) => {
+                    if !natural_less(&next_distance, &e.get().distance)? {
+                        continue;
+                    }
+                    e.insert(AStarEntry {
+                        distance: next_distance.clone(),
+                        parent: Some((state, tr.clone())),
+                    });
+                }
+                Entry::Vacant(e) => {
+                    e.insert(AStarEntry {
+                        distance: next_distance.clone(),
+                        parent: Some((state, tr.clone())),
+                    });
+                }
+            };
+            heap.push(AStarHeapItem {
+                estimate,
+                distance: next_distance,
+                state: Some(tr.nextstate),
+                id: num_pushed,
+            });
+            num_pushed += 1;
+        }
+    }
+
+    match f_parent {
+        Some((state, _, final_weight)) => {
+            let mut ofst: FO = astar_backtrace(&entries, state, final_weight)?;
+            ofst.set_symts_from_fst(ifst);
+            Ok(ofst)
+        }
+        None => Ok(ofst),
+    }
+}
+
+/// Creates an FST containing the single shortest path of the input FST, as
+/// [`shortest_path`](crate::algorithms::shortest_path) does, using the A* algorithm.
+///
+/// `heuristic` estimates the distance from a state to the final states. The states are
+/// expanded by increasing distance from the start times heuristic, starting from the
+/// start state, so only a part of the FST is visited : it works on lazy FSTs such
+/// as `ComposeFst`. The result is optimal if the heuristic is admissible, i.e. never
+/// worse than the real distance to the final states (`h(s) <= d(s)` in the tropical
+/// semiring). `|_| W::one()` is always admissible and makes the algorithm equivalent
+/// to Dijkstra's.
+///
+/// The weight must have the path property and extending a path must not improve its
+/// weight (e.g. no negative weights in the tropical semiring).
+///
+/// # Example
+/// ```
+/// # #[macro_use] extern crate rustfst;
+/// # use anyhow::Result;
+/// # use rustfst::utils::acceptor;
+/// # use rustfst::semirings::{Semiring, TropicalWeight};
+/// # use rustfst::fst_impls::VectorFst;
+/// # use rustfst::fst_traits::Fst;
+/// # use rustfst::algorithms::{astar_shortest_path, shortest_distance};
+/// # use rustfst::algorithms::union::union;
+/// # fn main() -> Result<()> {
+/// let mut fst : VectorFst<TropicalWeight> = fst![1, 2; 3.0];
+/// let other : VectorFst<TropicalWeight> = fst![3; 1.0];
+/// union(&mut fst, &other)?;
+///
+/// let distance = shortest_distance(&fst, true)?;
+/// let res : VectorFst<TropicalWeight> = astar_shortest_path(&fst, |s| distance[s as usize])?;
+/// let path = res.paths_iter().next().unwrap();
+/// assert_eq!(path.ilabels, vec![3]);
+/// assert_eq!(path.weight, TropicalWeight::new(1.0));
+/// # Ok(())
+/// # }
+/// ```
+pub fn astar_shortest_path<W, FI, FO, H>(ifst: &FI, heuristic: H) -> Result<FO>
+where
+    W: Semiring,
+    FI: Fst<W>,
+    FO: MutableFst<W>,
+    H: Fn(StateId) -> W,
+{
+    astar_shortest_path_with_config(ifst, heuristic, AStarConfig::default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::algorithms::compose::{compose, ComposeFst};
+    use crate::algorithms::tr_compares::ILabelCompare;
+    use crate::algorithms::union::union;
+    use crate::algorithms::{shortest_distance, shortest_path, tr_sort};
+    use crate::fst_impls::VectorFst;
+    use crate::fst_traits::ExpandedFst;
+    use crate::semirings::{LogWeight, TropicalWeight};
+    use crate::utils::{acceptor, transducer};
+
+    fn build_fst() -> Result<VectorFst<TropicalWeight>> {
+        let mut fst = VectorFst::<TropicalWeight>::new();
+        let s0 = fst.add_state();
+        let s1 = fst.add_state();
+        let s2 = fst.add_state();
+        let s3 = fst.add_state();
+        fst.set_start(s0)?;
+        fst.set_final(s3, TropicalWeight::new(0.5))?;
+        fst.set_final(s2, TropicalWeight::new(6.0))?;
+        fst.add_tr(s0, Tr::new(1, 1, 1.0, s1))?;
+        fst.add_tr(s0, Tr::new(2, 2, 0.5, s2))?;
+        fst.add_tr(s1, Tr::new(3, 3, 1.0, s3))?;
+        fst.add_tr(s2, Tr::new(4, 4, 3.0, s3))?;
+        fst.add_tr(s2, Tr::new(5, 5, 0.75, s1))?;
+        fst.add_tr(s3, Tr::new(6, 6, 1.0, s0))?;
+        Ok(fst)
+    }
+
+    #[test]
+    fn test_astar_shortest_path_same_as_shortest_path() -> Result<()> {
+        let fst = build_fst()?;
+        let ref_fst: VectorFst<TropicalWeight> = shortest_path(&fst)?;
+
+        let res: VectorFst<TropicalWeight> = astar_shortest_path(&fst, |_| TropicalWeight::one())?;
+        assert_eq!(res, ref_fst);
+
+        let distance = shortest_distance(&fst, true)?;
+        let res: VectorFst<TropicalWeight> = astar_shortest_path(&fst, |s| distance[s as usize])?;
+        assert_eq!(res, ref_fst);
+        Ok(())
+    }
+
+    #[test]
+    fn test_astar_shortest_path_beam() -> Result<()> {
+        let fst = build_fst()?;
+
+        let config = AStarConfig::default().with_beam(Some(TropicalWeight::new(2.0)));
+        let res: VectorFst<TropicalWeight> =
+            astar_shortest_path_with_config(&fst, |_| TropicalWeight::one(), config)?;
+        assert_eq!(res.num_states(), 0);
+
+        let config = AStarConfig::default().with_beam(Some(TropicalWeight::new(2.5)));
+        let res: VectorFst<TropicalWeight> =
+            astar_shortest_path_with_config(&fst, |_| TropicalWeight::one(), config)?;
+        let ref_fst: VectorFst<TropicalWeight> = shortest_path(&fst)?;
+        assert_eq!(res, ref_fst);
+        Ok(())
+    }
+
+    #[test]
+    fn test_astar_shortest_path_compose_fst() -> Result<()> {
+        let mut fst1: VectorFst<TropicalWeight> =
+            transducer(&[1, 2], &[3, 4], TropicalWeight::new(1.0));
+        union(
+            &mut fst1,
+            &transducer::<_, VectorFst<_>>(&[1], &[3], TropicalWeight::new(0.5)),
+        )?;
+        let mut fst2: VectorFst<TropicalWeight> =
+            transducer(&[3, 4], &[5, 6], TropicalWeight::new(2.0));
+        union(
+            &mut fst2,
+            &transducer::<_, VectorFst<_>>(&[3], &[7], TropicalWeight::new(4.0)),
+        )?;
+        tr_sort(&mut fst2, ILabelCompare {});
+
+        let composed: VectorFst<TropicalWeight> =
+            compose::<_, VectorFst<_>, VectorFst<_>, _, _, _>(&fst1, &fst2)?;
+        let ref_fst: VectorFst<TropicalWeight> = shortest_path(&composed)?;
+
+        let compose_fst =
+            ComposeFst::<_, VectorFst<_>, VectorFst<_>, _, _, _, _, _>::new_auto(fst1, fst2)?;
+        let res: VectorFst<TropicalWeight> =
+            astar_shortest_path(&compose_fst, |_| TropicalWeight::one())?;
+        assert_eq!(res, ref_fst);
+        assert_eq!(res.paths_iter().next().unwrap().olabels, vec![5, 6]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_astar_shortest_path_non_path_semiring() {
+        let fst: VectorFst<LogWeight> = acceptor(&[1], LogWeight::one());
+        let res: Result<VectorFst<LogWeight>> = astar_shortest_path(&fst, |_| LogWeight::one());
+        assert!(res.is_err());
+    }
+}
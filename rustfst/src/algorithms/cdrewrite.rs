@@ -0,0 +1,688 @@
+use anyhow::Result;
+
+use crate::algorithms::closure::{closure, ClosureType};
+use crate::algorithms::compose::compose;
+use crate::algorithms::concat::concat;
+use crate::algorithms::determinize::determinize;
+use crate::algorithms::rm_epsilon::rm_epsilon;
+use crate::algorithms::tr_compares::{ILabelCompare, OLabelCompare};
+use crate::algorithms::tr_mappers::RmWeightMapper;
+use crate::algorithms::{
+    connect, fst_convert, fst_convert_from_ref, project, tr_sort, ProjectType,
+};
+use crate::fst_impls::VectorFst;
+use crate::fst_properties::{compute_fst_properties, FstProperties};
+use crate::fst_traits::{AllocableFst, CoreFst, ExpandedFst, Fst, MutableFst};
+use crate::semirings::{Semiring, WeaklyDivisibleSemiring, WeightQuantize};
+use crate::utils::transducer;
+use crate::{Label, StateId, Tr, Trs, EPS_LABEL};
+
+/// Direction in which the contexts of a rewrite rule are checked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CdRewriteDirection {
+    /// The left context is matched against the output of the previous rewrites
+    /// and the right context against the input.
+    LeftToRight,
+    /// The right context is matched against the output of the previous rewrites
+    /// and the left context against the input.
+    RightToLeft,
+    /// Both contexts are matched against the input.
+    Simultaneous,
+}
+
+/// Whether a rewrite rule has to be applied wherever its contexts match.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CdRewriteMode {
+    Obligatory,
+    Optional,
+}
+
+/// The three kinds of transducers built by the `Marker` operation of Mohri and Sproat.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MarkerType {
+    /// Inserts the markers after each occurrence of the pattern.
+    Mark,
+    /// Only accepts the markers after an occurrence of the pattern.
+    Check,
+    /// Only accepts the markers when not after an occurrence of the pattern.
+    CheckComplement,
+}
+
+/// Labels of the auxiliary symbols `<1`, `<2` and `>` used during the construction.
+#[derive(Debug, Clone, Copy)]
+struct Markers {
+    lbrace1: Label,
+    lbrace2: Label,
+    rbrace: Label,
+}
+
+fn max_label<W: Semiring, F: ExpandedFst<W>>(fst: &F) -> Label {
+    fst.fst_iter()
+        .flat_map(|data| {
+            data.trs
+                .trs()
+                .iter()
+                .map(|tr| tr.ilabel.max(tr.olabel))
+                .collect::<Vec<_>>()
+        })
+        .max()
+        .unwrap_or(EPS_LABEL)
+}
+
+fn is_unweighted_acceptor<W: Semiring, F: ExpandedFst<W>>(fst: &F) -> Result<bool> {
+    let mask = FstProperties::ACCEPTOR | FstProperties::UNWEIGHTED;
+    let mut known = FstProperties::empty();
+    let props = compute_fst_properties(fst, mask, &mut known, true)?;
+    Ok(props.contains(mask))
+}
+
+/// Adds a self-loop for each pair of labels on every state, except the start state
+/// when `skip_start` is set.
+fn add_loops<W: Semiring>(fst: &mut VectorFst<W>, loops: &[(Label, Label)], skip_start: bool) {
+    let start = fst.start();
+    for s in 0..(fst.num_states() as StateId) {
+        if skip_start && start == Some(s) {
+            continue;
+        }
+        for (ilabel, olabel) in loops {
+            unsafe { fst.add_tr_unchecked(s, Tr::new(*ilabel, *olabel, W::one(), s)) };
+        }
+    }
+}
+
+/// Reverses an FST whose final weights are all `One`. Contrary to `reverse`, the
+/// weight type is left untouched.
+fn reverse_unweighted<W: Semiring>(fst: &VectorFst<W>) -> VectorFst<W> {
+    let mut ofst = VectorFst::new();
+    let start = match fst.start() {
+        Some(s) => s,
+        None => return ofst,
+    };
+    ofst.add_states(fst.num_states() + 1);
+    let superinitial = fst.num_states() as StateId;
+    unsafe {
+        ofst.set_start_unchecked(superinitial);
+        ofst.set_final_unchecked(start, W::one());
+        for s in 0..(fst.num_states() as StateId) {
+            for tr in fst.get_trs_unchecked(s).trs() {
+                ofst.add_tr_unchecked(
+                    tr.nextstate,
+                    Tr::new(tr.ilabel, tr.olabel, tr.weight.clone(), s),
+                );
+            }
+            if fst.is_final_unchecked(s) {
+                ofst.add_tr_unchecked(superinitial, Tr::new(EPS_LABEL, EPS_LABEL, W::one(), s));
+            }
+        }
+    }
+    ofst
+}
+
+/// Allows the labels of `loops` anywhere inside the strings of `fst` but before
+/// their first symbol. With `reverse`, they are allowed anywhere but after their last symbol.
+fn ignore_internal<W: Semiring>(
+    fst: &VectorFst<W>,
+    loops: &[(Label, Label)],
+    reverse: bool,
+) -> Result<VectorFst<W>> {
+    let mut res = if reverse {
+        reverse_unweighted(fst)
+    } else {
+        fst.clone()
+    };
+    // Makes sure the start state has no incoming transition.
+    if let Some(start) = res.start() {
+        let nstart = res.add_state();
+        res.add_tr(nstart, Tr::new(EPS_LABEL, EPS_LABEL, W::one(), start))?;
+        res.set_start(nstart)?;
+    }
+    rm_epsilon(&mut res)?;
+    add_loops(&mut res, loops, true);
+    if reverse {
+        res = reverse_unweighted(&res);
+    }
+    Ok(res)
+}
+
+/// Applies the `Marker` operation to a deterministic automaton `fst` recognizing `Σ*β`.
+fn marker<W: Semiring>(
+    mut fst: VectorFst<W>,
+    marker_type: MarkerType,
+    markers: &[(Label, Label)],
+) -> Result<VectorFst<W>> {
+    for s in 0..(fst.num_states() as StateId) {
+        let is_final = fst.is_final(s)?;
+        match marker_type {
+            MarkerType::Mark if is_final => {
+                let ns = fst.add_state();
+                for tr in fst.pop_trs(s)? {
+                    fst.add_tr(ns, tr)?;
+                }
+                for (ilabel, olabel) in markers {
+                    fst.add_tr(s, Tr::new(*ilabel, *olabel, W::one(), ns))?;
+                }
+                fst.delete_final_weight(s)?;
+                fst.set_final(ns, W::one())?;
+            }
+            MarkerType::Check if is_final => {
+                for (ilabel, olabel) in markers {
+                    fst.add_tr(s, Tr::new(*ilabel, *olabel, W::one(), s))?;
+                }
+            }
+            MarkerType::CheckComplement if !is_final => {
+                for (ilabel, olabel) in markers {
+                    fst.add_tr(s, Tr::new(*ilabel, *olabel, W::one(), s))?;
+                }
+                fst.set_final(s, W::one())?;
+            }
+            _ => fst.set_final(s, W::one())?,
+        }
+    }
+    Ok(fst)
+}
+
+/// Builds the filter marking or checking the occurrences of `beta` in strings of
+/// `sigma`. With `reverse`, the filter is built right to left : the markers are then
+/// located before the occurrences of `beta` instead of after them.
+fn make_filter<W>(
+    beta: &VectorFst<W>,
+    sigma: &VectorFst<W>,
+    marker_type: MarkerType,
+    markers: &[(Label, Label)],
+    reverse: bool,
+) -> Result<VectorFst<W>>
+where
+    W: WeaklyDivisibleSemiring + WeightQuantize,
+{
+    let mut sigma_beta = sigma.clone();
+    if reverse {
+        concat(&mut sigma_beta, &reverse_unweighted(beta))?;
+    } else {
+        concat(&mut sigma_beta, beta)?;
+    }
+    rm_epsilon(&mut sigma_beta)?;
+    let dfa: VectorFst<W> = determinize(&sigma_beta)?;
+    let mut filter = marker(dfa, marker_type, markers)?;
+    if reverse {
+        filter = reverse_unweighted(&filter);
+        rm_epsilon(&mut filter)?;
+    }
+    Ok(filter)
+}
+
+fn compose_sorted<W: Semiring>(
+    mut fst1: VectorFst<W>,
+    mut fst2: VectorFst<W>,
+) -> Result<VectorFst<W>> {
+    tr_sort(&mut fst1, OLabelCompare {});
+    tr_sort(&mut fst2, ILabelCompare {});
+    compose::<_, VectorFst<_>, VectorFst<_>, _, _, _>(fst1, fst2)
+}
+
+/// Compiles the context-dependent rewrite rule `φ → ψ / λ __ ρ` into a transducer, following
+/// the construction of Mohri and Sproat ("An Efficient Compiler for Weighted Rewrite Rules", 1996).
+///
+/// - `tau` is the (possibly weighted) transducer rewriting `φ` into `ψ`.
+/// - `lambda` and `rho` are unweighted acceptors for the left and right contexts.
+/// - `sigma_star` is an unweighted acceptor for `Σ*`, the closure of the alphabet. It must
+///   cover every symbol appearing in the other FSTs.
+///
+/// The resulting transducer maps every string of `Σ*` to its rewrites. With
+/// `CdRewriteMode::Obligatory`, `φ` must be rewritten wherever it appears between `λ` and
+/// `ρ` while with `CdRewriteMode::Optional` it may also be left untouched.
+/// `direction` controls whether the contexts are matched against the input string or
+/// against the output of the previous rewrites.
+///
+/// The labels above the maximum label of the inputs are used internally as markers.
+///
+/// # Example
+/// ```
+/// # use anyhow::Result;
+/// # use rustfst::semirings::{Semiring, TropicalWeight};
+/// # use rustfst::fst_impls::VectorFst;
+/// # use rustfst::fst_traits::MutableFst;
+/// # use rustfst::algorithms::{cdrewrite, CdRewriteDirection, CdRewriteMode};
+/// # use rustfst::algorithms::closure::{closure, ClosureType};
+/// # use rustfst::algorithms::rewrite::top_rewrite;
+/// # use rustfst::algorithms::union::union;
+/// # use rustfst::utils::{acceptor, transducer};
+/// # fn main() -> Result<()> {
+/// // a → b / c __ d over the alphabet {a, b, c, d}.
+/// let tau : VectorFst<TropicalWeight> = transducer(&[1], &[2], TropicalWeight::one());
+/// let lambda : VectorFst<TropicalWeight> = acceptor(&[3], TropicalWeight::one());
+/// let rho : VectorFst<TropicalWeight> = acceptor(&[4], TropicalWeight::one());
+/// let mut sigma_star : VectorFst<TropicalWeight> = acceptor(&[1], TropicalWeight::one());
+/// for label in 2..=4 {
+///     let symbol : VectorFst<TropicalWeight> = acceptor(&[label], TropicalWeight::one());
+///     union(&mut sigma_star, &symbol)?;
+/// }
+/// closure(&mut sigma_star, ClosureType::ClosureStar);
+///
+/// let rule : VectorFst<_> = cdrewrite(
+///     &tau,
+///     &lambda,
+///     &rho,
+///     &sigma_star,
+///     CdRewriteDirection::LeftToRight,
+///     CdRewriteMode::Obligatory,
+/// )?;
+///
+/// assert_eq!(top_rewrite(&vec![3, 1, 4, 1, 4], &rule)?, vec![3, 2, 4, 1, 4]);
+/// # Ok(())
+/// # }
+/// ```
+pub fn cdrewrite<W, F1, F2>(
+    tau: &F1,
+    lambda: &F1,
+    rho: &F1,
+    sigma_star: &F1,
+    direction: CdRewriteDirection,
+    mode: CdRewriteMode,
+) -> Result<F2>
+where
+    W: WeaklyDivisibleSemiring + WeightQuantize,
+    F1: ExpandedFst<W>,
+    F2: MutableFst<W> + AllocableFst<W>,
+{
+    for (name, fst) in &[("lambda", lambda), ("rho", rho), ("sigma_star", sigma_star)] {
+        if !is_unweighted_acceptor(*fst)? {
+            bail!("CdRewrite : {} must be an unweighted acceptor", name)
+        }
+    }
+
+    let max = [tau, lambda, rho, sigma_star]
+        .iter()
+        .map(|f| max_label(*f))
+        .max()
+        .unwrap_or(EPS_LABEL);
+    let m = Markers {
+        lbrace1: max + 1,
+        lbrace2: max + 2,
+        rbrace: max + 3,
+    };
+
+    let tau: VectorFst<W> = fst_convert_from_ref(tau);
+    let lambda: VectorFst<W> = fst_convert_from_ref(lambda);
+    let rho: VectorFst<W> = fst_convert_from_ref(rho);
+    let sigma: VectorFst<W> = fst_convert_from_ref(sigma_star);
+
+    // φ, unweighted, with `>` allowed inside of it.
+    let mut phi = tau.clone();
+    project(&mut phi, ProjectType::ProjectInput);
+    phi.tr_map(&mut RmWeightMapper {})?;
+    let left_to_right = direction != CdRewriteDirection::RightToLeft;
+    let phi = ignore_internal(&phi, &[(m.rbrace, m.rbrace)], !left_to_right)?;
+    let rbrace_fst: VectorFst<W> = transducer(&[m.rbrace], &[m.rbrace], W::one());
+    let phi_rbrace = if left_to_right {
+        let mut res = phi;
+        concat(&mut res, &rbrace_fst)?;
+        res
+    } else {
+        let mut res = rbrace_fst;
+        concat(&mut res, &phi)?;
+        res
+    };
+    let mut sigma_rbrace = sigma.clone();
+    add_loops(&mut sigma_rbrace, &[(m.rbrace, m.rbrace)], false);
+
+    let insert_rbrace = [(EPS_LABEL, m.rbrace)];
+    let insert_lbraces = [(EPS_LABEL, m.lbrace1), (EPS_LABEL, m.lbrace2)];
+
+    // `r` inserts `>` before each right context (after each left context for RightToLeft)
+    // and `f` inserts `<1` or `<2` before each φ followed by `>` (after each φ preceded by `>`).
+    let (r, f) = match direction {
+        CdRewriteDirection::LeftToRight | CdRewriteDirection::Simultaneous => (
+            make_filter(&rho, &sigma, MarkerType::Mark, &insert_rbrace, true)?,
+            make_filter(
+                &phi_rbrace,
+                &sigma_rbrace,
+                MarkerType::Mark,
+                &insert_lbraces,
+                true,
+            )?,
+        ),
+        CdRewriteDirection::RightToLeft => (
+            make_filter(&lambda, &sigma, MarkerType::Mark, &insert_rbrace, false)?,
+            make_filter(
+                &phi_rbrace,
+                &sigma_rbrace,
+                MarkerType::Mark,
+                &insert_lbraces,
+                false,
+            )?,
+        ),
+    };
+
+    // `l1` only accepts `<1` after a left context (before a right context for RightToLeft)
+    // and `l2` only accepts `<2` elsewhere. With `Simultaneous`, they run on the input
+    // and thus keep the markers for the replacement.
+    let (l1, l2) = match direction {
+        CdRewriteDirection::LeftToRight | CdRewriteDirection::RightToLeft => {
+            let (context, reverse) = if direction == CdRewriteDirection::LeftToRight {
+                (&lambda, false)
+            } else {
+                (&rho, true)
+            };
+            let mut l1 = make_filter(
+                context,
+                &sigma,
+                MarkerType::Check,
+                &[(m.lbrace1, EPS_LABEL)],
+                reverse,
+            )?;
+            add_loops(&mut l1, &[(m.lbrace2, m.lbrace2)], false);
+            let l2 = match mode {
+                CdRewriteMode::Obligatory => make_filter(
+                    context,
+                    &sigma,
+                    MarkerType::CheckComplement,
+                    &[(m.lbrace2, EPS_LABEL)],
+                    reverse,
+                )?,
+                CdRewriteMode::Optional => {
+                    let mut l2 = sigma.clone();
+                    add_loops(&mut l2, &[(m.lbrace2, EPS_LABEL)], false);
+                    l2
+                }
+            };
+            (l1, Some(l2))
+        }
+        CdRewriteDirection::Simultaneous => {
+            let mut l1 = make_filter(
+                &lambda,
+                &sigma,
+                MarkerType::Check,
+                &[(m.lbrace1, m.lbrace1)],
+                false,
+            )?;
+            add_loops(
+                &mut l1,
+                &[(m.lbrace2, m.lbrace2), (m.rbrace, m.rbrace)],
+                false,
+            );
+            let l2 = match mode {
+                CdRewriteMode::Obligatory => {
+                    let mut l2 = make_filter(
+                        &lambda,
+                        &sigma,
+                        MarkerType::CheckComplement,
+                        &[(m.lbrace2, m.lbrace2)],
+                        false,
+                    )?;
+                    add_loops(
+                        &mut l2,
+                        &[(m.lbrace1, m.lbrace1), (m.rbrace, m.rbrace)],
+                        false,
+                    );
+                    Some(l2)
+                }
+                CdRewriteMode::Optional => None,
+            };
+            (l1, l2)
+        }
+    };
+
+    let replace = make_replace(&tau, &sigma, m, direction)?;
+
+    let mut res = compose_sorted(r, f)?;
+    if direction == CdRewriteDirection::Simultaneous {
+        res = compose_sorted(res, l1)?;
+        if let Some(l2) = l2 {
+            res = compose_sorted(res, l2)?;
+        }
+        res = compose_sorted(res, replace)?;
+    } else {
+        res = compose_sorted(res, replace)?;
+        res = compose_sorted(res, l1)?;
+        if let Some(l2) = l2 {
+            res = compose_sorted(res, l2)?;
+        }
+    }
+    connect(&mut res)?;
+
+    let mut ofst: F2 = fst_convert(res);
+    if let Some(symt) = tau.input_symbols() {
+        ofst.set_input_symbols(symt.clone());
+    }
+    if let Some(symt) = tau.output_symbols() {
+        ofst.set_output_symbols(symt.clone());
+    }
+    Ok(ofst)
+}
+
+/// Builds the transducer rewriting φ into ψ between the markers and deleting `>`.
+fn make_replace<W: Semiring>(
+    tau: &VectorFst<W>,
+    sigma: &VectorFst<W>,
+    m: Markers,
+    direction: CdRewriteDirection,
+) -> Result<VectorFst<W>> {
+    let lbrace2 = if direction == CdRewriteDirection::Simultaneous {
+        (m.lbrace2, EPS_LABEL)
+    } else {
+        (m.lbrace2, m.lbrace2)
+    };
+    let mut sigma_markers = sigma.clone();
+    add_loops(&mut sigma_markers, &[lbrace2, (m.rbrace, EPS_LABEL)], false);
+
+    // The markers located inside a rewritten φ are deleted.
+    let tau = ignore_internal(
+        tau,
+        &[
+            (m.lbrace1, EPS_LABEL),
+            (m.lbrace2, EPS_LABEL),
+            (m.rbrace, EPS_LABEL),
+        ],
+        direction == CdRewriteDirection::RightToLeft,
+    )?;
+    let lbrace1_olabel = if direction == CdRewriteDirection::Simultaneous {
+        EPS_LABEL
+    } else {
+        m.lbrace1
+    };
+    let lbrace1: VectorFst<W> = transducer(&[m.lbrace1], &[lbrace1_olabel], W::one());
+    let rbrace: VectorFst<W> = transducer(&[m.rbrace], &[EPS_LABEL], W::one());
+
+    let mut rewrite = if direction == CdRewriteDirection::RightToLeft {
+        let mut res = rbrace;
+        concat(&mut res, &tau)?;
+        concat(&mut res, &lbrace1)?;
+        res
+    } else {
+        let mut res = lbrace1;
+        concat(&mut res, &tau)?;
+        concat(&mut res, &rbrace)?;
+        res
+    };
+    concat(&mut rewrite, &sigma_markers)?;
+    closure(&mut rewrite, ClosureType::ClosureStar);
+
+    let mut replace = sigma_markers;
+    concat(&mut replace, &rewrite)?;
+    Ok(replace)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::algorithms::rewrite::{rewrites, top_rewrite};
+    use crate::algorithms::union::union;
+    use crate::semirings::TropicalWeight;
+    use crate::utils::acceptor;
+
+    // Alphabet {a, b, c, d} = {1, 2, 3, 4}.
+    fn sigma_star() -> Result<VectorFst<TropicalWeight>> {
+        let mut sigma: VectorFst<TropicalWeight> = acceptor(&[1], TropicalWeight::one());
+        for label in 2..=4 {
+            let symbol: VectorFst<_> = acceptor(&[label], TropicalWeight::one());
+            union(&mut sigma, &symbol)?;
+        }
+        closure(&mut sigma, ClosureType::ClosureStar);
+        Ok(sigma)
+    }
+
+    fn rule(
+        tau: (&[Label], &[Label]),
+        lambda: &[Label],
+        rho: &[Label],
+        direction: CdRewriteDirection,
+        mode: CdRewriteMode,
+    ) -> Result<VectorFst<TropicalWeight>> {
+        let tau: VectorFst<TropicalWeight> = transducer(tau.0, tau.1, TropicalWeight::one());
+        let lambda = acceptor(lambda, TropicalWeight::one());
+        let rho = acceptor(rho, TropicalWeight::one());
+        cdrewrite(&tau, &lambda, &rho, &sigma_star()?, direction, mode)
+    }
+
+    #[test]
+    fn test_cdrewrite_contexts() -> Result<()> {
+        let fst = rule(
+            (&[1], &[2]),
+            &[3],
+            &[4],
+            CdRewriteDirection::LeftToRight,
+            CdRewriteMode::Obligatory,
+        )?;
+        assert_eq!(rewrites(&vec![3, 1, 4], &fst)?, vec![vec![3, 2, 4]]);
+        assert_eq!(
+            rewrites(&vec![3, 1, 4, 3, 1, 4], &fst)?,
+            vec![vec![3, 2, 4, 3, 2, 4]]
+        );
+        assert_eq!(rewrites(&vec![1, 1, 4], &fst)?, vec![vec![1, 1, 4]]);
+        assert_eq!(rewrites(&vec![3, 1], &fst)?, vec![vec![3, 1]]);
+        assert_eq!(
+            rewrites(&Vec::<Label>::new(), &fst)?,
+            vec![Vec::<Label>::new()]
+        );
+
+        // No context : a → b everywhere.
+        let fst = rule(
+            (&[1], &[2]),
+            &[],
+            &[],
+            CdRewriteDirection::LeftToRight,
+            CdRewriteMode::Obligatory,
+        )?;
+        assert_eq!(rewrites(&vec![1, 3, 1], &fst)?, vec![vec![2, 3, 2]]);
+
+        // Multi-symbol φ : a b → c / __ d.
+        let fst = rule(
+            (&[1, 2], &[3]),
+            &[],
+            &[4],
+            CdRewriteDirection::LeftToRight,
+            CdRewriteMode::Obligatory,
+        )?;
+        assert_eq!(
+            rewrites(&vec![1, 2, 4, 1, 2], &fst)?,
+            vec![vec![3, 4, 1, 2]]
+        );
+        assert_eq!(rewrites(&vec![1, 2, 2, 4], &fst)?, vec![vec![1, 2, 2, 4]]);
+
+        // Insertion : ε → c / a __ b.
+        let fst = rule(
+            (&[], &[3]),
+            &[1],
+            &[2],
+            CdRewriteDirection::LeftToRight,
+            CdRewriteMode::Obligatory,
+        )?;
+        assert_eq!(rewrites(&vec![1, 2, 2], &fst)?, vec![vec![1, 3, 2, 2]]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_cdrewrite_directions() -> Result<()> {
+        let expected = [
+            (
+                CdRewriteDirection::LeftToRight,
+                vec![1, 2, 1],
+                vec![2, 2, 1],
+            ),
+            (
+                CdRewriteDirection::RightToLeft,
+                vec![1, 2, 2],
+                vec![1, 2, 1],
+            ),
+            (
+                CdRewriteDirection::Simultaneous,
+                vec![1, 2, 2],
+                vec![2, 2, 1],
+            ),
+        ];
+        for (direction, left_context, right_context) in expected.iter() {
+            // a → b / a __
+            let fst = rule(
+                (&[1], &[2]),
+                &[1],
+                &[],
+                *direction,
+                CdRewriteMode::Obligatory,
+            )?;
+            assert_eq!(rewrites(&vec![1, 1, 1], &fst)?, vec![left_context.clone()]);
+            // a → b / __ a
+            let fst = rule(
+                (&[1], &[2]),
+                &[],
+                &[1],
+                *direction,
+                CdRewriteMode::Obligatory,
+            )?;
+            assert_eq!(rewrites(&vec![1, 1, 1], &fst)?, vec![right_context.clone()]);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_cdrewrite_optional() -> Result<()> {
+        for direction in &[
+            CdRewriteDirection::LeftToRight,
+            CdRewriteDirection::RightToLeft,
+            CdRewriteDirection::Simultaneous,
+        ] {
+            let fst = rule((&[1], &[2]), &[3], &[], *direction, CdRewriteMode::Optional)?;
+            let mut res = rewrites(&vec![3, 1, 1, 3, 1], &fst)?;
+            res.sort();
+            assert_eq!(
+                res,
+                vec![
+                    vec![3, 1, 1, 3, 1],
+                    vec![3, 1, 1, 3, 2],
+                    vec![3, 2, 1, 3, 1],
+                    vec![3, 2, 1, 3, 2]
+                ]
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_cdrewrite_weighted() -> Result<()> {
+        let mut tau: VectorFst<TropicalWeight> = transducer(&[1], &[2], TropicalWeight::new(1.0));
+        let other: VectorFst<_> = transducer(&[1], &[3], TropicalWeight::new(0.5));
+        union(&mut tau, &other)?;
+        let eps: VectorFst<TropicalWeight> = acceptor(&[], TropicalWeight::one());
+        let fst: VectorFst<_> = cdrewrite(
+            &tau,
+            &eps,
+            &eps,
+            &sigma_star()?,
+            CdRewriteDirection::LeftToRight,
+            CdRewriteMode::Obligatory,
+        )?;
+        assert_eq!(top_rewrite(&vec![4, 1, 1], &fst)?, vec![4, 3, 3]);
+
+        let weighted: VectorFst<_> = acceptor(&[1], TropicalWeight::new(1.0));
+        assert!(cdrewrite::<_, _, VectorFst<_>>(
+            &tau,
+            &weighted,
+            &eps,
+            &sigma_star()?,
+            CdRewriteDirection::LeftToRight,
+            CdRewriteMode::Obligatory,
+        )
+        .is_err());
+        Ok(())
+    }
+}
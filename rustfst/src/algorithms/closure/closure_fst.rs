@@ -63,6 +63,56 @@ where
         ))
     }
 
+    /// Computes the bounded closure, accepting between `lower` and `upper` repetitions of
+    /// the FST, without upper bound if `upper` is `None`. The states of the input FST are
+    /// shared between the repetitions.
+    pub fn new_range(fst: F, lower: usize, upper: Option<usize>) -> Result<Self> {
+        if let Some(upper) = upper {
+            if upper < lower {
+                bail!(
+                    "ClosureFst : lower bound {} greater than upper bound {}",
+                    lower,
+                    upper
+                )
+            }
+        }
+        let props = fst.properties();
+        let mut rfst = F::new();
+        if let Some(isymt) = fst.input_symbols() {
+            rfst.set_input_symbols(Arc::clone(isymt));
+        }
+        if let Some(osymt) = fst.output_symbols() {
+            rfst.set_output_symbols(Arc::clone(osymt));
+        }
+        // State i of the root FST means that i repetitions have been read.
+        let num_states = upper.unwrap_or(lower) + 1;
+        rfst.add_states(num_states);
+        unsafe {
+            rfst.set_start_unchecked(0);
+            for s in 0..(num_states - 1) {
+                rfst.add_tr_unchecked(
+                    s as StateId,
+                    Tr::new(EPS_LABEL, NO_LABEL, W::one(), s as StateId + 1),
+                );
+            }
+            for s in lower..num_states {
+                rfst.set_final_unchecked(s as StateId, W::one());
+            }
+            if upper.is_none() {
+                rfst.add_tr_unchecked(
+                    lower as StateId,
+                    Tr::new(EPS_LABEL, NO_LABEL, W::one(), lower as StateId),
+                );
+            }
+        }
+        let fst_tuples = vec![(0, rfst), (NO_LABEL, fst)];
+
+        Ok(ClosureFst(
+            ReplaceFst::new(fst_tuples, 0, false)?,
+            closure_properties(props, true),
+        ))
+    }
+
     /// Turns the Lazy FST into a static one.
     pub fn compute<F2: MutableFst<W> + AllocableFst<W>>(&self) -> Result<F2> {
         self.0.compute()
@@ -1,3 +1,4 @@
+use anyhow::Result;
 use unsafe_unwrap::UnsafeUnwrap;
 
 use crate::algorithms::closure::ClosureType;
@@ -6,7 +7,7 @@ use crate::fst_properties::FstProperties;
 use crate::fst_traits::MutableFst;
 use crate::semirings::Semiring;
 use crate::tr::Tr;
-use crate::EPS_LABEL;
+use crate::{StateId, Trs, EPS_LABEL};
 
 /// This operation computes the concatenative closure.
 /// If A transduces string `x` to `y` with weight `a`,
@@ -69,3 +70,287 @@ where
         FstProperties::all_properties(),
     );
 }
+
+/// This operation computes the bounded concatenative closure. If A transduces string
+/// `x` to `y` with weight `a`, then the closure transduces `x^n` to `y^n` with weight
+/// `a^n` for every `n` between `lower` and `upper`. If `upper` is `None`, there is no
+/// upper bound : `closure_range(fst, 0, None)` is equivalent to the closure star and
+/// `closure_range(fst, 1, None)` to the closure plus.
+///
+/// Counting the repetitions requires a copy of the FST per repetition : the result has
+/// `N * |Q|` states (plus one when `lower` is 0), where `|Q|` is the number of states
+/// of the input FST and `N` is `upper`, or `max(lower, 1)` when `upper` is `None`. The
+/// last copy loops back onto itself when `upper` is `None`. For large bounds, prefer
+/// [`ClosureFst::new_range`](crate::algorithms::closure::ClosureFst::new_range) : it
+/// stores the input FST once and only builds the copies of the states that are visited.
+///
+/// # Example
+/// ```
+/// # use anyhow::Result;
+/// # use rustfst::fst_impls::VectorFst;
+/// # use rustfst::fst_traits::{Fst, MutableFst};
+/// # use rustfst::semirings::{Semiring, TropicalWeight};
+/// # use rustfst::utils::acceptor;
+/// # use rustfst::algorithms::closure::closure_range;
+/// # fn main() -> Result<()> {
+/// let mut fst : VectorFst<TropicalWeight> = acceptor(&[1, 2], TropicalWeight::new(1.0));
+/// closure_range(&mut fst, 2, Some(3))?;
+///
+/// let mut paths : Vec<_> = fst.paths_iter().map(|p| (p.ilabels, p.weight)).collect();
+/// paths.sort_by(|a, b| a.0.cmp(&b.0));
+/// assert_eq!(paths, vec![
+///     (vec![1, 2, 1, 2], TropicalWeight::new(2.0)),
+///     (vec![1, 2, 1, 2, 1, 2], TropicalWeight::new(3.0)),
+/// ]);
+/// # Ok(())
+/// # }
+/// ```
+pub fn closure_range<W, F>(fst: &mut F, lower: usize, upper: Option<usize>) -> Result<()>
+where
+    W: Semiring,
+    F: MutableFst<W>,
+{
+    if let Some(upper) = upper {
+        if upper < lower {
+            bail!(
+                "closure_range : lower bound {} greater than upper bound {}",
+                lower,
+                upper
+            )
+        }
+    }
+    let props = fst.properties();
+    let start_state = match fst.start() {
+        Some(s) => s,
+        None => {
+            // As for the closure star, the empty string is accepted if `lower` is 0.
+            if lower == 0 {
+                let nstart = fst.add_state();
+                unsafe {
+                    fst.set_start_unchecked(nstart);
+                    fst.set_final_unchecked(nstart, W::one());
+                }
+                fst.set_properties_with_mask(
+                    closure_properties(props, false),
+                    FstProperties::all_properties(),
+                );
+            }
+            return Ok(());
+        }
+    };
+
+    if upper == Some(0) {
+        // Only the empty string is accepted.
+        fst.del_all_states();
+        let s = fst.add_state();
+        unsafe {
+            fst.set_start_unchecked(s);
+            fst.set_final_unchecked(s, W::one());
+        }
+        fst.compute_and_update_properties_all()?;
+        return Ok(());
+    }
+
+    let num_states = fst.num_states() as StateId;
+    let num_copies = upper.unwrap_or_else(|| lower.max(1));
+    let final_states_id: Vec<_> = fst
+        .final_states_iter()
+        .map(|s| (s, unsafe { fst.final_weight_unchecked(s).unsafe_unwrap() }))
+        .collect();
+
+    // Copy k of state s is state k * num_states + s, the input FST being copy 0.
+    fst.add_states((num_copies - 1) * num_states as usize);
+    for copy in 1..num_copies as StateId {
+        let offset = copy * num_states;
+        for s in 0..num_states {
+            let trs: Vec<_> = unsafe { fst.get_trs_unchecked(s) }.trs().to_vec();
+            for mut tr in trs {
+                tr.nextstate += offset;
+                unsafe { fst.add_tr_unchecked(s + offset, tr) };
+            }
+        }
+    }
+
+    for copy in 0..num_copies {
+        let offset = copy as StateId * num_states;
+        let next_copy = if copy + 1 < num_copies {
+            Some(offset + num_states)
+        } else if upper.is_none() {
+            Some(offset)
+        } else {
+            None
+        };
+        for (s, final_weight) in final_states_id.iter() {
+            if let Some(next_copy) = next_copy {
+                unsafe {
+                    fst.add_tr_unchecked(
+                        s + offset,
+                        Tr::new(
+                            EPS_LABEL,
+                            EPS_LABEL,
+                            final_weight.clone(),
+                            next_copy + start_state,
+                        ),
+                    )
+                };
+            }
+            unsafe {
+                if copy + 1 >= lower {
+                    fst.set_final_unchecked(s + offset, final_weight.clone());
+                } else {
+                    fst.delete_final_weight_unchecked(s + offset);
+                }
+            }
+        }
+    }
+
+    if lower == 0 {
+        let nstart = fst.add_state();
+        unsafe {
+            fst.add_tr_unchecked(nstart, Tr::new(EPS_LABEL, EPS_LABEL, W::one(), start_state));
+            fst.set_start_unchecked(nstart);
+            fst.set_final_unchecked(nstart, W::one());
+        }
+    }
+
+    fst.set_properties_with_mask(
+        closure_properties(props, false),
+        FstProperties::all_properties(),
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::algorithms::closure::ClosureFst;
+    use crate::algorithms::concat::concat;
+    use crate::algorithms::union::union;
+    use crate::algorithms::{fst_convert_from_ref, rand_equivalent};
+    use crate::fst_impls::VectorFst;
+    use crate::fst_traits::{ExpandedFst, Fst};
+    use crate::semirings::LogWeight;
+    use crate::utils::acceptor;
+
+    fn build_fst() -> Result<VectorFst<LogWeight>> {
+        let mut fst: VectorFst<LogWeight> = acceptor(&[1], LogWeight::new(1.0));
+        let other: VectorFst<LogWeight> = acceptor(&[2, 3], LogWeight::new(0.5));
+        union(&mut fst, &other)?;
+        Ok(fst)
+    }
+
+    // Concatenates `lower` copies, then nested optional copies or a closure star.
+    fn build_reference(lower: usize, upper: Option<usize>) -> Result<VectorFst<LogWeight>> {
+        let item = build_fst()?;
+        let mut res: VectorFst<LogWeight> = acceptor(&[], LogWeight::one());
+        for _ in 0..lower {
+            concat(&mut res, &item)?;
+        }
+        let tail = match upper {
+            Some(upper) => {
+                let mut tail: VectorFst<LogWeight> = acceptor(&[], LogWeight::one());
+                for _ in lower..upper {
+                    let mut optional = item.clone();
+                    concat(&mut optional, &tail)?;
+                    let epsilon: VectorFst<LogWeight> = acceptor(&[], LogWeight::one());
+                    union(&mut optional, &epsilon)?;
+                    tail = optional;
+                }
+                tail
+            }
+            None => {
+                let mut tail = item;
+                closure(&mut tail, ClosureType::ClosureStar);
+                tail
+            }
+        };
+        concat(&mut res, &tail)?;
+        Ok(res)
+    }
+
+    #[test]
+    fn test_closure_range() -> Result<()> {
+        for (lower, upper) in &[
+            (0, Some(0)),
+            (0, Some(2)),
+            (1, Some(1)),
+            (2, Some(4)),
+            (3, Some(3)),
+            (0, None),
+            (1, None),
+            (3, None),
+        ] {
+            let reference = build_reference(*lower, *upper)?;
+
+            let mut fst = build_fst()?;
+            fst.closure_range(*lower, *upper)?;
+            assert!(
+                rand_equivalent(&fst, &reference, 50, 2022)?,
+                "{} {:?}",
+                lower,
+                upper
+            );
+
+            let lazy: VectorFst<_> =
+                fst_convert_from_ref(&ClosureFst::new_range(build_fst()?, *lower, *upper)?);
+            assert!(
+                rand_equivalent(&lazy, &reference, 50, 2022)?,
+                "{} {:?}",
+                lower,
+                upper
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_closure_range_empty_fst() -> Result<()> {
+        let epsilon = vec![(vec![], LogWeight::one())];
+        for upper in &[None, Some(0), Some(2)] {
+            let mut fst = VectorFst::<LogWeight>::new();
+            closure_range(&mut fst, 0, *upper)?;
+            let paths: Vec<_> = fst.paths_iter().map(|p| (p.ilabels, p.weight)).collect();
+            assert_eq!(paths, epsilon, "{:?}", upper);
+
+            let lazy: VectorFst<_> = fst_convert_from_ref(&ClosureFst::new_range(
+                VectorFst::<LogWeight>::new(),
+                0,
+                *upper,
+            )?);
+            let paths: Vec<_> = lazy.paths_iter().map(|p| (p.ilabels, p.weight)).collect();
+            assert_eq!(paths, epsilon, "{:?}", upper);
+        }
+
+        let mut fst = VectorFst::<LogWeight>::new();
+        closure_range(&mut fst, 1, None)?;
+        assert_eq!(fst.paths_iter().count(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_closure_range_num_states() -> Result<()> {
+        let num_states = build_fst()?.num_states();
+        for (lower, upper, expected) in &[
+            (0, Some(0), 1),
+            (0, Some(2), 2 * num_states + 1),
+            (2, Some(4), 4 * num_states),
+            (0, None, num_states + 1),
+            (1, None, num_states),
+            (3, None, 3 * num_states),
+        ] {
+            let mut fst = build_fst()?;
+            closure_range(&mut fst, *lower, *upper)?;
+            assert_eq!(fst.num_states(), *expected, "{} {:?}", lower, upper);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_closure_range_invalid_bounds() -> Result<()> {
+        let mut fst = build_fst()?;
+        assert!(closure_range(&mut fst, 3, Some(2)).is_err());
+        assert!(ClosureFst::new_range(fst, 3, Some(2)).is_err());
+        Ok(())
+    }
+}
@@ -2,7 +2,7 @@ mod closure_fst;
 mod closure_static;
 
 pub use closure_fst::ClosureFst;
-pub use closure_static::closure;
+pub use closure_static::{closure, closure_range};
 
 /// Defines the different types of closure : Star or Plus.
 #[derive(Clone, Debug, Copy, PartialEq)]
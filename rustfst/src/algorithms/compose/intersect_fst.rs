@@ -0,0 +1,283 @@
+use std::borrow::Borrow;
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use anyhow::Result;
+
+use crate::algorithms::compose::compose_filters::{
+    ComposeFilter, ComposeFilterBuilder, SequenceComposeFilterBuilder,
+};
+use crate::algorithms::compose::is_acceptor;
+use crate::algorithms::compose::matchers::{GenericMatcher, Matcher};
+use crate::algorithms::compose::{
+    ComposeFst, ComposeFstOpOptions, ComposeFstOpState, ComposeStateTuple,
+};
+use crate::algorithms::lazy::{FstCache, SimpleVecCache};
+use crate::fst_properties::FstProperties;
+use crate::fst_traits::{AllocableFst, CoreFst, Fst, FstIterator, MutableFst, StateIterator};
+use crate::semirings::Semiring;
+use crate::{StateId, SymbolTable, TrsVec};
+
+/// Computes the intersection (Hadamard product) of two FSAs. This version is a delayed FST.
+/// Only strings that are in both automata are retained in the result.
+///
+/// Both arguments must be acceptors. The output is an acceptor.
+#[derive(Debug)]
+pub struct IntersectFst<W, F1, F2, B1, B2, M1, M2, CFB, Cache = SimpleVecCache<W>>(
+    ComposeFst<W, F1, F2, B1, B2, M1, M2, CFB, Cache>,
+)
+where
+    W: Semiring,
+    F1: Fst<W>,
+    F2: Fst<W>,
+    B1: Borrow<F1> + Debug + Clone,
+    B2: Borrow<F2> + Debug + Clone,
+    M1: Matcher<W, F1, B1>,
+    M2: Matcher<W, F2, B2>,
+    CFB: ComposeFilterBuilder<W, F1, F2, B1, B2, M1, M2>;
+
+impl<W, F1, F2, B1, B2, M1, M2, CFB, Cache> Clone
+    for IntersectFst<W, F1, F2, B1, B2, M1, M2, CFB, Cache>
+where
+    W: Semiring + Clone,
+    F1: Fst<W>,
+    F2: Fst<W>,
+    B1: Borrow<F1> + Debug + Clone,
+    B2: Borrow<F2> + Debug + Clone,
+    M1: Matcher<W, F1, B1>,
+    M2: Matcher<W, F2, B2>,
+    CFB: ComposeFilterBuilder<W, F1, F2, B1, B2, M1, M2> + Clone,
+    Cache: FstCache<W> + Clone,
+{
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+fn check_acceptors<W: Semiring, F1: Fst<W>, F2: Fst<W>>(fst1: &F1, fst2: &F2) -> Result<()> {
+    if !is_acceptor(fst1)? || !is_acceptor(fst2)? {
+        bail!("IntersectFst: input FSTs are not acceptors")
+    }
+    Ok(())
+}
+
+impl<W, F1, F2, B1, B2, M1, M2, CFB, Cache> IntersectFst<W, F1, F2, B1, B2, M1, M2, CFB, Cache>
+where
+    W: Semiring,
+    F1: Fst<W>,
+    F2: Fst<W>,
+    B1: Borrow<F1> + Debug + Clone,
+    B2: Borrow<F2> + Debug + Clone,
+    M1: Matcher<W, F1, B1>,
+    M2: Matcher<W, F2, B2>,
+    CFB: ComposeFilterBuilder<W, F1, F2, B1, B2, M1, M2>,
+    Cache: FstCache<W>,
+{
+    pub fn new_with_options(
+        fst1: B1,
+        fst2: B2,
+        opts: ComposeFstOpOptions<
+            M1,
+            M2,
+            CFB,
+            ComposeFstOpState<
+                ComposeStateTuple<
+                    <CFB::CF as ComposeFilter<W, F1, F2, B1, B2, CFB::IM1, CFB::IM2>>::FS,
+                >,
+            >,
+        >,
+    ) -> Result<Self>
+    where
+        Cache: Default,
+    {
+        check_acceptors(fst1.borrow(), fst2.borrow())?;
+        Ok(IntersectFst(ComposeFst::new_with_options(
+            fst1, fst2, opts,
+        )?))
+    }
+
+    pub fn new(fst1: B1, fst2: B2) -> Result<Self>
+    where
+        Cache: Default,
+    {
+        Self::new_with_options(fst1, fst2, ComposeFstOpOptions::default())
+    }
+
+    /// Turns the Lazy FST into a static one.
+    pub fn compute<F: MutableFst<W> + AllocableFst<W>>(&self) -> Result<F> {
+        let mut ofst: F = self.0.compute()?;
+        ofst.set_properties_with_mask(
+            FstProperties::ACCEPTOR,
+            FstProperties::ACCEPTOR | FstProperties::NOT_ACCEPTOR,
+        );
+        Ok(ofst)
+    }
+}
+
+impl<W, F1, F2, B1, B2>
+    IntersectFst<
+        W,
+        F1,
+        F2,
+        B1,
+        B2,
+        GenericMatcher<W, F1, B1>,
+        GenericMatcher<W, F2, B2>,
+        SequenceComposeFilterBuilder<
+            W,
+            F1,
+            F2,
+            B1,
+            B2,
+            GenericMatcher<W, F1, B1>,
+            GenericMatcher<W, F2, B2>,
+        >,
+    >
+where
+    W: Semiring,
+    F1: Fst<W>,
+    F2: Fst<W>,
+    B1: Borrow<F1> + Debug + Clone,
+    B2: Borrow<F2> + Debug + Clone,
+{
+    pub fn new_auto(fst1: B1, fst2: B2) -> Result<Self> {
+        check_acceptors(fst1.borrow(), fst2.borrow())?;
+        Ok(IntersectFst(ComposeFst::new_auto(fst1, fst2)?))
+    }
+}
+
+impl<W, F1, F2, B1, B2, M1, M2, CFB, Cache> CoreFst<W>
+    for IntersectFst<W, F1, F2, B1, B2, M1, M2, CFB, Cache>
+where
+    W: Semiring,
+    F1: Fst<W>,
+    F2: Fst<W>,
+    B1: Borrow<F1> + Debug + Clone,
+    B2: Borrow<F2> + Debug + Clone,
+    M1: Matcher<W, F1, B1>,
+    M2: Matcher<W, F2, B2>,
+    CFB: ComposeFilterBuilder<W, F1, F2, B1, B2, M1, M2>,
+    Cache: FstCache<W>,
+{
+    type TRS = TrsVec<W>;
+
+    fn start(&self) -> Option<StateId> {
+        self.0.start()
+    }
+
+    fn final_weight(&self, state_id: StateId) -> Result<Option<W>> {
+        self.0.final_weight(state_id)
+    }
+
+    unsafe fn final_weight_unchecked(&self, state_id: StateId) -> Option<W> {
+        self.0.final_weight_unchecked(state_id)
+    }
+
+    fn num_trs(&self, s: StateId) -> Result<usize> {
+        self.0.num_trs(s)
+    }
+
+    unsafe fn num_trs_unchecked(&self, s: StateId) -> usize {
+        self.0.num_trs_unchecked(s)
+    }
+
+    fn get_trs(&self, state_id: StateId) -> Result<Self::TRS> {
+        self.0.get_trs(state_id)
+    }
+
+    unsafe fn get_trs_unchecked(&self, state_id: StateId) -> Self::TRS {
+        self.0.get_trs_unchecked(state_id)
+    }
+
+    fn properties(&self) -> FstProperties {
+        // Both inputs have been checked to be acceptors on construction.
+        (self.0.properties() & !FstProperties::NOT_ACCEPTOR) | FstProperties::ACCEPTOR
+    }
+
+    fn num_input_epsilons(&self, state: StateId) -> Result<usize> {
+        self.0.num_input_epsilons(state)
+    }
+
+    fn num_output_epsilons(&self, state: StateId) -> Result<usize> {
+        self.0.num_output_epsilons(state)
+    }
+}
+
+impl<'a, W, F1, F2, B1, B2, M1, M2, CFB, Cache> StateIterator<'a>
+    for IntersectFst<W, F1, F2, B1, B2, M1, M2, CFB, Cache>
+where
+    W: Semiring,
+    F1: Fst<W> + 'a,
+    F2: Fst<W> + 'a,
+    B1: Borrow<F1> + Debug + Clone + 'a,
+    B2: Borrow<F2> + Debug + Clone + 'a,
+    M1: Matcher<W, F1, B1> + 'a,
+    M2: Matcher<W, F2, B2> + 'a,
+    CFB: ComposeFilterBuilder<W, F1, F2, B1, B2, M1, M2> + 'a,
+    Cache: FstCache<W> + 'a,
+{
+    type Iter = <ComposeFst<W, F1, F2, B1, B2, M1, M2, CFB, Cache> as StateIterator<'a>>::Iter;
+
+    fn states_iter(&'a self) -> Self::Iter {
+        self.0.states_iter()
+    }
+}
+
+impl<'a, W, F1, F2, B1, B2, M1, M2, CFB, Cache> FstIterator<'a, W>
+    for IntersectFst<W, F1, F2, B1, B2, M1, M2, CFB, Cache>
+where
+    W: Semiring,
+    F1: Fst<W> + 'a,
+    F2: Fst<W> + 'a,
+    B1: Borrow<F1> + Debug + Clone + 'a,
+    B2: Borrow<F2> + Debug + Clone + 'a,
+    M1: Matcher<W, F1, B1> + 'a,
+    M2: Matcher<W, F2, B2> + 'a,
+    CFB: ComposeFilterBuilder<W, F1, F2, B1, B2, M1, M2> + 'a,
+    Cache: FstCache<W> + 'a,
+{
+    type FstIter =
+        <ComposeFst<W, F1, F2, B1, B2, M1, M2, CFB, Cache> as FstIterator<'a, W>>::FstIter;
+
+    fn fst_iter(&'a self) -> Self::FstIter {
+        self.0.fst_iter()
+    }
+}
+
+impl<W, F1, F2, B1, B2, M1, M2, CFB, Cache> Fst<W>
+    for IntersectFst<W, F1, F2, B1, B2, M1, M2, CFB, Cache>
+where
+    W: Semiring,
+    F1: Fst<W> + 'static,
+    F2: Fst<W> + 'static,
+    B1: Borrow<F1> + Debug + Clone + 'static,
+    B2: Borrow<F2> + Debug + Clone + 'static,
+    M1: Matcher<W, F1, B1> + 'static,
+    M2: Matcher<W, F2, B2> + 'static,
+    CFB: ComposeFilterBuilder<W, F1, F2, B1, B2, M1, M2> + 'static,
+    Cache: FstCache<W> + 'static,
+{
+    fn input_symbols(&self) -> Option<&Arc<SymbolTable>> {
+        self.0.input_symbols()
+    }
+
+    fn output_symbols(&self) -> Option<&Arc<SymbolTable>> {
+        self.0.output_symbols()
+    }
+
+    fn set_input_symbols(&mut self, symt: Arc<SymbolTable>) {
+        self.0.set_input_symbols(symt)
+    }
+
+    fn set_output_symbols(&mut self, symt: Arc<SymbolTable>) {
+        self.0.set_output_symbols(symt)
+    }
+
+    fn take_input_symbols(&mut self) -> Option<Arc<SymbolTable>> {
+        self.0.take_input_symbols()
+    }
+
+    fn take_output_symbols(&mut self) -> Option<Arc<SymbolTable>> {
+        self.0.take_output_symbols()
+    }
+}
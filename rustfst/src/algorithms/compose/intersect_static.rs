@@ -0,0 +1,272 @@
+use std::borrow::Borrow;
+use std::fmt::Debug;
+
+use anyhow::Result;
+
+use crate::algorithms::compose::{compose_with_config, ComposeConfig, ComposeFilterEnum};
+use crate::fst_properties::FstProperties;
+use crate::fst_traits::{AllocableFst, ExpandedFst, Fst, MutableFst};
+use crate::semirings::Semiring;
+use crate::Trs;
+
+#[derive(PartialOrd, PartialEq, Debug, Clone, Copy)]
+pub struct IntersectConfig {
+    pub compose_filter: ComposeFilterEnum,
+    pub connect: bool,
+}
+
+impl Default for IntersectConfig {
+    fn default() -> Self {
+        Self {
+            compose_filter: ComposeFilterEnum::AutoFilter,
+            connect: true,
+        }
+    }
+}
+
+impl IntersectConfig {
+    pub fn new(compose_filter: ComposeFilterEnum, connect: bool) -> Self {
+        Self {
+            compose_filter,
+            connect,
+        }
+    }
+}
+
+/// Checks whether an FST is an acceptor. The stored properties are used if they are known,
+/// otherwise the transitions are scanned for an input label differing from the output label.
+///
+/// `compute_fst_properties` is not used here because it requires an `ExpandedFst` while the
+/// lazy algorithms (`IntersectFst`, `DifferenceFst`) accept any `Fst`. The input is only
+/// borrowed, so the computed property can't be stored back into it.
+pub(crate) fn is_acceptor<W: Semiring, F: Fst<W>>(fst: &F) -> Result<bool> {
+    let props = fst.properties();
+    if props.contains(FstProperties::ACCEPTOR) {
+        return Ok(true);
+    }
+    if props.contains(FstProperties::NOT_ACCEPTOR) {
+        return Ok(false);
+    }
+    for state in fst.states_iter() {
+        if fst
+            .get_trs(state)?
+            .trs()
+            .iter()
+            .any(|tr| tr.ilabel != tr.olabel)
+        {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+pub fn intersect_with_config<
+    W: Semiring,
+    F1: ExpandedFst<W>,
+    F2: ExpandedFst<W>,
+    B1: Borrow<F1> + Debug + Clone,
+    B2: Borrow<F2> + Debug + Clone,
+    F3: MutableFst<W> + AllocableFst<W>,
+>(
+    fst1: B1,
+    fst2: B2,
+    config: IntersectConfig,
+) -> Result<F3> {
+    if !is_acceptor(fst1.borrow())? || !is_acceptor(fst2.borrow())? {
+        bail!("Intersect: input FSTs are not acceptors")
+    }
+    let compose_config = ComposeConfig {
+        compose_filter: config.compose_filter,
+        connect: config.connect,
+        ..ComposeConfig::default()
+    };
+    let mut ofst: F3 = compose_with_config(fst1, fst2, compose_config)?;
+    ofst.set_properties_with_mask(
+        FstProperties::ACCEPTOR,
+        FstProperties::ACCEPTOR | FstProperties::NOT_ACCEPTOR,
+    );
+    Ok(ofst)
+}
+
+/// This operation computes the intersection (Hadamard product) of two FSAs.
+/// Only strings that are in both automata are retained in the result.
+///
+/// The two arguments must be acceptors. One of the arguments must be label-sorted.
+///
+/// # Example
+/// ```
+/// # #[macro_use] extern crate rustfst;
+/// # use anyhow::Result;
+/// # use rustfst::utils::acceptor;
+/// # use rustfst::semirings::{Semiring, TropicalWeight};
+/// # use rustfst::fst_impls::VectorFst;
+/// # use rustfst::algorithms::compose::intersect;
+/// # use rustfst::algorithms::union::union;
+/// # fn main() -> Result<()> {
+/// let mut fst_1 : VectorFst<TropicalWeight> = fst![1,2; 0.5];
+/// let other : VectorFst<TropicalWeight> = fst![3; 1.0];
+/// union(&mut fst_1, &other)?;
+///
+/// let fst_2 : VectorFst<TropicalWeight> = fst![1,2; 1.5];
+///
+/// let intersected_fst : VectorFst<TropicalWeight> = intersect(fst_1, fst_2)?;
+/// let fst_ref : VectorFst<TropicalWeight> = fst![1,2; 2.0];
+/// assert_eq!(intersected_fst, fst_ref);
+/// # Ok(())
+/// # }
+/// ```
+pub fn intersect<
+    W: Semiring,
+    F1: ExpandedFst<W>,
+    F2: ExpandedFst<W>,
+    F3: MutableFst<W> + AllocableFst<W>,
+    B1: Borrow<F1> + Debug + Clone,
+    B2: Borrow<F2> + Debug + Clone,
+>(
+    fst1: B1,
+    fst2: B2,
+) -> Result<F3> {
+    intersect_with_config(fst1, fst2, IntersectConfig::default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::Arc;
+
+    use crate::algorithms::compose::compose_filters::SequenceComposeFilterBuilder;
+    use crate::algorithms::compose::matchers::SortedMatcher;
+    use crate::algorithms::compose::IntersectFst;
+    use crate::algorithms::tr_compares::ILabelCompare;
+    use crate::algorithms::tr_sort;
+    use crate::algorithms::union::union;
+    use crate::fst_impls::VectorFst;
+    use crate::fst_traits::{CoreFst, Fst};
+    use crate::semirings::TropicalWeight;
+    use crate::utils::{acceptor, transducer};
+    use crate::SymbolTable;
+
+    fn build_fsts() -> Result<(VectorFst<TropicalWeight>, VectorFst<TropicalWeight>)> {
+        let mut fst1: VectorFst<TropicalWeight> = acceptor(&[1, 2, 3], TropicalWeight::new(1.0));
+        let other: VectorFst<TropicalWeight> = acceptor(&[1, 4], TropicalWeight::new(0.5));
+        union(&mut fst1, &other)?;
+        tr_sort(&mut fst1, ILabelCompare {});
+
+        let mut fst2: VectorFst<TropicalWeight> = acceptor(&[1, 2, 3], TropicalWeight::new(2.0));
+        let other: VectorFst<TropicalWeight> = acceptor(&[2], TropicalWeight::new(0.5));
+        union(&mut fst2, &other)?;
+        tr_sort(&mut fst2, ILabelCompare {});
+        Ok((fst1, fst2))
+    }
+
+    #[test]
+    fn test_intersect_all_filters() -> Result<()> {
+        let (fst1, fst2) = build_fsts()?;
+        let fst_ref: VectorFst<TropicalWeight> = acceptor(&[1, 2, 3], TropicalWeight::new(3.0));
+
+        for compose_filter in &[
+            ComposeFilterEnum::AutoFilter,
+            ComposeFilterEnum::NullFilter,
+            ComposeFilterEnum::TrivialFilter,
+            ComposeFilterEnum::SequenceFilter,
+            ComposeFilterEnum::AltSequenceFilter,
+            ComposeFilterEnum::MatchFilter,
+            ComposeFilterEnum::NoMatchFilter,
+        ] {
+            let config = IntersectConfig::new(*compose_filter, true);
+            let fst_res: VectorFst<TropicalWeight> =
+                intersect_with_config(fst1.clone(), fst2.clone(), config)?;
+            assert!(fst_res.properties().contains(FstProperties::ACCEPTOR));
+            assert_eq!(
+                fst_res.paths_iter().collect::<Vec<_>>(),
+                fst_ref.paths_iter().collect::<Vec<_>>(),
+                "{:?}",
+                compose_filter
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_intersect_symbol_tables() -> Result<()> {
+        let (mut fst1, mut fst2) = build_fsts()?;
+        let mut symt = SymbolTable::new();
+        symt.add_symbols(vec!["a", "b", "c", "d"]);
+        let symt = Arc::new(symt);
+        fst1.set_input_symbols(Arc::clone(&symt));
+        fst1.set_output_symbols(Arc::clone(&symt));
+        fst2.set_input_symbols(Arc::clone(&symt));
+        fst2.set_output_symbols(Arc::clone(&symt));
+
+        let fst_res: VectorFst<TropicalWeight> = intersect(fst1, fst2)?;
+        assert_eq!(fst_res.input_symbols(), Some(&symt));
+        assert_eq!(fst_res.output_symbols(), Some(&symt));
+        Ok(())
+    }
+
+    #[test]
+    fn test_intersect_lazy() -> Result<()> {
+        let (fst1, fst2) = build_fsts()?;
+        let fst_static: VectorFst<TropicalWeight> = intersect_with_config(
+            fst1.clone(),
+            fst2.clone(),
+            IntersectConfig::new(ComposeFilterEnum::AutoFilter, false),
+        )?;
+
+        let lazy_fst = IntersectFst::<
+            _,
+            VectorFst<_>,
+            VectorFst<_>,
+            _,
+            _,
+            SortedMatcher<_, _, _>,
+            SortedMatcher<_, _, _>,
+            SequenceComposeFilterBuilder<_, _, _, _, _, _, _>,
+        >::new(Arc::new(fst1.clone()), Arc::new(fst2.clone()))?;
+        let fst_lazy: VectorFst<TropicalWeight> = lazy_fst.compute()?;
+        assert_eq!(fst_static, fst_lazy);
+
+        let auto_fst =
+            IntersectFst::<_, VectorFst<_>, VectorFst<_>, _, _, _, _, _>::new_auto(&fst1, &fst2)?;
+        assert!(auto_fst.properties().contains(FstProperties::ACCEPTOR));
+        Ok(())
+    }
+
+    #[test]
+    fn test_intersect_unknown_acceptor_property() -> Result<()> {
+        // Acceptors that don't advertise the property are accepted both statically and lazily.
+        let (mut fst1, mut fst2) = build_fsts()?;
+        let mask = FstProperties::ACCEPTOR | FstProperties::NOT_ACCEPTOR;
+        fst1.set_properties_with_mask(FstProperties::empty(), mask);
+        fst2.set_properties_with_mask(FstProperties::empty(), mask);
+        assert!(!fst1.properties().intersects(mask));
+
+        let fst_static: VectorFst<TropicalWeight> = intersect(fst1.clone(), fst2.clone())?;
+        let lazy_fst =
+            IntersectFst::<_, VectorFst<_>, VectorFst<_>, _, _, _, _, _>::new_auto(&fst1, &fst2)?;
+        assert!(lazy_fst.properties().contains(FstProperties::ACCEPTOR));
+        let fst_lazy: VectorFst<TropicalWeight> = lazy_fst.compute()?;
+        assert!(fst_static.properties().contains(FstProperties::ACCEPTOR));
+        assert!(fst_lazy.properties().contains(FstProperties::ACCEPTOR));
+        assert_eq!(
+            fst_static.paths_iter().collect::<Vec<_>>(),
+            fst_lazy.paths_iter().collect::<Vec<_>>()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_intersect_not_acceptor() -> Result<()> {
+        let (fst1, _) = build_fsts()?;
+        let fst2: VectorFst<TropicalWeight> =
+            transducer(&[1, 2, 3], &[1, 2, 4], TropicalWeight::one());
+        let res: Result<VectorFst<TropicalWeight>> = intersect(fst1.clone(), fst2.clone());
+        assert!(res.is_err());
+        assert!(
+            IntersectFst::<_, VectorFst<_>, VectorFst<_>, _, _, _, _, _>::new_auto(&fst1, &fst2)
+                .is_err()
+        );
+        Ok(())
+    }
+}
@@ -5,6 +5,7 @@ use anyhow::Result;
 use bitflags::bitflags;
 pub use generic_matcher::GenericMatcher;
 pub use multi_eps_matcher::{MultiEpsMatcher, MultiEpsMatcherFlags};
+pub use rho_matcher::RhoMatcher;
 pub use sigma_matcher::SigmaMatcher;
 pub use sorted_matcher::SortedMatcher;
 
@@ -16,6 +17,7 @@ use std::borrow::Borrow;
 
 mod generic_matcher;
 mod multi_eps_matcher;
+mod rho_matcher;
 mod sigma_matcher;
 mod sorted_matcher;
 
@@ -0,0 +1,200 @@
+use std::borrow::Borrow;
+use std::fmt::Debug;
+use std::iter::Peekable;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use anyhow::Result;
+
+use crate::algorithms::compose::matchers::{
+    IterItemMatcher, MatchType, Matcher, MatcherFlags, MatcherRewriteMode, REQUIRE_PRIORITY,
+};
+use crate::fst_properties::FstProperties;
+use crate::fst_traits::Fst;
+use crate::{Label, Semiring, StateId, EPS_LABEL, NO_LABEL};
+
+/// Matcher handling a special `rho` label. A transition labelled with `rho`
+/// matches any label that doesn't have an explicit match at the current state.
+/// The `rho` label is rewritten with the matched label when returned.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RhoMatcher<W, F, B, M>
+where
+    W: Semiring,
+    F: Fst<W>,
+    B: Borrow<F>,
+    M: Matcher<W, F, B>,
+{
+    match_type: MatchType,
+    rho_label: Label,
+    matcher: Arc<M>,
+    rewrite_both: bool,
+    w: PhantomData<(W, F, B)>,
+}
+
+fn has_rho<W, F, B, M>(state: StateId, matcher: &Arc<M>, rho_label: Label) -> Result<bool>
+where
+    W: Semiring,
+    F: Fst<W>,
+    B: Borrow<F>,
+    M: Matcher<W, F, B>,
+{
+    if rho_label != NO_LABEL {
+        Ok(matcher.iter(state, rho_label)?.next().is_some())
+    } else {
+        Ok(false)
+    }
+}
+
+impl<W, F, B, M> RhoMatcher<W, F, B, M>
+where
+    W: Semiring,
+    F: Fst<W>,
+    B: Borrow<F>,
+    M: Matcher<W, F, B>,
+{
+    pub fn new(
+        match_type: MatchType,
+        rho_label: Label,
+        rewrite_mode: MatcherRewriteMode,
+        matcher: Arc<M>,
+    ) -> Result<Self> {
+        if match_type == MatchType::MatchBoth {
+            bail!("RhoMatcher: Bad match type")
+        }
+        if rho_label == EPS_LABEL {
+            bail!("RhoMatcher: {} cannot be used as rho_label", EPS_LABEL)
+        }
+        let rewrite_both = match rewrite_mode {
+            MatcherRewriteMode::MatcherRewriteAuto => matcher
+                .fst()
+                .borrow()
+                .properties()
+                .contains(FstProperties::ACCEPTOR),
+            MatcherRewriteMode::MatcherRewriteAlways => true,
+            MatcherRewriteMode::MatcherRewriteNever => false,
+        };
+        Ok(Self {
+            match_type,
+            rho_label,
+            matcher,
+            rewrite_both,
+            w: PhantomData,
+        })
+    }
+
+    pub fn rho_label(&self) -> Label {
+        self.rho_label
+    }
+}
+
+impl<W, F, B, M> Matcher<W, F, B> for RhoMatcher<W, F, B, M>
+where
+    W: Semiring,
+    F: Fst<W>,
+    B: Borrow<F> + Debug,
+    M: Matcher<W, F, B>,
+{
+    type Iter = IteratorRhoMatcher<W, M::Iter>;
+
+    fn new(_fst: B, _match_type: MatchType) -> Result<Self>
+    where
+        Self: Sized,
+    {
+        bail!("This constructor can't be used for initializing RhoMatcher.")
+    }
+
+    fn iter(&self, state: StateId, label: Label) -> Result<Self::Iter> {
+        if label == self.rho_label && self.rho_label != NO_LABEL {
+            bail!("RhoMatcher::Find: bad label (rho)")
+        }
+        let mut explicit_iterator = self.matcher.iter(state, label)?.peekable();
+        if label == EPS_LABEL || label == NO_LABEL || explicit_iterator.peek().is_some() {
+            return Ok(IteratorRhoMatcher {
+                rho_match: NO_LABEL,
+                rho_label: self.rho_label,
+                match_type: self.match_type,
+                rewrite_both: self.rewrite_both,
+                matcher_iterator: explicit_iterator,
+            });
+        }
+        let rho_iterator = if self.rho_label != NO_LABEL {
+            self.matcher.iter(state, self.rho_label)?.peekable()
+        } else {
+            // Empty iterator, nothing can be matched.
+            explicit_iterator
+        };
+        Ok(IteratorRhoMatcher {
+            rho_match: label,
+            rho_label: self.rho_label,
+            match_type: self.match_type,
+            rewrite_both: self.rewrite_both,
+            matcher_iterator: rho_iterator,
+        })
+    }
+
+    fn final_weight(&self, state: StateId) -> Result<Option<W>> {
+        self.matcher.final_weight(state)
+    }
+
+    fn match_type(&self, test: bool) -> Result<MatchType> {
+        self.matcher.match_type(test)
+    }
+
+    fn flags(&self) -> MatcherFlags {
+        if self.rho_label == NO_LABEL || self.match_type == MatchType::MatchNone {
+            self.matcher.flags()
+        } else {
+            self.matcher.flags() | MatcherFlags::REQUIRE_MATCH
+        }
+    }
+
+    fn priority(&self, state: StateId) -> Result<usize> {
+        if has_rho(state, &self.matcher, self.rho_label)? {
+            Ok(REQUIRE_PRIORITY)
+        } else {
+            self.matcher.priority(state)
+        }
+    }
+
+    fn fst(&self) -> &B {
+        self.matcher.fst()
+    }
+}
+
+pub struct IteratorRhoMatcher<W: Semiring, I: Iterator<Item = IterItemMatcher<W>>> {
+    /// Label matched by the rho transitions. `NO_LABEL` if the matches are explicit.
+    rho_match: Label,
+    rho_label: Label,
+    match_type: MatchType,
+    rewrite_both: bool,
+    matcher_iterator: Peekable<I>,
+}
+
+impl<W: Semiring, I: Iterator<Item = IterItemMatcher<W>>> Iterator for IteratorRhoMatcher<W, I> {
+    type Item = IterItemMatcher<W>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.matcher_iterator.next()?;
+        if self.rho_match == NO_LABEL {
+            return Some(item);
+        }
+        // Only explicit rho transitions are rewritten, an epsilon loop can't be a rho match.
+        let mut rho_tr = match item {
+            IterItemMatcher::Tr(tr) => tr,
+            IterItemMatcher::EpsLoop => return Some(IterItemMatcher::EpsLoop),
+        };
+        if self.rewrite_both {
+            if rho_tr.ilabel == self.rho_label {
+                rho_tr.ilabel = self.rho_match;
+            }
+            if rho_tr.olabel == self.rho_label {
+                rho_tr.olabel = self.rho_match;
+            }
+        } else if self.match_type == MatchType::MatchInput {
+            rho_tr.ilabel = self.rho_match;
+        } else {
+            rho_tr.olabel = self.rho_match;
+        }
+        Some(IterItemMatcher::Tr(rho_tr))
+    }
+}
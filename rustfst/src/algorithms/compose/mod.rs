@@ -7,6 +7,9 @@ pub use self::compose_static::{
     compose, compose_with_config, ComposeConfig, ComposeFilterEnum, MatcherConfig,
     SigmaMatcherConfig,
 };
+pub use self::intersect_fst::IntersectFst;
+pub(crate) use self::intersect_static::is_acceptor;
+pub use self::intersect_static::{intersect, intersect_with_config, IntersectConfig};
 pub use self::interval_reach_visitor::IntervalReachVisitor;
 pub use self::interval_set::{IntInterval, IntervalSet};
 pub use self::label_reachable::{LabelReachable, LabelReachableData};
@@ -25,6 +28,8 @@ mod compose_fst;
 mod compose_fst_op;
 mod compose_state_tuple;
 mod compose_static;
+mod intersect_fst;
+mod intersect_static;
 mod interval_reach_visitor;
 mod interval_set;
 mod label_reachable;
@@ -62,7 +62,7 @@ where
     }
 
     fn properties(&self) -> FstProperties {
-        unimplemented!()
+        self.0.properties()
     }
 
     fn num_input_epsilons(&self, state: StateId) -> Result<usize> {
@@ -159,6 +159,10 @@ where
         self.0.compute()
     }
 
+    pub fn state_out_dist(&self, state: StateId) -> Result<W> {
+        self.0.op.state_out_dist(state)
+    }
+
     pub fn out_dist(self) -> Result<Vec<W>> {
         self.0.op.out_dist()
     }
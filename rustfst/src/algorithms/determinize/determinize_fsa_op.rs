@@ -145,11 +145,7 @@ where
     }
 
     fn norm_tr(&self, det_tr: &mut DeterminizeTr<W>) -> Result<()> {
-        det_tr
-            .dest_tuple
-            .subset
-            .pairs
-            .sort_by(|a, b| a.state.cmp(&b.state));
+        det_tr.dest_tuple.subset.pairs.sort_by_key(|a| a.state);
 
         for dest_elt in det_tr.dest_tuple.subset.pairs.iter() {
             det_tr.weight = CD::common_divisor(&det_tr.weight, &dest_elt.weight)?;
@@ -183,6 +179,12 @@ where
         self.state_table.find_id_from_ref(tuple)
     }
 
+    pub fn state_out_dist(&self, state: StateId) -> Result<W> {
+        self.state_table
+            .state_out_dist(state)
+            .ok_or_else(|| format_err!("Outdist for state {} has not been computed", state))
+    }
+
     pub fn out_dist(self) -> Result<Vec<W>> {
         let out_dist = self.state_table.out_dist();
         out_dist
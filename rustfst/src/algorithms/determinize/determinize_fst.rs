@@ -0,0 +1,182 @@
+use std::borrow::Borrow;
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use anyhow::Result;
+
+use crate::algorithms::determinize::determinize_fst_op::DeterminizeFstOp;
+use crate::algorithms::determinize::DeterminizeConfig;
+use crate::algorithms::lazy::{LazyFst, SimpleVecCache};
+use crate::fst_properties::FstProperties;
+use crate::fst_traits::{AllocableFst, CoreFst, Fst, FstIterator, MutableFst, StateIterator};
+use crate::semirings::{WeaklyDivisibleSemiring, WeightQuantize};
+use crate::{StateId, SymbolTable, TrsVec};
+
+type InnerLazyFst<W, F, B> = LazyFst<W, DeterminizeFstOp<W, F, B>, SimpleVecCache<W>>;
+
+/// Determinizes an FST : the output is an equivalent FST in which no state has
+/// two transitions with the same input label. Transducers are determinized through
+/// their gallic representation according to the `DeterminizeType` of the config.
+/// This version is a Delayed FST : the subsets are only computed when their state
+/// is visited.
+pub struct DeterminizeFst<W, F, B>(InnerLazyFst<W, F, B>)
+where
+    W: WeaklyDivisibleSemiring + WeightQuantize + 'static,
+    F: Fst<W> + 'static,
+    B: Borrow<F> + Debug + 'static;
+
+impl<W, F, B> CoreFst<W> for DeterminizeFst<W, F, B>
+where
+    W: WeaklyDivisibleSemiring + WeightQuantize + 'static,
+    F: Fst<W> + 'static,
+    B: Borrow<F> + Debug + 'static,
+{
+    type TRS = TrsVec<W>;
+
+    fn start(&self) -> Option<StateId> {
+        self.0.start()
+    }
+
+    fn final_weight(&self, state_id: StateId) -> Result<Option<W>> {
+        self.0.final_weight(state_id)
+    }
+
+    unsafe fn final_weight_unchecked(&self, state_id: StateId) -> Option<W> {
+        self.0.final_weight_unchecked(state_id)
+    }
+
+    fn num_trs(&self, s: StateId) -> Result<usize> {
+        self.0.num_trs(s)
+    }
+
+    unsafe fn num_trs_unchecked(&self, s: StateId) -> usize {
+        self.0.num_trs_unchecked(s)
+    }
+
+    fn get_trs(&self, state_id: StateId) -> Result<Self::TRS> {
+        self.0.get_trs(state_id)
+    }
+
+    unsafe fn get_trs_unchecked(&self, state_id: StateId) -> Self::TRS {
+        self.0.get_trs_unchecked(state_id)
+    }
+
+    fn properties(&self) -> FstProperties {
+        self.0.properties()
+    }
+
+    fn num_input_epsilons(&self, state: StateId) -> Result<usize> {
+        self.0.num_input_epsilons(state)
+    }
+
+    fn num_output_epsilons(&self, state: StateId) -> Result<usize> {
+        self.0.num_output_epsilons(state)
+    }
+}
+
+impl<'a, W, F, B> StateIterator<'a> for DeterminizeFst<W, F, B>
+where
+    W: WeaklyDivisibleSemiring + WeightQuantize + 'static,
+    F: Fst<W> + 'static,
+    B: Borrow<F> + Debug + 'static,
+{
+    type Iter = <InnerLazyFst<W, F, B> as StateIterator<'a>>::Iter;
+
+    fn states_iter(&'a self) -> Self::Iter {
+        self.0.states_iter()
+    }
+}
+
+impl<'a, W, F, B> FstIterator<'a, W> for DeterminizeFst<W, F, B>
+where
+    W: WeaklyDivisibleSemiring + WeightQuantize + 'static,
+    F: Fst<W> + 'static,
+    B: Borrow<F> + Debug + 'static,
+{
+    type FstIter = <InnerLazyFst<W, F, B> as FstIterator<'a, W>>::FstIter;
+
+    fn fst_iter(&'a self) -> Self::FstIter {
+        self.0.fst_iter()
+    }
+}
+
+impl<W, F, B> Fst<W> for DeterminizeFst<W, F, B>
+where
+    W: WeaklyDivisibleSemiring + WeightQuantize + 'static,
+    F: Fst<W> + 'static,
+    B: Borrow<F> + Debug + 'static,
+{
+    fn input_symbols(&self) -> Option<&Arc<SymbolTable>> {
+        self.0.input_symbols()
+    }
+
+    fn output_symbols(&self) -> Option<&Arc<SymbolTable>> {
+        self.0.output_symbols()
+    }
+
+    fn set_input_symbols(&mut self, symt: Arc<SymbolTable>) {
+        self.0.set_input_symbols(symt)
+    }
+
+    fn set_output_symbols(&mut self, symt: Arc<SymbolTable>) {
+        self.0.set_output_symbols(symt)
+    }
+
+    fn take_input_symbols(&mut self) -> Option<Arc<SymbolTable>> {
+        self.0.take_input_symbols()
+    }
+
+    fn take_output_symbols(&mut self) -> Option<Arc<SymbolTable>> {
+        self.0.take_output_symbols()
+    }
+}
+
+impl<W, F, B> Debug for DeterminizeFst<W, F, B>
+where
+    W: WeaklyDivisibleSemiring + WeightQuantize + 'static,
+    F: Fst<W> + 'static,
+    B: Borrow<F> + Debug + 'static,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<W, F, B> DeterminizeFst<W, F, B>
+where
+    W: WeaklyDivisibleSemiring + WeightQuantize + 'static,
+    F: Fst<W> + 'static,
+    B: Borrow<F> + Debug + 'static,
+{
+    pub fn new(fst: B) -> Result<Self> {
+        Self::new_with_config(fst, DeterminizeConfig::default())
+    }
+
+    /// The pruning thresholds of the config are not supported by the lazy version.
+    pub fn new_with_config(fst: B, config: DeterminizeConfig<W>) -> Result<Self> {
+        let isymt = fst.borrow().input_symbols().cloned();
+        let osymt = fst.borrow().output_symbols().cloned();
+        let fst_op = DeterminizeFstOp::new(fst, config)?;
+        let fst_cache = SimpleVecCache::default();
+        let lazy_fst = LazyFst::from_op_and_cache(fst_op, fst_cache, isymt, osymt);
+        Ok(DeterminizeFst(lazy_fst))
+    }
+
+    /// Turns the Lazy FST into a static one.
+    pub fn compute<F2: MutableFst<W> + AllocableFst<W>>(&self) -> Result<F2> {
+        self.0.compute()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fst_impls::VectorFst;
+    use crate::semirings::TropicalWeight;
+
+    #[test]
+    fn test_determinize_fst_sync() {
+        fn is_sync<T: Sync>() {}
+        is_sync::<DeterminizeFst<TropicalWeight, VectorFst<_>, VectorFst<_>>>();
+    }
+}
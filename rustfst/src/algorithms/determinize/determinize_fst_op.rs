@@ -0,0 +1,377 @@
+use std::borrow::Borrow;
+use std::fmt::Debug;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use anyhow::Result;
+
+use crate::algorithms::determinize::{
+    CommonDivisor, DefaultCommonDivisor, DeterminizeConfig, DeterminizeFsa, DeterminizeFsaOp,
+    DeterminizeType, GallicCommonDivisor,
+};
+use crate::algorithms::factor_weight::factor_iterators::{
+    GallicFactor, GallicFactorMin, GallicFactorRestrict,
+};
+use crate::algorithms::factor_weight::{
+    FactorIterator, FactorWeightFst, FactorWeightOptions, FactorWeightType,
+};
+use crate::algorithms::lazy::{FstOp, LazyFst, SimpleVecCache, StateTable};
+use crate::algorithms::weight_converters::{FromGallicConverter, ToGallicConverter};
+use crate::algorithms::{FinalTr, WeightConverter};
+use crate::fst_properties::mutable_properties::determinize_properties;
+use crate::fst_properties::FstProperties;
+use crate::fst_traits::Fst;
+use crate::semirings::{
+    GallicWeight, GallicWeightMin, GallicWeightRestrict, SemiringProperties,
+    WeaklyDivisibleSemiring, WeightQuantize,
+};
+use crate::{Label, Semiring, StateId, Tr, Trs, TrsVec, EPS_LABEL, KDELTA};
+
+/// Converts the weights of an FST to gallic weights on the fly.
+pub struct ToGallicOp<W: Semiring, F: Fst<W>, B: Borrow<F>, G: Semiring> {
+    fst: B,
+    properties: FstProperties,
+    w: PhantomData<(W, F, G)>,
+}
+
+impl<W: Semiring, F: Fst<W>, B: Borrow<F>, G: Semiring> Debug for ToGallicOp<W, F, B, G> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ToGallicOp {{ fst: {:?} }}", self.fst.borrow())
+    }
+}
+
+impl<W, F, B, G> ToGallicOp<W, F, B, G>
+where
+    W: Semiring,
+    F: Fst<W>,
+    B: Borrow<F>,
+    G: Semiring,
+    ToGallicConverter: WeightConverter<W, G>,
+{
+    fn new(fst: B) -> Self {
+        // The input labels are copied on the output side so the result is always an acceptor.
+        let properties =
+            ToGallicConverter {}.properties(fst.borrow().properties()) | FstProperties::ACCEPTOR;
+        Self {
+            fst,
+            properties,
+            w: PhantomData,
+        }
+    }
+}
+
+impl<W, F, B, G> FstOp<G> for ToGallicOp<W, F, B, G>
+where
+    W: Semiring,
+    F: Fst<W>,
+    B: Borrow<F>,
+    G: Semiring,
+    ToGallicConverter: WeightConverter<W, G>,
+{
+    fn compute_start(&self) -> Result<Option<StateId>> {
+        Ok(self.fst.borrow().start())
+    }
+
+    fn compute_trs(&self, state: StateId) -> Result<TrsVec<G>> {
+        let mut converter = ToGallicConverter {};
+        let trs = self
+            .fst
+            .borrow()
+            .get_trs(state)?
+            .trs()
+            .iter()
+            .map(|tr| converter.tr_map(tr))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(TrsVec(Arc::new(trs)))
+    }
+
+    fn compute_final_weight(&self, state: StateId) -> Result<Option<G>> {
+        match self.fst.borrow().final_weight(state)? {
+            Some(weight) => {
+                let final_tr = FinalTr {
+                    ilabel: EPS_LABEL,
+                    olabel: EPS_LABEL,
+                    weight,
+                };
+                Ok(Some(ToGallicConverter {}.final_tr_map(&final_tr)?.weight))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn properties(&self) -> FstProperties {
+        self.properties
+    }
+}
+
+/// Converts the gallic weights of an FST back to weights and output labels on the fly.
+/// Final weights that can't be represented as such are moved on a transition to a
+/// superfinal state. The states are `Some` of a state of the gallic FST or `None`
+/// for the superfinal state.
+pub struct FromGallicOp<W: Semiring, G: Semiring, F: Fst<G>> {
+    fst: F,
+    state_table: StateTable<Option<StateId>>,
+    properties: FstProperties,
+    superfinal_label: Label,
+    w: PhantomData<(W, G)>,
+}
+
+impl<W: Semiring, G: Semiring, F: Fst<G>> Debug for FromGallicOp<W, G, F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "FromGallicOp {{ fst: {:?}, state_table: {:?} }}",
+            self.fst, self.state_table
+        )
+    }
+}
+
+impl<W, G, F> FromGallicOp<W, G, F>
+where
+    W: Semiring,
+    G: Semiring,
+    F: Fst<G>,
+    FromGallicConverter: WeightConverter<G, W>,
+{
+    fn new(fst: F, superfinal_label: Label) -> Self {
+        let properties = FromGallicConverter { superfinal_label }.properties(fst.properties());
+        Self {
+            fst,
+            state_table: StateTable::new(),
+            properties,
+            superfinal_label,
+            w: PhantomData,
+        }
+    }
+
+    fn converter(&self) -> FromGallicConverter {
+        FromGallicConverter {
+            superfinal_label: self.superfinal_label,
+        }
+    }
+
+    fn map_final_tr(&self, state: StateId) -> Result<Option<FinalTr<W>>> {
+        match self.fst.final_weight(state)? {
+            Some(weight) => {
+                let final_tr = FinalTr {
+                    ilabel: EPS_LABEL,
+                    olabel: EPS_LABEL,
+                    weight,
+                };
+                Ok(Some(self.converter().final_tr_map(&final_tr)?))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+impl<W, G, F> FstOp<W> for FromGallicOp<W, G, F>
+where
+    W: Semiring,
+    G: Semiring,
+    F: Fst<G>,
+    FromGallicConverter: WeightConverter<G, W>,
+{
+    fn compute_start(&self) -> Result<Option<StateId>> {
+        Ok(self.fst.start().map(|s| self.state_table.find_id(Some(s))))
+    }
+
+    fn compute_trs(&self, state: StateId) -> Result<TrsVec<W>> {
+        let state = match self.state_table.find_tuple(state) {
+            Some(s) => s,
+            None => return Ok(TrsVec::default()),
+        };
+        let mut converter = self.converter();
+        let trs_owner = self.fst.get_trs(state)?;
+        let mut trs = Vec::with_capacity(trs_owner.trs().len() + 1);
+        for tr in trs_owner.trs() {
+            let mut mapped_tr = converter.tr_map(tr)?;
+            mapped_tr.nextstate = self.state_table.find_id(Some(tr.nextstate));
+            trs.push(mapped_tr);
+        }
+        if let Some(final_tr) = self.map_final_tr(state)? {
+            if final_tr.ilabel != EPS_LABEL || final_tr.olabel != EPS_LABEL {
+                trs.push(Tr::new(
+                    final_tr.ilabel,
+                    final_tr.olabel,
+                    final_tr.weight,
+                    self.state_table.find_id(None),
+                ));
+            }
+        }
+        Ok(TrsVec(Arc::new(trs)))
+    }
+
+    fn compute_final_weight(&self, state: StateId) -> Result<Option<W>> {
+        let state = match self.state_table.find_tuple(state) {
+            Some(s) => s,
+            None => return Ok(Some(W::one())),
+        };
+        match self.map_final_tr(state)? {
+            Some(final_tr) if final_tr.ilabel == EPS_LABEL && final_tr.olabel == EPS_LABEL => {
+                Ok(Some(final_tr.weight))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn properties(&self) -> FstProperties {
+        self.properties
+    }
+}
+
+type ToGallicFst<W, F, B, G> = LazyFst<G, ToGallicOp<W, F, B, G>, SimpleVecCache<G>>;
+type GallicDeterminizeFsa<W, F, B, G> = DeterminizeFsa<
+    G,
+    ToGallicFst<W, F, B, G>,
+    GallicCommonDivisor,
+    ToGallicFst<W, F, B, G>,
+    Vec<G>,
+>;
+type GallicFactorWeightFst<W, F, B, G, FI> =
+    FactorWeightFst<G, GallicDeterminizeFsa<W, F, B, G>, GallicDeterminizeFsa<W, F, B, G>, FI>;
+/// Determinization of a transducer : conversion to a gallic acceptor, determinization
+/// of this acceptor, factorization of the gallic weights and conversion back.
+type GallicDeterminizeOp<W, F, B, G, FI> =
+    FromGallicOp<W, G, GallicFactorWeightFst<W, F, B, G, FI>>;
+
+fn gallic_determinize_op<W, F, B, G, FI>(
+    fst: B,
+    delta: f32,
+    subsequential_label: Label,
+) -> Result<GallicDeterminizeOp<W, F, B, G, FI>>
+where
+    W: Semiring,
+    F: Fst<W> + 'static,
+    B: Borrow<F> + 'static,
+    G: WeaklyDivisibleSemiring + WeightQuantize + 'static,
+    FI: FactorIterator<G> + 'static,
+    ToGallicConverter: WeightConverter<W, G>,
+    FromGallicConverter: WeightConverter<G, W>,
+    GallicCommonDivisor: CommonDivisor<G>,
+{
+    let isymt = fst.borrow().input_symbols().cloned();
+    let osymt = fst.borrow().output_symbols().cloned();
+    let to_gallic_fst = LazyFst::from_op_and_cache(
+        ToGallicOp::new(fst),
+        SimpleVecCache::default(),
+        isymt,
+        osymt,
+    );
+    let det_fsa = DeterminizeFsa::new(to_gallic_fst, None, delta)?;
+    let factor_opts = FactorWeightOptions {
+        delta: KDELTA,
+        mode: FactorWeightType::FACTOR_FINAL_WEIGHTS,
+        final_ilabel: subsequential_label,
+        final_olabel: subsequential_label,
+        increment_final_ilabel: false,
+        increment_final_olabel: false,
+    };
+    let factored_fst = FactorWeightFst::new(det_fsa, factor_opts)?;
+    Ok(FromGallicOp::new(factored_fst, subsequential_label))
+}
+
+#[derive(Debug)]
+enum DeterminizeFstOpInner<W, F, B>
+where
+    W: WeaklyDivisibleSemiring + WeightQuantize + 'static,
+    F: Fst<W> + 'static,
+    B: Borrow<F> + Debug + 'static,
+{
+    Fsa(DeterminizeFsaOp<W, F, DefaultCommonDivisor, B, Vec<W>>),
+    Functional(GallicDeterminizeOp<W, F, B, GallicWeightRestrict<W>, GallicFactorRestrict<W>>),
+    NonFunctional(GallicDeterminizeOp<W, F, B, GallicWeight<W>, GallicFactor<W>>),
+    Disambiguate(GallicDeterminizeOp<W, F, B, GallicWeightMin<W>, GallicFactorMin<W>>),
+}
+
+#[derive(Debug)]
+pub struct DeterminizeFstOp<W, F, B>
+where
+    W: WeaklyDivisibleSemiring + WeightQuantize + 'static,
+    F: Fst<W> + 'static,
+    B: Borrow<F> + Debug + 'static,
+{
+    inner: DeterminizeFstOpInner<W, F, B>,
+    properties: FstProperties,
+}
+
+impl<W, F, B> DeterminizeFstOp<W, F, B>
+where
+    W: WeaklyDivisibleSemiring + WeightQuantize + 'static,
+    F: Fst<W> + 'static,
+    B: Borrow<F> + Debug + 'static,
+{
+    pub fn new(fst: B, config: DeterminizeConfig<W>) -> Result<Self> {
+        if config.prunes() {
+            bail!(
+                "DeterminizeFst : pruning thresholds are only supported by determinize_with_config"
+            )
+        }
+        let iprops = fst.borrow().properties();
+        let delta = config.delta;
+        let label = config.subsequential_label;
+        let inner = if iprops.contains(FstProperties::ACCEPTOR) {
+            if !W::properties().contains(SemiringProperties::LEFT_SEMIRING) {
+                bail!("DeterminizeFst : weight must be left distributive")
+            }
+            DeterminizeFstOpInner::Fsa(DeterminizeFsaOp::new(fst, None, delta)?)
+        } else {
+            match config.det_type {
+                DeterminizeType::DeterminizeFunctional => {
+                    DeterminizeFstOpInner::Functional(gallic_determinize_op(fst, delta, label)?)
+                }
+                DeterminizeType::DeterminizeNonFunctional => {
+                    DeterminizeFstOpInner::NonFunctional(gallic_determinize_op(fst, delta, label)?)
+                }
+                DeterminizeType::DeterminizeDisambiguate => {
+                    if !W::properties().contains(SemiringProperties::PATH) {
+                        bail!("DeterminizeFst : weight needs to have the path property to disambiguate output")
+                    }
+                    DeterminizeFstOpInner::Disambiguate(gallic_determinize_op(fst, delta, label)?)
+                }
+            }
+        };
+        let distinct_psubsequential_labels =
+            config.det_type != DeterminizeType::DeterminizeNonFunctional;
+        let properties = determinize_properties(iprops, false, distinct_psubsequential_labels);
+        Ok(Self { inner, properties })
+    }
+}
+
+impl<W, F, B> FstOp<W> for DeterminizeFstOp<W, F, B>
+where
+    W: WeaklyDivisibleSemiring + WeightQuantize + 'static,
+    F: Fst<W> + 'static,
+    B: Borrow<F> + Debug + 'static,
+{
+    fn compute_start(&self) -> Result<Option<StateId>> {
+        match &self.inner {
+            DeterminizeFstOpInner::Fsa(op) => op.compute_start(),
+            DeterminizeFstOpInner::Functional(op) => op.compute_start(),
+            DeterminizeFstOpInner::NonFunctional(op) => op.compute_start(),
+            DeterminizeFstOpInner::Disambiguate(op) => op.compute_start(),
+        }
+    }
+
+    fn compute_trs(&self, id: StateId) -> Result<TrsVec<W>> {
+        match &self.inner {
+            DeterminizeFstOpInner::Fsa(op) => op.compute_trs(id),
+            DeterminizeFstOpInner::Functional(op) => op.compute_trs(id),
+            DeterminizeFstOpInner::NonFunctional(op) => op.compute_trs(id),
+            DeterminizeFstOpInner::Disambiguate(op) => op.compute_trs(id),
+        }
+    }
+
+    fn compute_final_weight(&self, id: StateId) -> Result<Option<W>> {
+        match &self.inner {
+            DeterminizeFstOpInner::Fsa(op) => op.compute_final_weight(id),
+            DeterminizeFstOpInner::Functional(op) => op.compute_final_weight(id),
+            DeterminizeFstOpInner::NonFunctional(op) => op.compute_final_weight(id),
+            DeterminizeFstOpInner::Disambiguate(op) => op.compute_final_weight(id),
+        }
+    }
+
+    fn properties(&self) -> FstProperties {
+        self.properties
+    }
+}
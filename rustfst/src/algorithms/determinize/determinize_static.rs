@@ -9,8 +9,10 @@ use crate::algorithms::factor_weight::factor_iterators::{
     GallicFactor, GallicFactorMin, GallicFactorRestrict,
 };
 use crate::algorithms::factor_weight::{factor_weight, FactorWeightOptions, FactorWeightType};
+use crate::algorithms::prune::{prune, prune_to_with_distance, PruneConfig};
 use crate::algorithms::weight_convert;
 use crate::algorithms::weight_converters::{FromGallicConverter, ToGallicConverter};
+use crate::algorithms::{shortest_distance_with_config, ShortestDistanceConfig};
 use crate::fst_impls::VectorFst;
 use crate::fst_properties::mutable_properties::determinize_properties;
 use crate::fst_properties::FstProperties;
@@ -19,7 +21,7 @@ use crate::semirings::SemiringProperties;
 use crate::semirings::{
     GallicWeight, GallicWeightMin, GallicWeightRestrict, WeaklyDivisibleSemiring, WeightQuantize,
 };
-use crate::{EPS_LABEL, KDELTA};
+use crate::{Label, Semiring, StateId, EPS_LABEL, KDELTA};
 
 pub fn determinize_with_distance<W, F1, F2>(
     ifst: &F1,
@@ -52,7 +54,36 @@ where
     det_fsa.compute()
 }
 
-pub fn determinize_fst<W, F1, F2>(fst_in: &F1, det_type: DeterminizeType, delta: f32) -> Result<F2>
+/// Determinizes an acceptor while pruning it : the distance to the final states
+/// of each subset is computed from the distances of the input FST, which allows
+/// to drop the subsets worse than the threshold before expanding them.
+fn determinize_fsa_pruned<W, F1, F2>(fst_in: &F1, config: &DeterminizeConfig<W>) -> Result<F2>
+where
+    W: WeaklyDivisibleSemiring + WeightQuantize,
+    F1: ExpandedFst<W>,
+    F2: MutableFst<W> + AllocableFst<W>,
+{
+    if !W::properties().contains(SemiringProperties::LEFT_SEMIRING) {
+        bail!("determinize_fsa : weight must be left distributive")
+    }
+    let in_dist =
+        shortest_distance_with_config(fst_in, true, ShortestDistanceConfig::new(config.delta))?;
+    let det_fsa = DeterminizeFsa::<_, F1, DefaultCommonDivisor, _, _>::new(
+        fst_in,
+        Some(in_dist.as_slice()),
+        config.delta,
+    )?;
+    prune_to_with_distance(&det_fsa, config.prune_config(), |s| {
+        det_fsa.state_out_dist(s)
+    })
+}
+
+pub fn determinize_fst<W, F1, F2>(
+    fst_in: &F1,
+    det_type: DeterminizeType,
+    delta: f32,
+    subsequential_label: Label,
+) -> Result<F2>
 where
     W: WeaklyDivisibleSemiring + WeightQuantize + 'static,
     F1: ExpandedFst<W>,
@@ -60,14 +91,14 @@ where
 {
     let mut to_gallic = ToGallicConverter {};
     let mut from_gallic = FromGallicConverter {
-        superfinal_label: EPS_LABEL,
+        superfinal_label: subsequential_label,
     };
 
     let factor_opts = FactorWeightOptions {
         delta: KDELTA,
         mode: FactorWeightType::FACTOR_FINAL_WEIGHTS,
-        final_ilabel: EPS_LABEL,
-        final_olabel: EPS_LABEL,
+        final_ilabel: subsequential_label,
+        final_olabel: subsequential_label,
         increment_final_ilabel: false,
         increment_final_olabel: false,
     };
@@ -118,14 +149,29 @@ where
 }
 
 #[derive(Clone, Debug, Copy, PartialOrd, PartialEq)]
-pub struct DeterminizeConfig {
+pub struct DeterminizeConfig<W: Semiring> {
     pub delta: f32,
     pub det_type: DeterminizeType,
+    /// Pruning weight threshold. The residual subsets whose weight is worse
+    /// than the weight of the best path times this threshold are dropped.
+    /// `W::zero()` disables it.
+    pub weight_threshold: W,
+    /// Maximum number of states in the output, `None` for no limit.
+    pub state_threshold: Option<StateId>,
+    /// Label used for the transitions to the superfinal state created
+    /// when determinizing a transducer.
+    pub subsequential_label: Label,
 }
 
-impl DeterminizeConfig {
+impl<W: Semiring> DeterminizeConfig<W> {
     pub fn new(delta: f32, det_type: DeterminizeType) -> Self {
-        Self { delta, det_type }
+        Self {
+            delta,
+            det_type,
+            weight_threshold: W::zero(),
+            state_threshold: None,
+            subsequential_label: EPS_LABEL,
+        }
     }
 
     pub fn with_delta(self, delta: f32) -> Self {
@@ -135,15 +181,45 @@ impl DeterminizeConfig {
     pub fn with_det_type(self, det_type: DeterminizeType) -> Self {
         Self { det_type, ..self }
     }
-}
 
-impl Default for DeterminizeConfig {
-    fn default() -> Self {
+    pub fn with_weight_threshold(self, weight_threshold: W) -> Self {
+        Self {
+            weight_threshold,
+            ..self
+        }
+    }
+
+    pub fn with_state_threshold(self, state_threshold: Option<StateId>) -> Self {
         Self {
-            delta: KDELTA,
-            det_type: DeterminizeType::DeterminizeFunctional,
+            state_threshold,
+            ..self
         }
     }
+
+    pub fn with_subsequential_label(self, subsequential_label: Label) -> Self {
+        Self {
+            subsequential_label,
+            ..self
+        }
+    }
+
+    /// Whether one of the pruning thresholds is set.
+    pub(crate) fn prunes(&self) -> bool {
+        !self.weight_threshold.is_zero() || self.state_threshold.is_some()
+    }
+
+    fn prune_config(&self) -> PruneConfig<W> {
+        PruneConfig::default()
+            .with_weight_threshold(self.weight_threshold.clone())
+            .with_state_threshold(self.state_threshold)
+            .with_delta(self.delta)
+    }
+}
+
+impl<W: Semiring> Default for DeterminizeConfig<W> {
+    fn default() -> Self {
+        Self::new(KDELTA, DeterminizeType::DeterminizeFunctional)
+    }
 }
 
 pub fn determinize<W, F1, F2>(fst_in: &F1) -> Result<F2>
@@ -169,7 +245,11 @@ where
 ///
 /// ![determinize_out](https://raw.githubusercontent.com/Garvys/rustfst-images-doc/master/images/determinize_out.svg?sanitize=true)
 ///
-pub fn determinize_with_config<W, F1, F2>(fst_in: &F1, config: DeterminizeConfig) -> Result<F2>
+/// When `weight_threshold` or `state_threshold` is set in the config, the result
+/// is pruned as with [`prune`](crate::algorithms::prune::prune). For acceptors, the
+/// pruning happens during the subset construction. The weight must then have
+/// the path property.
+pub fn determinize_with_config<W, F1, F2>(fst_in: &F1, config: DeterminizeConfig<W>) -> Result<F2>
 where
     W: WeaklyDivisibleSemiring + WeightQuantize,
     F1: ExpandedFst<W>,
@@ -179,9 +259,17 @@ where
     let det_type = config.det_type;
     let iprops = fst_in.borrow().properties();
     let mut fst_res: F2 = if iprops.contains(FstProperties::ACCEPTOR) {
-        determinize_fsa::<_, F1, _, DefaultCommonDivisor>(fst_in, delta)?
+        if config.prunes() {
+            determinize_fsa_pruned(fst_in, &config)?
+        } else {
+            determinize_fsa::<_, F1, _, DefaultCommonDivisor>(fst_in, delta)?
+        }
     } else {
-        determinize_fst(fst_in, det_type, delta)?
+        let mut fst_res = determinize_fst(fst_in, det_type, delta, config.subsequential_label)?;
+        if config.prunes() {
+            prune(&mut fst_res, config.prune_config())?;
+        }
+        fst_res
     };
 
     let distinct_psubsequential_labels = !(det_type == DeterminizeType::DeterminizeNonFunctional);
@@ -196,11 +284,18 @@ where
 
 #[cfg(test)]
 mod tests {
+    use crate::algorithms::determinize::DeterminizeFst;
+    use crate::algorithms::prune::prune_to;
+    use crate::algorithms::union::union;
+    use crate::algorithms::{isomorphic, project, ProjectType};
     use crate::fst_impls::VectorFst;
+    use crate::fst_traits::{CoreFst, StateIterator};
     use crate::semirings::TropicalWeight;
     use crate::tr::Tr;
+    use crate::utils::{acceptor, transducer};
     use crate::Semiring;
     use crate::SymbolTable;
+    use crate::Trs;
     use proptest::prelude::any;
     use proptest::proptest;
     use std::sync::Arc;
@@ -269,6 +364,148 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_determinize_fst_lazy() -> Result<()> {
+        let mut input_fst = VectorFst::<TropicalWeight>::new();
+        let s0 = input_fst.add_state();
+        let s1 = input_fst.add_state();
+        let s2 = input_fst.add_state();
+        let s3 = input_fst.add_state();
+
+        input_fst.set_start(s0)?;
+        input_fst.set_final(s3, TropicalWeight::new(0.5))?;
+
+        input_fst.add_tr(s0, Tr::new(1, 5, 2.0, s1))?;
+        input_fst.add_tr(s0, Tr::new(1, 0, 3.0, s2))?;
+        input_fst.add_tr(s1, Tr::new(2, 0, 4.0, s3))?;
+        input_fst.add_tr(s2, Tr::new(2, 5, 3.0, s3))?;
+        input_fst.add_tr(s2, Tr::new(3, 7, 1.0, s3))?;
+
+        for det_type in [
+            DeterminizeType::DeterminizeFunctional,
+            DeterminizeType::DeterminizeNonFunctional,
+            DeterminizeType::DeterminizeDisambiguate,
+        ] {
+            let config = DeterminizeConfig::default().with_det_type(det_type);
+            let static_fst: VectorFst<TropicalWeight> =
+                determinize_with_config(&input_fst, config)?;
+            let lazy_fst: VectorFst<TropicalWeight> =
+                DeterminizeFst::<_, VectorFst<_>, _>::new_with_config(
+                    Arc::new(input_fst.clone()),
+                    config,
+                )?
+                .compute()?;
+            assert!(isomorphic(&static_fst, &lazy_fst)?, "{:?}", det_type);
+        }
+
+        let mut acceptor_fst = input_fst.clone();
+        project(&mut acceptor_fst, ProjectType::ProjectInput);
+        let static_fst: VectorFst<TropicalWeight> = determinize(&acceptor_fst)?;
+        let lazy_fst: VectorFst<TropicalWeight> =
+            DeterminizeFst::<_, VectorFst<_>, _>::new(Arc::new(acceptor_fst))?.compute()?;
+        assert!(isomorphic(&static_fst, &lazy_fst)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_determinize_prune_acceptor() -> Result<()> {
+        let mut input_fst = VectorFst::<TropicalWeight>::new();
+        let s0 = input_fst.add_state();
+        let s1 = input_fst.add_state();
+        let s2 = input_fst.add_state();
+        let s3 = input_fst.add_state();
+        input_fst.set_start(s0)?;
+        input_fst.set_final(s3, TropicalWeight::one())?;
+        input_fst.add_tr(s0, Tr::new(1, 1, 0.5, s1))?;
+        input_fst.add_tr(s0, Tr::new(1, 1, 1.0, s2))?;
+        input_fst.add_tr(s0, Tr::new(4, 4, 3.5, s3))?;
+        input_fst.add_tr(s1, Tr::new(2, 2, 0.0, s3))?;
+        input_fst.add_tr(s2, Tr::new(3, 3, 4.0, s3))?;
+
+        let config = DeterminizeConfig::default().with_weight_threshold(TropicalWeight::new(2.0));
+        let pruned_fst: VectorFst<TropicalWeight> = determinize_with_config(&input_fst, config)?;
+        let paths: Vec<_> = pruned_fst.paths_iter().collect();
+        assert_eq!(paths.len(), 1);
+        assert_eq!(paths[0].ilabels, vec![1, 2]);
+        assert_eq!(paths[0].weight, TropicalWeight::new(0.5));
+
+        let det_fst: VectorFst<TropicalWeight> = determinize(&input_fst)?;
+        let ref_fst: VectorFst<TropicalWeight> = prune_to(
+            &det_fst,
+            PruneConfig::default().with_weight_threshold(TropicalWeight::new(2.0)),
+        )?;
+        assert!(isomorphic(&pruned_fst, &ref_fst)?);
+
+        let config = DeterminizeConfig::default().with_state_threshold(Some(2));
+        let pruned_fst: VectorFst<TropicalWeight> = determinize_with_config(&input_fst, config)?;
+        assert!(pruned_fst.num_states() <= 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_determinize_prune_transducer() -> Result<()> {
+        let mut input_fst: VectorFst<TropicalWeight> =
+            transducer(&[1, 2], &[3, 4], TropicalWeight::new(1.0));
+        union(
+            &mut input_fst,
+            &transducer::<_, VectorFst<_>>(&[1, 3], &[3, 5], TropicalWeight::new(6.0)),
+        )?;
+
+        let config = DeterminizeConfig::default().with_weight_threshold(TropicalWeight::new(2.0));
+        let pruned_fst: VectorFst<TropicalWeight> = determinize_with_config(&input_fst, config)?;
+        let paths: Vec<_> = pruned_fst.paths_iter().collect();
+        assert_eq!(paths.len(), 1);
+        assert_eq!(paths[0].ilabels, vec![1, 2]);
+        assert_eq!(paths[0].olabels, vec![3, 4]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_determinize_subsequential_label() -> Result<()> {
+        let mut input_fst = VectorFst::<TropicalWeight>::new();
+        let s0 = input_fst.add_state();
+        let s1 = input_fst.add_state();
+        let s2 = input_fst.add_state();
+        input_fst.set_start(s0)?;
+        input_fst.set_final(s1, TropicalWeight::one())?;
+        input_fst.set_final(s2, TropicalWeight::one())?;
+        input_fst.add_tr(s0, Tr::new(1, 5, 1.0, s1))?;
+        input_fst.add_tr(s0, Tr::new(1, 6, 2.0, s2))?;
+
+        let config = DeterminizeConfig::default()
+            .with_det_type(DeterminizeType::DeterminizeNonFunctional)
+            .with_subsequential_label(9);
+        let static_fst: VectorFst<TropicalWeight> = determinize_with_config(&input_fst, config)?;
+        let lazy_fst: VectorFst<TropicalWeight> =
+            DeterminizeFst::<_, VectorFst<_>, _>::new_with_config(
+                Arc::new(input_fst.clone()),
+                config,
+            )?
+            .compute()?;
+        assert!(isomorphic(&static_fst, &lazy_fst)?);
+
+        let superfinal_trs: Vec<_> = static_fst
+            .states_iter()
+            .flat_map(|s| static_fst.get_trs(s).unwrap().trs().to_vec())
+            .filter(|tr| tr.ilabel == 9)
+            .collect();
+        assert_eq!(superfinal_trs.len(), 2);
+        assert!(superfinal_trs
+            .iter()
+            .all(|tr| static_fst.is_final(tr.nextstate).unwrap()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_determinize_fst_lazy_rejects_thresholds() {
+        let input_fst: VectorFst<TropicalWeight> = acceptor(&[1, 2], TropicalWeight::new(1.0));
+        let config = DeterminizeConfig::default().with_state_threshold(Some(3));
+        assert!(
+            DeterminizeFst::<_, VectorFst<_>, _>::new_with_config(Arc::new(input_fst), config)
+                .is_err()
+        );
+    }
+
     proptest! {
         #[test]
         fn test_proptest_determinize_keeps_symts(mut fst in any::<VectorFst::<TropicalWeight>>()) {
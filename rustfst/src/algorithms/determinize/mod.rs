@@ -1,14 +1,19 @@
 use determinize_fsa::DeterminizeFsa;
 use determinize_fsa_op::DeterminizeFsaOp;
+pub use determinize_fst::DeterminizeFst;
 pub use determinize_static::{
     determinize, determinize_with_config, determinize_with_distance, DeterminizeConfig,
 };
-use divisors::{DefaultCommonDivisor, GallicCommonDivisor};
-use element::{DeterminizeElement, DeterminizeStateTuple, DeterminizeTr, WeightedSubset};
-use state_table::DeterminizeStateTable;
+pub(crate) use divisors::{CommonDivisor, DefaultCommonDivisor, GallicCommonDivisor};
+pub(crate) use element::{
+    DeterminizeElement, DeterminizeStateTuple, DeterminizeTr, WeightedSubset,
+};
+pub(crate) use state_table::DeterminizeStateTable;
 
 mod determinize_fsa;
 mod determinize_fsa_op;
+mod determinize_fst;
+mod determinize_fst_op;
 mod determinize_static;
 mod divisors;
 mod element;
@@ -66,6 +66,13 @@ impl<W: Semiring, B: Borrow<[W]>> DeterminizeStateTable<W, B> {
         inner.table.get_by_left(&tuple_id).unwrap().clone()
     }
 
+    /// Distance from a DFA state to the final states. Only available when the
+    /// table has been created with `in_dist` and the state has been inserted.
+    pub fn state_out_dist(&self, tuple_id: StateId) -> Option<W> {
+        let inner = self.0.lock().unwrap();
+        inner.out_dist.get(tuple_id as usize).cloned().flatten()
+    }
+
     pub fn out_dist(self) -> Vec<Option<W>> {
         let inner = self.0.into_inner().unwrap();
         inner.out_dist
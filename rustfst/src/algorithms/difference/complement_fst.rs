@@ -0,0 +1,192 @@
+use std::borrow::Borrow;
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use anyhow::Result;
+
+use crate::algorithms::difference::complement_fst_op::ComplementFstOp;
+use crate::algorithms::difference::RHO_LABEL;
+use crate::algorithms::lazy::{LazyFst, SimpleVecCache};
+use crate::fst_properties::{compute_fst_properties, FstProperties};
+use crate::fst_traits::{
+    AllocableFst, CoreFst, ExpandedFst, Fst, FstIterator, MutableFst, StateIterator,
+};
+use crate::semirings::Semiring;
+use crate::{StateId, SymbolTable, Trs, TrsVec};
+
+type InnerLazyFst<W, F, B> = LazyFst<W, ComplementFstOp<W, F, B>, SimpleVecCache<W>>;
+
+/// Complements an automaton. This is a library-internal operation that
+/// introduces a special `rho` label (`RHO_LABEL`) matching any label without
+/// an explicit transition; use difference to get back an ordinary FST.
+/// The input must be an unweighted, epsilon-free, deterministic acceptor
+/// that doesn't use `RHO_LABEL`. This version is a delayed FST.
+pub struct ComplementFst<W: Semiring, F: Fst<W>, B: Borrow<F>>(InnerLazyFst<W, F, B>);
+
+impl<W, F, B> CoreFst<W> for ComplementFst<W, F, B>
+where
+    W: Semiring,
+    F: Fst<W>,
+    B: Borrow<F>,
+{
+    type TRS = TrsVec<W>;
+
+    fn start(&self) -> Option<StateId> {
+        self.0.start()
+    }
+
+    fn final_weight(&self, state_id: StateId) -> Result<Option<W>> {
+        self.0.final_weight(state_id)
+    }
+
+    unsafe fn final_weight_unchecked(&self, state_id: StateId) -> Option<W> {
+        self.0.final_weight_unchecked(state_id)
+    }
+
+    fn num_trs(&self, s: StateId) -> Result<usize> {
+        self.0.num_trs(s)
+    }
+
+    unsafe fn num_trs_unchecked(&self, s: StateId) -> usize {
+        self.0.num_trs_unchecked(s)
+    }
+
+    fn get_trs(&self, state_id: StateId) -> Result<Self::TRS> {
+        self.0.get_trs(state_id)
+    }
+
+    unsafe fn get_trs_unchecked(&self, state_id: StateId) -> Self::TRS {
+        self.0.get_trs_unchecked(state_id)
+    }
+
+    fn properties(&self) -> FstProperties {
+        self.0.properties()
+    }
+
+    fn num_input_epsilons(&self, state: StateId) -> Result<usize> {
+        self.0.num_input_epsilons(state)
+    }
+
+    fn num_output_epsilons(&self, state: StateId) -> Result<usize> {
+        self.0.num_output_epsilons(state)
+    }
+}
+
+impl<'a, W, F, B> StateIterator<'a> for ComplementFst<W, F, B>
+where
+    W: Semiring,
+    F: Fst<W> + 'a,
+    B: Borrow<F> + 'a,
+{
+    type Iter = <InnerLazyFst<W, F, B> as StateIterator<'a>>::Iter;
+
+    fn states_iter(&'a self) -> Self::Iter {
+        self.0.states_iter()
+    }
+}
+
+impl<'a, W, F, B> FstIterator<'a, W> for ComplementFst<W, F, B>
+where
+    W: Semiring,
+    F: Fst<W> + 'a,
+    B: Borrow<F> + 'a,
+{
+    type FstIter = <InnerLazyFst<W, F, B> as FstIterator<'a, W>>::FstIter;
+
+    fn fst_iter(&'a self) -> Self::FstIter {
+        self.0.fst_iter()
+    }
+}
+
+impl<W, F, B> Fst<W> for ComplementFst<W, F, B>
+where
+    W: Semiring,
+    F: Fst<W> + 'static,
+    B: Borrow<F> + 'static,
+{
+    fn input_symbols(&self) -> Option<&Arc<SymbolTable>> {
+        self.0.input_symbols()
+    }
+
+    fn output_symbols(&self) -> Option<&Arc<SymbolTable>> {
+        self.0.output_symbols()
+    }
+
+    fn set_input_symbols(&mut self, symt: Arc<SymbolTable>) {
+        self.0.set_input_symbols(symt)
+    }
+
+    fn set_output_symbols(&mut self, symt: Arc<SymbolTable>) {
+        self.0.set_output_symbols(symt)
+    }
+
+    fn take_input_symbols(&mut self) -> Option<Arc<SymbolTable>> {
+        self.0.take_input_symbols()
+    }
+
+    fn take_output_symbols(&mut self) -> Option<Arc<SymbolTable>> {
+        self.0.take_output_symbols()
+    }
+}
+
+impl<W, F, B> Debug for ComplementFst<W, F, B>
+where
+    W: Semiring,
+    F: Fst<W>,
+    B: Borrow<F>,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<W, F, B> ComplementFst<W, F, B>
+where
+    W: Semiring,
+    F: ExpandedFst<W>,
+    B: Borrow<F>,
+{
+    pub fn new(fst: B) -> Result<Self> {
+        let mask = FstProperties::NO_EPSILONS
+            | FstProperties::I_DETERMINISTIC
+            | FstProperties::ACCEPTOR
+            | FstProperties::UNWEIGHTED;
+        let mut known = FstProperties::empty();
+        let props = compute_fst_properties(fst.borrow(), mask, &mut known, true)?;
+        if !props.contains(mask) {
+            bail!("ComplementFst: Argument not an unweighted epsilon-free deterministic acceptor")
+        }
+        for s in fst.borrow().states_iter() {
+            if fst
+                .borrow()
+                .get_trs(s)?
+                .trs()
+                .iter()
+                .any(|tr| tr.ilabel == RHO_LABEL)
+            {
+                bail!(
+                    "ComplementFst: Argument uses the label {} reserved for rho transitions",
+                    RHO_LABEL
+                )
+            }
+        }
+        let isymt = fst.borrow().input_symbols().cloned();
+        let osymt = fst.borrow().output_symbols().cloned();
+        let fst_op = ComplementFstOp::new(fst);
+        let fst_cache = SimpleVecCache::default();
+        let lazy_fst = LazyFst::from_op_and_cache(fst_op, fst_cache, isymt, osymt);
+        Ok(ComplementFst(lazy_fst))
+    }
+}
+
+impl<W, F, B> ComplementFst<W, F, B>
+where
+    W: Semiring,
+    F: Fst<W>,
+    B: Borrow<F>,
+{
+    /// Turns the Lazy FST into a static one.
+    pub fn compute<F2: MutableFst<W> + AllocableFst<W>>(&self) -> Result<F2> {
+        self.0.compute()
+    }
+}
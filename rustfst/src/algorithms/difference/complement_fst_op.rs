@@ -0,0 +1,80 @@
+use std::borrow::Borrow;
+use std::fmt::Debug;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use anyhow::Result;
+
+use crate::algorithms::difference::RHO_LABEL;
+use crate::algorithms::lazy::FstOp;
+use crate::fst_properties::mutable_properties::complement_properties;
+use crate::fst_properties::FstProperties;
+use crate::fst_traits::Fst;
+use crate::semirings::Semiring;
+use crate::{StateId, Tr, Trs, TrsVec};
+
+pub struct ComplementFstOp<W: Semiring, F: Fst<W>, B: Borrow<F>> {
+    fst: B,
+    properties: FstProperties,
+    w: PhantomData<(W, F)>,
+}
+
+impl<W: Semiring, F: Fst<W>, B: Borrow<F>> Debug for ComplementFstOp<W, F, B> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "ComplementFstOp {{ fst: {:?}, properties: {:?} }}",
+            self.fst.borrow(),
+            self.properties
+        )
+    }
+}
+
+impl<W: Semiring, F: Fst<W>, B: Borrow<F>> ComplementFstOp<W, F, B> {
+    pub fn new(fst: B) -> Self {
+        let properties = complement_properties(fst.borrow().properties());
+        Self {
+            fst,
+            properties,
+            w: PhantomData,
+        }
+    }
+}
+
+impl<W: Semiring, F: Fst<W>, B: Borrow<F>> FstOp<W> for ComplementFstOp<W, F, B> {
+    fn compute_start(&self) -> Result<Option<StateId>> {
+        // The state 0 is a non-accepting sink of the input Fst which becomes
+        // an accepting sink in the complement.
+        match self.fst.borrow().start() {
+            None => Ok(Some(0)),
+            Some(s) => Ok(Some(s + 1)),
+        }
+    }
+
+    fn compute_trs(&self, state: StateId) -> Result<TrsVec<W>> {
+        let mut trs = if state == 0 {
+            vec![]
+        } else {
+            let trs_owner = self.fst.borrow().get_trs(state - 1)?;
+            let mut trs = Vec::with_capacity(trs_owner.trs().len() + 1);
+            for tr in trs_owner.trs() {
+                trs.push(Tr::new(tr.ilabel, tr.olabel, W::one(), tr.nextstate + 1));
+            }
+            trs
+        };
+        trs.push(Tr::new(RHO_LABEL, RHO_LABEL, W::one(), 0));
+        Ok(TrsVec(Arc::new(trs)))
+    }
+
+    fn compute_final_weight(&self, state: StateId) -> Result<Option<W>> {
+        if state == 0 || !self.fst.borrow().is_final(state - 1)? {
+            Ok(Some(W::one()))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn properties(&self) -> FstProperties {
+        self.properties
+    }
+}
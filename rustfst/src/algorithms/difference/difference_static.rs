@@ -0,0 +1,255 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+
+use crate::algorithms::compose::compose_filters::SequenceComposeFilterBuilder;
+use crate::algorithms::compose::matchers::{
+    MatchType, Matcher, MatcherRewriteMode, RhoMatcher, SortedMatcher,
+};
+use crate::algorithms::compose::{is_acceptor, ComposeFst, ComposeFstOpOptions};
+use crate::algorithms::difference::{ComplementFst, RHO_LABEL};
+use crate::fst_impls::VectorFst;
+use crate::fst_traits::{AllocableFst, ExpandedFst, MutableFst};
+use crate::semirings::Semiring;
+
+/// Configuration for the difference algorithm.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DifferenceConfig {
+    /// Whether to connect the resulting FST.
+    pub connect: bool,
+}
+
+impl Default for DifferenceConfig {
+    fn default() -> Self {
+        Self { connect: true }
+    }
+}
+
+/// This operation computes the difference between two FSAs.
+/// Only strings that are in the first automaton but not in the second are retained in the result.
+///
+/// The first argument must be an acceptor; the second argument must be an unweighted,
+/// epsilon-free, deterministic acceptor. The second argument must also be sorted on input labels
+/// and can't use `RHO_LABEL`. The weights of the first automaton are preserved.
+///
+/// # Example
+/// ```
+/// # #[macro_use] extern crate rustfst;
+/// # use anyhow::Result;
+/// # use rustfst::utils::acceptor;
+/// # use rustfst::semirings::{Semiring, TropicalWeight};
+/// # use rustfst::fst_impls::VectorFst;
+/// # use rustfst::fst_traits::Fst;
+/// # use rustfst::algorithms::union::union;
+/// # use rustfst::algorithms::difference::difference;
+/// # fn main() -> Result<()> {
+/// let mut lexicon : VectorFst<TropicalWeight> = fst![1, 2, 3];
+/// let word : VectorFst<TropicalWeight> = fst![1, 2];
+/// union(&mut lexicon, &word)?;
+///
+/// let blacklist : VectorFst<TropicalWeight> = fst![1, 2];
+///
+/// let filtered : VectorFst<TropicalWeight> = difference(&lexicon, &blacklist)?;
+/// let paths : Vec<_> = filtered.paths_iter().collect();
+/// assert_eq!(paths.len(), 1);
+/// assert_eq!(paths[0].ilabels, vec![1, 2, 3]);
+/// # Ok(())
+/// # }
+/// ```
+pub fn difference<W, F1, F2, F3>(fst1: &F1, fst2: &F2) -> Result<F3>
+where
+    W: Semiring,
+    F1: ExpandedFst<W>,
+    F2: ExpandedFst<W>,
+    F3: MutableFst<W> + AllocableFst<W>,
+{
+    difference_with_config(fst1, fst2, DifferenceConfig::default())
+}
+
+/// This operation computes the difference between two FSAs.
+/// Only strings that are in the first automaton but not in the second are retained in the result.
+///
+/// The first argument must be an acceptor; the second argument must be an unweighted,
+/// epsilon-free, deterministic acceptor. The second argument must also be sorted on input labels
+/// and can't use `RHO_LABEL`.
+pub fn difference_with_config<W, F1, F2, F3>(
+    fst1: &F1,
+    fst2: &F2,
+    config: DifferenceConfig,
+) -> Result<F3>
+where
+    W: Semiring,
+    F1: ExpandedFst<W>,
+    F2: ExpandedFst<W>,
+    F3: MutableFst<W> + AllocableFst<W>,
+{
+    if !is_acceptor(fst1)? {
+        bail!("Difference: 1st argument not an acceptor")
+    }
+
+    // The complement is linear in the size of the second FST. It is expanded
+    // once here so that it can be borrowed by the composition.
+    let cfst: VectorFst<W> = ComplementFst::<_, F2, _>::new(fst2)?.compute()?;
+
+    let matcher1 = SortedMatcher::new(fst1, MatchType::MatchNone)?;
+    let matcher2 = RhoMatcher::new(
+        MatchType::MatchInput,
+        RHO_LABEL,
+        MatcherRewriteMode::MatcherRewriteAuto,
+        Arc::new(SortedMatcher::new(&cfst, MatchType::MatchInput)?),
+    )?;
+    let opts = ComposeFstOpOptions::new(matcher1, matcher2, None, None);
+
+    let mut ofst: F3 = ComposeFst::<
+        _,
+        F1,
+        VectorFst<W>,
+        &F1,
+        &VectorFst<W>,
+        _,
+        _,
+        SequenceComposeFilterBuilder<_, _, _, _, _, _, _>,
+    >::new_with_options(fst1, &cfst, opts)?
+    .compute()?;
+
+    if config.connect {
+        crate::algorithms::connect(&mut ofst)?;
+    }
+
+    Ok(ofst)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::difference::DifferenceFst;
+    use crate::algorithms::union::union;
+    use crate::fst_properties::{compute_fst_properties, FstProperties};
+    use crate::fst_traits::{CoreFst, Fst};
+    use crate::semirings::TropicalWeight;
+    use crate::utils::{acceptor, transducer};
+    use crate::Label;
+
+    fn sorted_paths(fst: &VectorFst<TropicalWeight>) -> Vec<(Vec<Label>, TropicalWeight)> {
+        let mut paths: Vec<_> = fst.paths_iter().map(|p| (p.ilabels, p.weight)).collect();
+        paths.sort_by(|a, b| a.0.cmp(&b.0));
+        paths
+    }
+
+    fn lexicon() -> Result<VectorFst<TropicalWeight>> {
+        let mut fst: VectorFst<_> = acceptor(&[1, 2, 3], TropicalWeight::new(1.0));
+        union(
+            &mut fst,
+            &acceptor::<_, VectorFst<_>>(&[1, 2], TropicalWeight::new(2.0)),
+        )?;
+        union(
+            &mut fst,
+            &acceptor::<_, VectorFst<_>>(&[3], TropicalWeight::new(3.0)),
+        )?;
+        union(
+            &mut fst,
+            &acceptor::<_, VectorFst<_>>(&[], TropicalWeight::new(4.0)),
+        )?;
+        Ok(fst)
+    }
+
+    fn blacklist() -> Result<VectorFst<TropicalWeight>> {
+        // Accepts "1 2" and "3".
+        let mut fst = VectorFst::new();
+        fst.add_states(4);
+        fst.set_start(0)?;
+        fst.emplace_tr(0, 1, 1, TropicalWeight::one(), 1)?;
+        fst.emplace_tr(0, 3, 3, TropicalWeight::one(), 3)?;
+        fst.emplace_tr(1, 2, 2, TropicalWeight::one(), 2)?;
+        fst.set_final(2, TropicalWeight::one())?;
+        fst.set_final(3, TropicalWeight::one())?;
+        Ok(fst)
+    }
+
+    #[test]
+    fn test_difference() -> Result<()> {
+        let fst: VectorFst<_> = difference(&lexicon()?, &blacklist()?)?;
+        assert_eq!(
+            sorted_paths(&fst),
+            vec![
+                (vec![], TropicalWeight::new(4.0)),
+                (vec![1, 2, 3], TropicalWeight::new(1.0)),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_difference_lazy() -> Result<()> {
+        let lexicon = lexicon()?;
+        let static_fst: VectorFst<_> = difference(&lexicon, &blacklist()?)?;
+        let lazy_fst = DifferenceFst::<_, VectorFst<_>, VectorFst<_>, _, _>::new(
+            Arc::new(lexicon),
+            Arc::new(blacklist()?),
+        )?;
+        let lazy_fst: VectorFst<_> = lazy_fst.compute()?;
+        assert_eq!(sorted_paths(&static_fst), sorted_paths(&lazy_fst));
+        assert!(lazy_fst.properties().contains(FstProperties::ACCEPTOR));
+        Ok(())
+    }
+
+    #[test]
+    fn test_difference_unknown_acceptor_property() -> Result<()> {
+        let mut lexicon = lexicon()?;
+        let mask = FstProperties::ACCEPTOR | FstProperties::NOT_ACCEPTOR;
+        lexicon.set_properties_with_mask(FstProperties::empty(), mask);
+        assert!(!lexicon.properties().intersects(mask));
+
+        let static_fst: VectorFst<_> = difference(&lexicon, &blacklist()?)?;
+        let lazy_fst = DifferenceFst::<_, VectorFst<_>, VectorFst<_>, _, _>::new(
+            Arc::new(lexicon),
+            Arc::new(blacklist()?),
+        )?;
+        let lazy_fst: VectorFst<_> = lazy_fst.compute()?;
+        assert_eq!(sorted_paths(&static_fst), sorted_paths(&lazy_fst));
+        Ok(())
+    }
+
+    #[test]
+    fn test_complement_properties() -> Result<()> {
+        let mut blacklist = blacklist()?;
+        blacklist.compute_and_update_properties_all()?;
+        assert!(blacklist
+            .properties()
+            .contains(FstProperties::I_LABEL_SORTED | FstProperties::ACCESSIBLE));
+
+        let complement = ComplementFst::<_, VectorFst<_>, _>::new(&blacklist)?;
+        let props = complement.properties();
+        assert!(props.contains(FstProperties::I_LABEL_SORTED | FstProperties::O_LABEL_SORTED));
+
+        let cfst: VectorFst<TropicalWeight> = complement.compute()?;
+        let mut known = FstProperties::empty();
+        let computed =
+            compute_fst_properties(&cfst, FstProperties::all_properties(), &mut known, false)?;
+        assert!(
+            computed.contains(props),
+            "Computed props = {:?}\nProps = {:?}",
+            computed,
+            props
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_difference_errors() -> Result<()> {
+        let fst_transducer: VectorFst<TropicalWeight> =
+            transducer(&[1, 2], &[2, 3], TropicalWeight::one());
+        let fst_weighted: VectorFst<TropicalWeight> = acceptor(&[1], TropicalWeight::new(2.0));
+        assert!(difference::<_, _, _, VectorFst<_>>(&fst_transducer, &blacklist()?).is_err());
+        assert!(difference::<_, _, _, VectorFst<_>>(&lexicon()?, &fst_weighted).is_err());
+
+        let fst_rho: VectorFst<TropicalWeight> = acceptor(&[RHO_LABEL], TropicalWeight::one());
+        assert!(difference::<_, _, _, VectorFst<_>>(&lexicon()?, &fst_rho).is_err());
+        assert!(DifferenceFst::<_, VectorFst<_>, VectorFst<_>, _, _>::new(
+            Arc::new(lexicon()?),
+            Arc::new(fst_rho)
+        )
+        .is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,14 @@
+mod complement_fst;
+mod complement_fst_op;
+mod difference_fst;
+mod difference_static;
+
+pub use complement_fst::ComplementFst;
+pub use difference_fst::DifferenceFst;
+pub use difference_static::{difference, difference_with_config, DifferenceConfig};
+
+use crate::Label;
+
+/// Label used by the `ComplementFst` to represent all the labels that
+/// don't have an explicit transition leaving a state.
+pub const RHO_LABEL: Label = Label::MAX - 1;
@@ -0,0 +1,777 @@
+use std::collections::btree_map::Entry as EntryBTreeMap;
+use std::collections::hash_map::Entry as EntryHashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use anyhow::Result;
+
+use crate::algorithms::determinize::{
+    CommonDivisor, DefaultCommonDivisor, DeterminizeElement, DeterminizeStateTable,
+    DeterminizeStateTuple, DeterminizeTr, GallicCommonDivisor, WeightedSubset,
+};
+use crate::algorithms::factor_weight::factor_iterators::GallicFactorRestrict;
+use crate::algorithms::factor_weight::{factor_weight, FactorWeightOptions, FactorWeightType};
+use crate::algorithms::lazy::{FstOp, LazyFst, SimpleVecCache};
+use crate::algorithms::prune::{prune_to_with_projection, PruneConfig};
+use crate::algorithms::tr_compares::ILabelCompare;
+use crate::algorithms::union_find::UnionFind;
+use crate::algorithms::weight_converters::{FromGallicConverter, ToGallicConverter};
+use crate::algorithms::{
+    connect, fst_convert_from_ref, shortest_distance_with_config, tr_sort, weight_convert,
+    ShortestDistanceConfig,
+};
+use crate::fst_impls::VectorFst;
+use crate::fst_properties::FstProperties;
+use crate::fst_traits::{AllocableFst, CoreFst, ExpandedFst, MutableFst};
+use crate::semirings::{
+    DivideType, GallicWeightRestrict, Semiring, SemiringProperties, WeaklyDivisibleSemiring,
+    WeightQuantize,
+};
+use crate::{Label, StateId, Tr, Trs, TrsVec, EPS_LABEL, KDELTA};
+
+/// Identifies a transition by its source state and its position among the
+/// trs of this state. `None` stands for the super-final transition.
+type TrId = (StateId, Option<usize>);
+
+/// Pairs of ambiguous candidates along with the states to merge.
+type Ambiguities = (Vec<(TrId, TrId)>, Option<UnionFind>);
+
+/// Configuration for disambiguation.
+#[derive(Debug, Clone, PartialOrd, PartialEq)]
+pub struct DisambiguateConfig<W: Semiring> {
+    /// Quantization delta used to compare the subsets built during the determinization.
+    pub delta: f32,
+    /// Pruning weight threshold. `W::zero()` disables it.
+    pub weight_threshold: W,
+    /// Maximum number of states to keep in the output.
+    pub state_threshold: Option<StateId>,
+}
+
+impl<W: Semiring> Default for DisambiguateConfig<W> {
+    fn default() -> Self {
+        Self {
+            delta: KDELTA,
+            weight_threshold: W::zero(),
+            state_threshold: None,
+        }
+    }
+}
+
+impl<W: Semiring> DisambiguateConfig<W> {
+    pub fn new(delta: f32, weight_threshold: W, state_threshold: Option<StateId>) -> Self {
+        Self {
+            delta,
+            weight_threshold,
+            state_threshold,
+        }
+    }
+
+    pub fn with_delta(self, delta: f32) -> Self {
+        Self { delta, ..self }
+    }
+
+    pub fn with_weight_threshold(self, weight_threshold: W) -> Self {
+        Self {
+            weight_threshold,
+            ..self
+        }
+    }
+
+    pub fn with_state_threshold(self, state_threshold: Option<StateId>) -> Self {
+        Self {
+            state_threshold,
+            ..self
+        }
+    }
+}
+
+/// Computes the pairs of states (s1, s2) such that s1 and s2 can be reached from the
+/// start state with the same string and a final state can be reached from both of them
+/// with the same string.
+fn common_future<W: Semiring>(fst: &VectorFst<W>) -> Result<HashSet<(StateId, StateId)>> {
+    let start = match fst.start() {
+        Some(s) => s,
+        None => return Ok(HashSet::new()),
+    };
+
+    let mut pairs = vec![(start, start)];
+    let mut pair_ids = HashMap::new();
+    pair_ids.insert((start, start), 0);
+    let mut predecessors: Vec<Vec<usize>> = vec![vec![]];
+    let mut queue = VecDeque::new();
+    queue.push_back(0);
+
+    while let Some(pair_id) = queue.pop_front() {
+        let (s1, s2) = pairs[pair_id];
+        let trs2 = fst.get_trs(s2)?;
+        for tr1 in fst.get_trs(s1)?.trs() {
+            for tr2 in trs2.trs().iter().filter(|tr2| tr2.ilabel == tr1.ilabel) {
+                let next_pair = (tr1.nextstate, tr2.nextstate);
+                let next_pair_id = match pair_ids.entry(next_pair) {
+                    EntryHashMap::Occupied(e) => *e.get(),
+                    EntryHashMap::Vacant(e) => {
+                        let n = pairs.len();
+                        e.insert(n);
+                        pairs.push(next_pair);
+                        predecessors.push(vec![]);
+                        queue.push_back(n);
+                        n
+                    }
+                };
+                predecessors[next_pair_id].push(pair_id);
+            }
+        }
+    }
+
+    let mut coaccess = vec![false; pairs.len()];
+    let mut stack = vec![];
+    for (pair_id, (s1, s2)) in pairs.iter().enumerate() {
+        if fst.is_final(*s1)? && fst.is_final(*s2)? {
+            coaccess[pair_id] = true;
+            stack.push(pair_id);
+        }
+    }
+    while let Some(pair_id) = stack.pop() {
+        for &pred in &predecessors[pair_id] {
+            if !coaccess[pred] {
+                coaccess[pred] = true;
+                stack.push(pred);
+            }
+        }
+    }
+
+    Ok(pairs
+        .into_iter()
+        .zip(coaccess)
+        .filter(|(_, c)| *c)
+        .map(|(pair, _)| pair)
+        .collect())
+}
+
+fn norm_tr<W, CD>(det_tr: &mut DeterminizeTr<W>, delta: f32) -> Result<()>
+where
+    W: WeaklyDivisibleSemiring + WeightQuantize,
+    CD: CommonDivisor<W>,
+{
+    let mut weights: BTreeMap<StateId, W> = BTreeMap::new();
+    for dest_elt in det_tr.dest_tuple.subset.pairs.drain(..) {
+        det_tr.weight = CD::common_divisor(&det_tr.weight, &dest_elt.weight)?;
+        match weights.entry(dest_elt.state) {
+            EntryBTreeMap::Vacant(e) => {
+                e.insert(dest_elt.weight);
+            }
+            EntryBTreeMap::Occupied(mut e) => {
+                e.get_mut().plus_assign(&dest_elt.weight)?;
+            }
+        };
+    }
+
+    let mut pairs = Vec::with_capacity(weights.len());
+    for (state, weight) in weights {
+        let mut weight = weight.divide(&det_tr.weight, DivideType::DivideLeft)?;
+        weight.quantize_assign(delta)?;
+        pairs.push(DeterminizeElement::new(state, weight));
+    }
+    det_tr.dest_tuple.subset.pairs = pairs;
+    Ok(())
+}
+
+/// Determinizes the FSA w.r.t. the common future relation : each state of the result
+/// has a head state in the input FSA and its subset only contains states that share
+/// a common future with this head. The states are expanded lazily so that the result
+/// can be pruned while it is built.
+#[derive(Debug)]
+struct PreDisambiguateOp<'a, W: Semiring, CD> {
+    fst: &'a VectorFst<W>,
+    related: HashSet<(StateId, StateId)>,
+    state_table: DeterminizeStateTable<W, Vec<W>>,
+    delta: f32,
+    ghost: PhantomData<CD>,
+}
+
+impl<'a, W, CD> PreDisambiguateOp<'a, W, CD>
+where
+    W: WeaklyDivisibleSemiring + WeightQuantize,
+    CD: CommonDivisor<W>,
+{
+    fn new(fst: &'a VectorFst<W>, delta: f32) -> Result<Self> {
+        Ok(Self {
+            fst,
+            related: common_future(fst)?,
+            state_table: DeterminizeStateTable::new(None),
+            delta,
+            ghost: PhantomData,
+        })
+    }
+
+    /// Head state in the input FSA of a state of the result.
+    fn head(&self, state: StateId) -> StateId {
+        self.state_table.find_tuple(state).filter_state
+    }
+}
+
+impl<'a, W, CD> FstOp<W> for PreDisambiguateOp<'a, W, CD>
+where
+    W: WeaklyDivisibleSemiring + WeightQuantize,
+    CD: CommonDivisor<W>,
+{
+    fn compute_start(&self) -> Result<Option<StateId>> {
+        match self.fst.start() {
+            Some(start) => {
+                let start_tuple = DeterminizeStateTuple {
+                    subset: WeightedSubset::from_vec(vec![DeterminizeElement::new(
+                        start,
+                        W::one(),
+                    )]),
+                    filter_state: start,
+                };
+                Ok(Some(self.state_table.find_id_from_ref(&start_tuple)?))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn compute_trs(&self, state: StateId) -> Result<TrsVec<W>> {
+        let tuple = self.state_table.find_tuple(state);
+        let head = tuple.filter_state;
+
+        // One determinized transition per (label, nextstate) leaving the head state. An
+        // element is added to the destination subset only if it shares a common future
+        // with the destination head.
+        let mut label_map: BTreeMap<Label, Vec<DeterminizeTr<W>>> = BTreeMap::new();
+        for tr in self.fst.get_trs(head)?.trs() {
+            let det_trs = label_map.entry(tr.ilabel).or_default();
+            if det_trs
+                .iter()
+                .all(|det_tr| det_tr.dest_tuple.filter_state != tr.nextstate)
+            {
+                det_trs.push(DeterminizeTr::from_tr(tr, tr.nextstate));
+            }
+        }
+        for src_elt in tuple.subset.iter() {
+            for tr in self.fst.get_trs(src_elt.state)?.trs() {
+                if let Some(det_trs) = label_map.get_mut(&tr.ilabel) {
+                    let weight = src_elt.weight.times(&tr.weight)?;
+                    for det_tr in det_trs.iter_mut() {
+                        if self
+                            .related
+                            .contains(&(tr.nextstate, det_tr.dest_tuple.filter_state))
+                        {
+                            det_tr
+                                .dest_tuple
+                                .subset
+                                .pairs
+                                .push(DeterminizeElement::new(tr.nextstate, weight.clone()));
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut trs = vec![];
+        for mut det_tr in label_map.into_values().flatten() {
+            norm_tr::<W, CD>(&mut det_tr, self.delta)?;
+            let nextstate = self.state_table.find_id_from_ref(&det_tr.dest_tuple)?;
+            trs.push(Tr::new(
+                det_tr.label,
+                det_tr.label,
+                det_tr.weight,
+                nextstate,
+            ));
+        }
+        trs.sort_by_key(|tr| (tr.ilabel, tr.nextstate));
+        Ok(TrsVec(Arc::new(trs)))
+    }
+
+    fn compute_final_weight(&self, state: StateId) -> Result<Option<W>> {
+        let tuple = self.state_table.find_tuple(state);
+        // Only the states whose head is final can be final.
+        if !self.fst.is_final(tuple.filter_state)? {
+            return Ok(None);
+        }
+        let mut final_weight = W::zero();
+        for elt in tuple.subset.iter() {
+            if let Some(w) = self.fst.final_weight(elt.state)? {
+                final_weight.plus_assign(elt.weight.times(w)?)?;
+            }
+        }
+        if final_weight.is_zero() {
+            Ok(None)
+        } else {
+            Ok(Some(final_weight))
+        }
+    }
+
+    fn properties(&self) -> FstProperties {
+        FstProperties::ACCEPTOR
+    }
+}
+
+/// Pre-disambiguates the FSA, see `PreDisambiguateOp`. Returns the resulting FSA along
+/// with the head of each of its states.
+///
+/// When `prune` is set, the result is pruned while it is expanded. The thresholds are
+/// applied on the weights projected by `project` and the distances are those from
+/// each state of `fst` to its final states in the projected semiring.
+fn pre_disambiguate<W, CD, P, M>(
+    fst: &VectorFst<W>,
+    delta: f32,
+    prune: Option<(PruneConfig<P>, Vec<P>)>,
+    project: M,
+) -> Result<(VectorFst<W>, Vec<StateId>)>
+where
+    W: WeaklyDivisibleSemiring + WeightQuantize,
+    CD: CommonDivisor<W>,
+    P: Semiring,
+    M: Fn(&W) -> P,
+{
+    let op = PreDisambiguateOp::<W, CD>::new(fst, delta)?;
+    let lazy_fst: LazyFst<W, _, SimpleVecCache<W>> =
+        LazyFst::from_op_and_cache(&op, SimpleVecCache::default(), None, None);
+    match prune {
+        Some((prune_config, in_dist)) => {
+            let fdistance = |state: StateId| -> Result<P> {
+                let mut distance = P::zero();
+                for elt in op.state_table.find_tuple(state).subset.iter() {
+                    if let Some(d) = in_dist.get(elt.state as usize) {
+                        distance.plus_assign(project(&elt.weight).times(d)?)?;
+                    }
+                }
+                Ok(distance)
+            };
+            let (ofst, origin): (VectorFst<W>, _) =
+                prune_to_with_projection(&lazy_fst, prune_config, fdistance, &project)?;
+            let heads = origin.into_iter().map(|s| op.head(s)).collect();
+            Ok((ofst, heads))
+        }
+        None => {
+            let ofst: VectorFst<W> = lazy_fst.compute()?;
+            let heads = (0..ofst.num_states() as StateId)
+                .map(|s| op.head(s))
+                .collect();
+            Ok((ofst, heads))
+        }
+    }
+}
+
+/// Finds the pairs of ambiguous candidates (a, b) : a and b have the same label and the
+/// same destination, their source states are coreachable and the head of the source of
+/// `a` is larger than the head of the source of `b`. Also returns the states to merge
+/// because they have been split by quantization issues.
+fn find_ambiguities<W: Semiring>(fst: &VectorFst<W>, heads: &[StateId]) -> Result<Ambiguities> {
+    let mut candidates = vec![];
+    let mut merge: Option<UnionFind> = None;
+    let start = match fst.start() {
+        Some(s) => s,
+        None => return Ok((candidates, merge)),
+    };
+
+    let candidate = |s1: StateId, s2: StateId, a1: TrId, a2: TrId| {
+        if heads[s1 as usize] > heads[s2 as usize] {
+            (a1, a2)
+        } else {
+            (a2, a1)
+        }
+    };
+
+    let mut coreachable = HashSet::new();
+    coreachable.insert((start, start));
+    let mut queue = VecDeque::new();
+    queue.push_back((start, start));
+
+    while let Some((s1, s2)) = queue.pop_front() {
+        let trs2 = fst.get_trs(s2)?;
+        for (pos1, tr1) in fst.get_trs(s1)?.trs().iter().enumerate() {
+            for (pos2, tr2) in trs2.trs().iter().enumerate() {
+                if tr2.ilabel != tr1.ilabel {
+                    continue;
+                }
+                if s1 != s2 && tr1.nextstate == tr2.nextstate {
+                    candidates.push(candidate(s1, s2, (s1, Some(pos1)), (s2, Some(pos2))));
+                }
+                let pair = if tr1.nextstate <= tr2.nextstate {
+                    (tr1.nextstate, tr2.nextstate)
+                } else {
+                    (tr2.nextstate, tr1.nextstate)
+                };
+                if coreachable.insert(pair) {
+                    // Only possible if the state has been split by quantization issues.
+                    if pair.0 != pair.1 && heads[pair.0 as usize] == heads[pair.1 as usize] {
+                        merge
+                            .get_or_insert_with(|| UnionFind::new(fst.num_states()))
+                            .union(pair.0, pair.1);
+                    } else {
+                        queue.push_back(pair);
+                    }
+                }
+            }
+        }
+        if s1 != s2 && fst.is_final(s1)? && fst.is_final(s2)? {
+            candidates.push(candidate(s1, s2, (s1, None), (s2, None)));
+        }
+    }
+
+    Ok((candidates, merge))
+}
+
+/// Marks the ambiguous transitions to remove : for each pair of candidates (a, b),
+/// `a` is removed unless `b` is. The candidates are visited by increasing head of the
+/// source of `a`, so that `b` is always decided before `a`.
+fn mark_ambiguities(mut candidates: Vec<(TrId, TrId)>, heads: &[StateId]) -> BTreeSet<TrId> {
+    candidates.sort_by_key(|(a, _)| (heads[a.0 as usize], *a));
+    let mut ambiguous = BTreeSet::new();
+    for (a, b) in candidates {
+        if !ambiguous.contains(&b) {
+            ambiguous.insert(a);
+        }
+    }
+    ambiguous
+}
+
+fn disambiguate_fsa<W, CD, P, M>(
+    fst: &VectorFst<W>,
+    delta: f32,
+    prune: Option<(PruneConfig<P>, Vec<P>)>,
+    project: M,
+) -> Result<VectorFst<W>>
+where
+    W: WeaklyDivisibleSemiring + WeightQuantize,
+    CD: CommonDivisor<W>,
+    P: Semiring,
+    M: Fn(&W) -> P,
+{
+    let (mut ofst, heads) = pre_disambiguate::<W, CD, P, M>(fst, delta, prune, project)?;
+    let (mut candidates, merge) = find_ambiguities(&ofst, &heads)?;
+
+    // Merges the split states to remove spurious ambiguities.
+    if let Some(mut merge) = merge {
+        for state in 0..(ofst.num_states() as StateId) {
+            let mut it_tr = ofst.tr_iter_mut(state)?;
+            for idx in 0..it_tr.len() {
+                let nextstate = it_tr[idx].nextstate;
+                it_tr.set_nextstate(idx, merge.find_set(nextstate))?;
+            }
+        }
+        let (new_candidates, new_merge) = find_ambiguities(&ofst, &heads)?;
+        if new_merge.is_some() {
+            bail!("Disambiguate: unable to remove spurious ambiguities");
+        }
+        candidates = new_candidates;
+    }
+
+    // Redirects the ambiguous transitions to a dead state removed by connect.
+    let ambiguous = mark_ambiguities(candidates, &heads);
+    let dead = ofst.add_state();
+    for (state, pos) in ambiguous {
+        match pos {
+            Some(pos) => ofst.tr_iter_mut(state)?.set_nextstate(pos, dead)?,
+            None => ofst.delete_final_weight(state)?,
+        };
+    }
+    connect(&mut ofst)?;
+    Ok(ofst)
+}
+
+/// Returns an equivalent FST with the property that there are no two distinct paths
+/// from the initial state to a final state with the same input labeling. Contrary to
+/// `determinize`, the result is not required to be deterministic.
+///
+/// The weights must be (weakly) left divisible, which is the case for the Tropical and
+/// the Log semirings. Transducers must be functional. The pruning options require the
+/// weight to have the path property. They are applied during the determinization step,
+/// before the ambiguous paths are removed.
+///
+/// The algorithm is described in : Mohri, M. and Riley, M. 2015. On the disambiguation
+/// of weighted automata. In CIAA, pages 263-278.
+pub fn disambiguate_with_config<W, F1, F2>(ifst: &F1, config: DisambiguateConfig<W>) -> Result<F2>
+where
+    W: WeaklyDivisibleSemiring + WeightQuantize,
+    F1: ExpandedFst<W>,
+    F2: MutableFst<W> + AllocableFst<W>,
+{
+    if !W::properties().contains(SemiringProperties::LEFT_SEMIRING) {
+        bail!("Disambiguate: weight must be left distributive")
+    }
+    let do_prune = !config.weight_threshold.is_zero() || config.state_threshold.is_some();
+    if do_prune && !W::properties().contains(SemiringProperties::PATH) {
+        bail!("Disambiguate: weight must have the path property to use pruning options")
+    }
+
+    let mut sfst: VectorFst<W> = fst_convert_from_ref(ifst);
+    connect(&mut sfst)?;
+    tr_sort(&mut sfst, ILabelCompare {});
+
+    // The thresholds are applied while pre-disambiguating, which bounds the size of
+    // the FSA in which the ambiguities are searched.
+    let prune = if do_prune {
+        let in_dist =
+            shortest_distance_with_config(&sfst, true, ShortestDistanceConfig::new(config.delta))?;
+        let prune_config = PruneConfig::default()
+            .with_weight_threshold(config.weight_threshold)
+            .with_state_threshold(config.state_threshold)
+            .with_delta(config.delta);
+        Some((prune_config, in_dist))
+    } else {
+        None
+    };
+
+    let props = sfst.compute_and_update_properties(FstProperties::ACCEPTOR)?;
+    let mut ofst: F2 = if props.contains(FstProperties::ACCEPTOR) {
+        let dfst = disambiguate_fsa::<_, DefaultCommonDivisor, _, _>(
+            &sfst,
+            config.delta,
+            prune,
+            |w: &W| w.clone(),
+        )?;
+        fst_convert_from_ref(&dfst)
+    } else {
+        let mut to_gallic = ToGallicConverter {};
+        let fsa: VectorFst<GallicWeightRestrict<W>> = weight_convert(&sfst, &mut to_gallic)?;
+        let dfsa = disambiguate_fsa::<_, GallicCommonDivisor, _, _>(
+            &fsa,
+            config.delta,
+            prune,
+            |w: &GallicWeightRestrict<W>| w.value2().clone(),
+        )?;
+
+        let factor_opts = FactorWeightOptions {
+            delta: KDELTA,
+            mode: FactorWeightType::FACTOR_FINAL_WEIGHTS,
+            final_ilabel: EPS_LABEL,
+            final_olabel: EPS_LABEL,
+            increment_final_ilabel: false,
+            increment_final_olabel: false,
+        };
+        let factored_dfsa: VectorFst<GallicWeightRestrict<W>> =
+            factor_weight::<_, VectorFst<GallicWeightRestrict<W>>, _, _, GallicFactorRestrict<W>>(
+                &dfsa,
+                factor_opts,
+            )?;
+
+        let mut from_gallic = FromGallicConverter {
+            superfinal_label: EPS_LABEL,
+        };
+        weight_convert(&factored_dfsa, &mut from_gallic)?
+    };
+
+    ofst.set_symts_from_fst(ifst);
+    Ok(ofst)
+}
+
+/// Returns an equivalent FST with the property that there are no two distinct paths
+/// from the initial state to a final state with the same input labeling. Among the
+/// ambiguous paths, the one with the best weight is kept.
+///
+/// # Example
+/// ```
+/// # use anyhow::Result;
+/// # use rustfst::semirings::{Semiring, TropicalWeight};
+/// # use rustfst::fst_impls::VectorFst;
+/// # use rustfst::fst_traits::{CoreFst, Fst, MutableFst};
+/// # use rustfst::algorithms::disambiguate;
+/// # use rustfst::Tr;
+/// # fn main() -> Result<()> {
+/// let mut fst = VectorFst::<TropicalWeight>::new();
+/// let s0 = fst.add_state();
+/// let s1 = fst.add_state();
+/// let s2 = fst.add_state();
+/// fst.set_start(s0)?;
+/// fst.add_tr(s0, Tr::new(1, 1, TropicalWeight::new(1.0), s1))?;
+/// fst.add_tr(s0, Tr::new(1, 1, TropicalWeight::new(2.0), s1))?;
+/// fst.add_tr(s1, Tr::new(2, 2, TropicalWeight::new(1.0), s2))?;
+/// fst.set_final(s2, TropicalWeight::one())?;
+///
+/// let fst_disamb: VectorFst<_> = disambiguate(&fst)?;
+///
+/// let paths: Vec<_> = fst_disamb.paths_iter().collect();
+/// assert_eq!(paths.len(), 1);
+/// assert_eq!(paths[0].weight, TropicalWeight::new(2.0));
+/// # Ok(())
+/// # }
+/// ```
+pub fn disambiguate<W, F1, F2>(ifst: &F1) -> Result<F2>
+where
+    W: WeaklyDivisibleSemiring + WeightQuantize,
+    F1: ExpandedFst<W>,
+    F2: MutableFst<W> + AllocableFst<W>,
+{
+    disambiguate_with_config(ifst, DisambiguateConfig::default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::HashMap;
+
+    use crate::algorithms::compose::compose;
+    use crate::algorithms::shortest_distance;
+    use crate::fst_traits::Fst;
+    use crate::semirings::{LogWeight, TropicalWeight};
+    use crate::utils::acceptor;
+
+    fn build_fst<W: Semiring<Type = f32>>() -> Result<VectorFst<W>> {
+        // Two paths for "1 2" and two paths for "1 3".
+        let mut fst = VectorFst::<W>::new();
+        let s0 = fst.add_state();
+        let s1 = fst.add_state();
+        let s2 = fst.add_state();
+        let s3 = fst.add_state();
+        fst.set_start(s0)?;
+        fst.add_tr(s0, Tr::new(1, 1, W::new(1.0), s1))?;
+        fst.add_tr(s0, Tr::new(1, 1, W::new(2.0), s2))?;
+        fst.add_tr(s1, Tr::new(2, 2, W::new(2.0), s3))?;
+        fst.add_tr(s2, Tr::new(2, 2, W::new(0.5), s3))?;
+        fst.add_tr(s1, Tr::new(3, 3, W::new(1.0), s3))?;
+        fst.add_tr(s2, Tr::new(3, 3, W::new(3.0), s3))?;
+        fst.add_tr(s2, Tr::new(4, 4, W::new(1.0), s3))?;
+        fst.set_final(s3, W::new(0.5))?;
+        Ok(fst)
+    }
+
+    // Maps each input string to the weights of the paths accepting it.
+    fn paths_per_string<W: Semiring>(fst: &VectorFst<W>) -> HashMap<Vec<Label>, Vec<W>> {
+        let mut res: HashMap<_, Vec<_>> = HashMap::new();
+        for path in fst.paths_iter() {
+            res.entry(path.ilabels).or_default().push(path.weight);
+        }
+        res
+    }
+
+    // Sum of the weights of the paths of `fst` accepting `string`.
+    fn string_weight<W: Semiring>(fst: &VectorFst<W>, string: &[Label]) -> Result<W> {
+        let string_fst: VectorFst<W> = acceptor(string, W::one());
+        let mut fst = fst.clone();
+        tr_sort(&mut fst, ILabelCompare {});
+        let cfst: VectorFst<W> =
+            compose::<_, VectorFst<_>, VectorFst<_>, _, _, _>(&string_fst, &fst)?;
+        match cfst.start() {
+            Some(start) => Ok(shortest_distance(&cfst, true)?[start as usize].clone()),
+            None => Ok(W::zero()),
+        }
+    }
+
+    #[test]
+    fn test_disambiguate_tropical() -> Result<()> {
+        let fst = build_fst::<TropicalWeight>()?;
+        let fst_res: VectorFst<TropicalWeight> = disambiguate(&fst)?;
+
+        let paths = paths_per_string(&fst_res);
+        assert_eq!(paths.len(), 3);
+        assert_eq!(paths[&vec![1, 2]], vec![TropicalWeight::new(3.0)]);
+        assert_eq!(paths[&vec![1, 3]], vec![TropicalWeight::new(2.5)]);
+        assert_eq!(paths[&vec![1, 4]], vec![TropicalWeight::new(3.5)]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_disambiguate_log() -> Result<()> {
+        let fst = build_fst::<LogWeight>()?;
+        let fst_res: VectorFst<LogWeight> = disambiguate(&fst)?;
+
+        let paths = paths_per_string(&fst_res);
+        assert_eq!(paths.len(), 3);
+        for (string, weights) in paths.iter() {
+            assert_eq!(weights.len(), 1);
+            // The weight of each string is the sum of the weights of its ambiguous paths, up
+            // to the quantization of the residual weights.
+            let expected = string_weight(&fst, string)?;
+            assert!(
+                weights[0].approx_equal(&expected, 1e-2),
+                "{:?} : {:?} != {:?}",
+                string,
+                weights[0],
+                expected
+            );
+        }
+        assert_eq!(paths[&vec![1, 4]], vec![LogWeight::new(3.5)]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_disambiguate_transducer() -> Result<()> {
+        let mut fst = VectorFst::<TropicalWeight>::new();
+        let s0 = fst.add_state();
+        let s1 = fst.add_state();
+        let s2 = fst.add_state();
+        let s3 = fst.add_state();
+        let s4 = fst.add_state();
+        fst.set_start(s0)?;
+        fst.add_tr(s0, Tr::new(1, 5, TropicalWeight::new(1.0), s1))?;
+        fst.add_tr(s0, Tr::new(1, EPS_LABEL, TropicalWeight::new(0.5), s2))?;
+        fst.add_tr(s1, Tr::new(2, EPS_LABEL, TropicalWeight::new(1.0), s3))?;
+        fst.add_tr(s2, Tr::new(2, 5, TropicalWeight::new(2.0), s3))?;
+        fst.add_tr(s3, Tr::new(3, 6, TropicalWeight::one(), s4))?;
+        fst.set_final(s4, TropicalWeight::one())?;
+
+        let fst_res: VectorFst<TropicalWeight> = disambiguate(&fst)?;
+        let paths: Vec<_> = fst_res.paths_iter().collect();
+        assert_eq!(paths.len(), 1);
+        assert_eq!(paths[0].ilabels, vec![1, 2, 3]);
+        assert_eq!(paths[0].olabels, vec![5, 6]);
+        assert_eq!(paths[0].weight, TropicalWeight::new(2.0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_disambiguate_prune() -> Result<()> {
+        let fst = build_fst::<TropicalWeight>()?;
+        let config = DisambiguateConfig::default().with_weight_threshold(TropicalWeight::new(0.7));
+        let fst_res: VectorFst<TropicalWeight> = disambiguate_with_config(&fst, config)?;
+
+        let paths = paths_per_string(&fst_res);
+        assert_eq!(paths.len(), 2);
+        assert_eq!(paths[&vec![1, 2]], vec![TropicalWeight::new(3.0)]);
+        assert_eq!(paths[&vec![1, 3]], vec![TropicalWeight::new(2.5)]);
+
+        let config = DisambiguateConfig::default().with_state_threshold(Some(3));
+        let fst_res: VectorFst<TropicalWeight> = disambiguate_with_config(&fst, config)?;
+        assert!(fst_res.num_states() <= 3);
+
+        let config = DisambiguateConfig::default().with_state_threshold(Some(4));
+        let fst_res: VectorFst<TropicalWeight> = disambiguate_with_config(&fst, config)?;
+        let fst_ref: VectorFst<TropicalWeight> = disambiguate(&fst)?;
+        assert_eq!(paths_per_string(&fst_res), paths_per_string(&fst_ref));
+        Ok(())
+    }
+
+    #[test]
+    fn test_disambiguate_transducer_prune() -> Result<()> {
+        let mut fst = VectorFst::<TropicalWeight>::new();
+        let s0 = fst.add_state();
+        let s1 = fst.add_state();
+        let s2 = fst.add_state();
+        let s3 = fst.add_state();
+        let s4 = fst.add_state();
+        fst.set_start(s0)?;
+        fst.add_tr(s0, Tr::new(1, 5, TropicalWeight::new(1.0), s1))?;
+        fst.add_tr(s0, Tr::new(1, EPS_LABEL, TropicalWeight::new(0.5), s2))?;
+        fst.add_tr(s0, Tr::new(4, 4, TropicalWeight::new(5.0), s4))?;
+        fst.add_tr(s1, Tr::new(2, EPS_LABEL, TropicalWeight::new(1.0), s3))?;
+        fst.add_tr(s2, Tr::new(2, 5, TropicalWeight::new(2.0), s3))?;
+        fst.add_tr(s3, Tr::new(3, 6, TropicalWeight::one(), s4))?;
+        fst.set_final(s4, TropicalWeight::one())?;
+
+        let config = DisambiguateConfig::default().with_weight_threshold(TropicalWeight::new(1.0));
+        let fst_res: VectorFst<TropicalWeight> = disambiguate_with_config(&fst, config)?;
+        let paths: Vec<_> = fst_res.paths_iter().collect();
+        assert_eq!(paths.len(), 1);
+        assert_eq!(paths[0].ilabels, vec![1, 2, 3]);
+        assert_eq!(paths[0].olabels, vec![5, 6]);
+        assert_eq!(paths[0].weight, TropicalWeight::new(2.0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_mark_ambiguities_shared_transition() {
+        // (a, b) and (b, c) are ambiguous pairs sharing b. The pair (b, c) must be
+        // decided first, otherwise both a and b are removed.
+        let a = (1, Some(0));
+        let b = (2, Some(0));
+        let c = (3, Some(0));
+        let heads = vec![0, 5, 3, 1];
+        let ambiguous = mark_ambiguities(vec![(a, b), (b, c)], &heads);
+        assert_eq!(ambiguous.into_iter().collect::<Vec<_>>(), vec![b]);
+    }
+}
@@ -0,0 +1,191 @@
+use anyhow::Result;
+
+use crate::algorithms::factor_weight::factor_iterators::GallicFactor;
+use crate::algorithms::factor_weight::{factor_weight, FactorWeightOptions, FactorWeightType};
+use crate::algorithms::rm_epsilon::rm_epsilon;
+use crate::algorithms::weight_converters::{FromGallicConverter, ToGallicConverter};
+use crate::algorithms::{fst_convert_from_ref, invert, weight_convert};
+use crate::fst_impls::VectorFst;
+use crate::fst_traits::{AllocableFst, ExpandedFst, MutableFst};
+use crate::semirings::{GallicWeight, WeightQuantize};
+use crate::{EPS_LABEL, KDELTA};
+
+/// Side on which the epsilons are normalized.
+#[derive(Debug, Clone, Copy, PartialOrd, PartialEq, Eq)]
+pub enum EpsNormalizeType {
+    /// No input epsilon transition is followed by a non-epsilon input transition.
+    Input,
+    /// No output epsilon transition is followed by a non-epsilon output transition.
+    Output,
+}
+
+/// Returns an equivalent FST that is epsilon-normalized. An FST is
+/// input-epsilon-normalized if, on every path, no input epsilon transition is followed
+/// by a non-epsilon input transition. An FST is output-epsilon-normalized if, on
+/// every path, no output epsilon transition is followed by a non-epsilon output
+/// transition.
+///
+/// # Example
+/// ```
+/// # use anyhow::Result;
+/// # use rustfst::semirings::{Semiring, TropicalWeight};
+/// # use rustfst::fst_impls::VectorFst;
+/// # use rustfst::fst_traits::{CoreFst, MutableFst};
+/// # use rustfst::algorithms::{epsnormalize, EpsNormalizeType};
+/// # use rustfst::{Tr, EPS_LABEL};
+/// # fn main() -> Result<()> {
+/// let mut fst = VectorFst::<TropicalWeight>::new();
+/// let s0 = fst.add_state();
+/// let s1 = fst.add_state();
+/// let s2 = fst.add_state();
+/// fst.set_start(s0)?;
+/// fst.add_tr(s0, Tr::new(EPS_LABEL, 1, TropicalWeight::new(1.0), s1))?;
+/// fst.add_tr(s1, Tr::new(2, 2, TropicalWeight::new(2.0), s2))?;
+/// fst.set_final(s2, TropicalWeight::one())?;
+///
+/// let fst_normalized: VectorFst<_> = epsnormalize(&fst, EpsNormalizeType::Input)?;
+///
+/// let start = fst_normalized.start().unwrap();
+/// let tr = &fst_normalized.get_trs(start)?[0];
+/// assert_eq!((tr.ilabel, tr.olabel), (2, 1));
+/// # Ok(())
+/// # }
+/// ```
+pub fn epsnormalize<W, F1, F2>(ifst: &F1, eps_norm_type: EpsNormalizeType) -> Result<F2>
+where
+    W: WeightQuantize,
+    F1: ExpandedFst<W>,
+    F2: MutableFst<W> + AllocableFst<W>,
+{
+    let mut to_gallic = ToGallicConverter {};
+    let mut gfst: VectorFst<GallicWeight<W>> = match eps_norm_type {
+        EpsNormalizeType::Input => weight_convert(ifst, &mut to_gallic)?,
+        EpsNormalizeType::Output => {
+            let mut inv_fst: VectorFst<W> = fst_convert_from_ref(ifst);
+            invert(&mut inv_fst);
+            weight_convert(&inv_fst, &mut to_gallic)?
+        }
+    };
+
+    rm_epsilon(&mut gfst)?;
+
+    let factor_opts = FactorWeightOptions {
+        delta: KDELTA,
+        mode: FactorWeightType::FACTOR_FINAL_WEIGHTS | FactorWeightType::FACTOR_ARC_WEIGHTS,
+        final_ilabel: EPS_LABEL,
+        final_olabel: EPS_LABEL,
+        increment_final_ilabel: false,
+        increment_final_olabel: false,
+    };
+    let fwfst: VectorFst<GallicWeight<W>> =
+        factor_weight::<_, VectorFst<GallicWeight<W>>, _, _, GallicFactor<W>>(&gfst, factor_opts)?;
+
+    let mut from_gallic = FromGallicConverter {
+        superfinal_label: EPS_LABEL,
+    };
+    let mut ofst: F2 = weight_convert(&fwfst, &mut from_gallic)?;
+    if eps_norm_type == EpsNormalizeType::Output {
+        invert(&mut ofst);
+    }
+    ofst.set_symts_from_fst(ifst);
+    Ok(ofst)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::Arc;
+
+    use crate::fst_traits::Fst;
+    use crate::semirings::{Semiring, TropicalWeight};
+    use crate::{StateId, SymbolTable, Tr, Trs};
+
+    fn build_fst() -> Result<VectorFst<TropicalWeight>> {
+        let mut fst = VectorFst::<TropicalWeight>::new();
+        let s0 = fst.add_state();
+        let s1 = fst.add_state();
+        let s2 = fst.add_state();
+        let s3 = fst.add_state();
+        fst.set_start(s0)?;
+        fst.add_tr(s0, Tr::new(EPS_LABEL, 1, TropicalWeight::new(1.0), s1))?;
+        fst.add_tr(s1, Tr::new(2, EPS_LABEL, TropicalWeight::new(2.0), s2))?;
+        fst.add_tr(s2, Tr::new(3, 3, TropicalWeight::new(0.5), s3))?;
+        fst.add_tr(s0, Tr::new(4, 4, TropicalWeight::new(3.0), s3))?;
+        fst.set_final(s3, TropicalWeight::new(1.5))?;
+        Ok(fst)
+    }
+
+    // Returns true if on every path, an epsilon label is never followed by a non-epsilon one.
+    fn is_eps_normalized<F: ExpandedFst<TropicalWeight>>(fst: &F, input: bool) -> Result<bool> {
+        let label = |tr: &Tr<TropicalWeight>| if input { tr.ilabel } else { tr.olabel };
+        let mut after_eps = vec![false; fst.num_states()];
+        let mut stack: Vec<StateId> = fst.start().into_iter().collect();
+        let mut visited = vec![false; fst.num_states()];
+        while let Some(s) = stack.pop() {
+            if visited[s as usize] {
+                continue;
+            }
+            visited[s as usize] = true;
+            for tr in fst.get_trs(s)?.trs() {
+                if after_eps[s as usize] && label(tr) != EPS_LABEL {
+                    return Ok(false);
+                }
+                if after_eps[s as usize] || label(tr) == EPS_LABEL {
+                    after_eps[tr.nextstate as usize] = true;
+                }
+                stack.push(tr.nextstate);
+            }
+        }
+        Ok(true)
+    }
+
+    fn sorted_paths(fst: &VectorFst<TropicalWeight>) -> Vec<String> {
+        let mut paths: Vec<_> = fst
+            .paths_iter()
+            .map(|p| format!("{:?} {:?} {:?}", p.ilabels, p.olabels, p.weight))
+            .collect();
+        paths.sort();
+        paths
+    }
+
+    #[test]
+    fn test_epsnormalize_input() -> Result<()> {
+        let fst = build_fst()?;
+        assert!(!is_eps_normalized(&fst, true)?);
+
+        let fst_res: VectorFst<_> = epsnormalize(&fst, EpsNormalizeType::Input)?;
+        assert!(is_eps_normalized(&fst_res, true)?);
+        assert_eq!(sorted_paths(&fst_res), sorted_paths(&fst));
+        Ok(())
+    }
+
+    #[test]
+    fn test_epsnormalize_output() -> Result<()> {
+        let fst = build_fst()?;
+        assert!(!is_eps_normalized(&fst, false)?);
+
+        let fst_res: VectorFst<_> = epsnormalize(&fst, EpsNormalizeType::Output)?;
+        assert!(is_eps_normalized(&fst_res, false)?);
+        assert_eq!(sorted_paths(&fst_res), sorted_paths(&fst));
+        Ok(())
+    }
+
+    #[test]
+    fn test_epsnormalize_symbol_tables() -> Result<()> {
+        let mut fst = build_fst()?;
+        let mut isymt = SymbolTable::new();
+        isymt.add_symbols(vec!["a", "b", "c", "d"]);
+        let mut osymt = SymbolTable::new();
+        osymt.add_symbols(vec!["e", "f", "g", "h"]);
+        fst.set_input_symbols(Arc::new(isymt));
+        fst.set_output_symbols(Arc::new(osymt));
+
+        for eps_norm_type in &[EpsNormalizeType::Input, EpsNormalizeType::Output] {
+            let fst_res: VectorFst<_> = epsnormalize(&fst, *eps_norm_type)?;
+            assert_eq!(fst_res.input_symbols(), fst.input_symbols());
+            assert_eq!(fst_res.output_symbols(), fst.output_symbols());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,255 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::Result;
+
+use crate::algorithms::tr_mappers::QuantizeMapper;
+use crate::algorithms::union_find::UnionFind;
+use crate::algorithms::{
+    connect, fst_convert_from_ref, push_weights_with_config, tr_map, PushWeightsConfig,
+    ReweightType,
+};
+use crate::fst_impls::VectorFst;
+use crate::fst_properties::{compute_fst_properties, FstProperties};
+use crate::fst_traits::{CoreFst, ExpandedFst};
+use crate::semirings::{WeaklyDivisibleSemiring, WeightQuantize};
+use crate::{Label, StateId, Trs};
+
+/// Returns whether the FST is weighted. Fails if the FST is not an epsilon-free
+/// deterministic acceptor.
+fn check_properties<W: WeaklyDivisibleSemiring, F: ExpandedFst<W>>(
+    fst: &F,
+    position: &str,
+) -> Result<bool> {
+    let required =
+        FstProperties::NO_EPSILONS | FstProperties::I_DETERMINISTIC | FstProperties::ACCEPTOR;
+    let mut known = FstProperties::empty();
+    let props = compute_fst_properties(
+        fst,
+        required | FstProperties::UNWEIGHTED | FstProperties::WEIGHTED,
+        &mut known,
+        true,
+    )?;
+    if !props.contains(required) {
+        bail!(
+            "Equivalent: {} argument is not an epsilon-free deterministic acceptor",
+            position
+        )
+    }
+    Ok(!props.contains(FstProperties::UNWEIGHTED))
+}
+
+fn prepare_fst<W, F>(fst: &F, weighted: bool, delta: f32) -> Result<VectorFst<W>>
+where
+    W: WeaklyDivisibleSemiring + WeightQuantize,
+    F: ExpandedFst<W>,
+{
+    let mut efst: VectorFst<W> = fst_convert_from_ref(fst);
+    connect(&mut efst)?;
+    if weighted {
+        push_weights_with_config(
+            &mut efst,
+            ReweightType::ReweightToInitial,
+            PushWeightsConfig::default().with_delta(delta),
+        )?;
+        tr_map(&mut efst, &QuantizeMapper::new(delta))?;
+    }
+    Ok(efst)
+}
+
+/// Union of the states of both FSTs : the states of the first FST are mapped to
+/// `[0, n1)`, the states of the second one to `[n1, n1 + n2)` and `n1 + n2` is a
+/// non-final dead state without any transition.
+struct MappedFsts<W: WeaklyDivisibleSemiring> {
+    fst1: VectorFst<W>,
+    fst2: VectorFst<W>,
+}
+
+impl<W: WeaklyDivisibleSemiring> MappedFsts<W> {
+    fn dead_state(&self) -> StateId {
+        (self.fst1.num_states() + self.fst2.num_states()) as StateId
+    }
+
+    fn unmap(&self, state: StateId) -> Option<(&VectorFst<W>, StateId, StateId)> {
+        let n1 = self.fst1.num_states() as StateId;
+        if state < n1 {
+            Some((&self.fst1, state, 0))
+        } else if state < self.dead_state() {
+            Some((&self.fst2, state - n1, n1))
+        } else {
+            None
+        }
+    }
+
+    fn final_weight(&self, state: StateId) -> Result<Option<W>> {
+        match self.unmap(state) {
+            Some((fst, s, _)) => fst.final_weight(s),
+            None => Ok(None),
+        }
+    }
+
+    /// Maps each label to the destination state and the weight of the transition.
+    fn trs(&self, state: StateId) -> Result<BTreeMap<Label, (StateId, W)>> {
+        let mut res = BTreeMap::new();
+        if let Some((fst, s, offset)) = self.unmap(state) {
+            for tr in fst.get_trs(s)?.trs() {
+                res.insert(tr.ilabel, (tr.nextstate + offset, tr.weight.clone()));
+            }
+        }
+        Ok(res)
+    }
+}
+
+/// Tests if two epsilon-free deterministic weighted acceptors are equivalent,
+/// that is if they accept the same strings with the same weights.
+///
+/// The weights are pushed towards the initial state and quantized with `delta`
+/// before comparing them. The equivalence classes of the pairs of states are
+/// maintained with a Union-Find structure.
+///
+/// # Example
+/// ```
+/// # #[macro_use] extern crate rustfst;
+/// # use anyhow::Result;
+/// # use rustfst::utils::acceptor;
+/// # use rustfst::semirings::{Semiring, TropicalWeight};
+/// # use rustfst::fst_impls::VectorFst;
+/// # use rustfst::fst_traits::MutableFst;
+/// # use rustfst::algorithms::equivalent;
+/// # use rustfst::{Tr, KDELTA};
+/// # fn main() -> Result<()> {
+/// let fst_1 : VectorFst<TropicalWeight> = fst![1, 2; 3.0];
+///
+/// let mut fst_2 = VectorFst::<TropicalWeight>::new();
+/// let s0 = fst_2.add_state();
+/// let s1 = fst_2.add_state();
+/// let s2 = fst_2.add_state();
+/// fst_2.set_start(s0)?;
+/// fst_2.add_tr(s0, Tr::new(1, 1, TropicalWeight::new(1.0), s1))?;
+/// fst_2.add_tr(s1, Tr::new(2, 2, TropicalWeight::new(2.0), s2))?;
+/// fst_2.set_final(s2, TropicalWeight::one())?;
+///
+/// assert!(equivalent(&fst_1, &fst_2, KDELTA)?);
+/// # Ok(())
+/// # }
+/// ```
+pub fn equivalent<W, F1, F2>(fst1: &F1, fst2: &F2, delta: f32) -> Result<bool>
+where
+    W: WeaklyDivisibleSemiring + WeightQuantize,
+    F1: ExpandedFst<W>,
+    F2: ExpandedFst<W>,
+{
+    let weighted1 = check_properties(fst1, "1st")?;
+    let weighted2 = check_properties(fst2, "2nd")?;
+    let weighted = weighted1 || weighted2;
+
+    let fsts = MappedFsts {
+        fst1: prepare_fst(fst1, weighted, delta)?,
+        fst2: prepare_fst(fst2, weighted, delta)?,
+    };
+
+    let (start1, start2) = match (fsts.fst1.start(), fsts.fst2.start()) {
+        (None, None) => return Ok(true),
+        (Some(s1), Some(s2)) => (s1, s2 + fsts.fst1.num_states() as StateId),
+        _ => return Ok(false),
+    };
+
+    let dead = fsts.dead_state();
+    let mut eq_classes = UnionFind::new(dead as usize + 1);
+    eq_classes.union(start1, start2);
+    let mut stack = vec![(start1, start2)];
+
+    while let Some((s1, s2)) = stack.pop() {
+        if fsts.final_weight(s1)? != fsts.final_weight(s2)? {
+            return Ok(false);
+        }
+
+        let trs1 = fsts.trs(s1)?;
+        let trs2 = fsts.trs(s2)?;
+        let labels: BTreeSet<_> = trs1.keys().chain(trs2.keys()).collect();
+        for label in labels {
+            let (next1, next2) = match (trs1.get(label), trs2.get(label)) {
+                (Some((n1, w1)), Some((n2, w2))) => {
+                    if w1 != w2 {
+                        return Ok(false);
+                    }
+                    (*n1, *n2)
+                }
+                (Some((n1, _)), None) => (*n1, dead),
+                (None, Some((n2, _))) => (dead, *n2),
+                (None, None) => unreachable!(),
+            };
+            let p1 = eq_classes.find_set(next1);
+            let p2 = eq_classes.find_set(next2);
+            if p1 != p2 {
+                eq_classes.union(p1, p2);
+                stack.push((next1, next2));
+            }
+        }
+    }
+
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::algorithms::union::union;
+    use crate::fst_traits::MutableFst;
+    use crate::semirings::{Semiring, TropicalWeight};
+    use crate::utils::{acceptor, transducer};
+    use crate::{Tr, KDELTA};
+
+    // Accepts "1 2" with weight 3 and "1 3" with weight 2, with a redundant state.
+    fn build_fst(w1: f32, w2: f32) -> Result<VectorFst<TropicalWeight>> {
+        let mut fst = VectorFst::<TropicalWeight>::new();
+        let s0 = fst.add_state();
+        let s1 = fst.add_state();
+        let s2 = fst.add_state();
+        let s3 = fst.add_state();
+        fst.set_start(s0)?;
+        fst.add_tr(s0, Tr::new(1, 1, TropicalWeight::new(w1), s1))?;
+        fst.add_tr(s1, Tr::new(2, 2, TropicalWeight::new(w2), s2))?;
+        fst.add_tr(s1, Tr::new(3, 3, TropicalWeight::new(w2 - 1.0), s3))?;
+        fst.set_final(s2, TropicalWeight::one())?;
+        fst.set_final(s3, TropicalWeight::one())?;
+        Ok(fst)
+    }
+
+    #[test]
+    fn test_equivalent_weighted() -> Result<()> {
+        let fst1 = build_fst(1.0, 2.0)?;
+        let fst2 = build_fst(2.5, 0.5)?;
+        let fst3 = build_fst(1.0, 3.0)?;
+        assert!(equivalent(&fst1, &fst1, KDELTA)?);
+        assert!(equivalent(&fst1, &fst2, KDELTA)?);
+        assert!(!equivalent(&fst1, &fst3, KDELTA)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_equivalent_unweighted() -> Result<()> {
+        let fst1: VectorFst<TropicalWeight> = acceptor(&[1, 2], TropicalWeight::one());
+        let fst2: VectorFst<TropicalWeight> = acceptor(&[1, 3], TropicalWeight::one());
+        let fst3: VectorFst<TropicalWeight> = acceptor(&[1, 2, 3], TropicalWeight::one());
+        let empty = VectorFst::<TropicalWeight>::new();
+        assert!(equivalent(&fst1, &fst1.clone(), KDELTA)?);
+        assert!(!equivalent(&fst1, &fst2, KDELTA)?);
+        assert!(!equivalent(&fst1, &fst3, KDELTA)?);
+        assert!(!equivalent(&fst1, &empty, KDELTA)?);
+        assert!(equivalent(&empty, &empty.clone(), KDELTA)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_equivalent_invalid_inputs() -> Result<()> {
+        let fst1: VectorFst<TropicalWeight> = acceptor(&[1, 2], TropicalWeight::one());
+        let fst2: VectorFst<TropicalWeight> = transducer(&[1, 2], &[3, 4], TropicalWeight::one());
+        assert!(equivalent(&fst1, &fst2, KDELTA).is_err());
+
+        let mut fst3 = fst1.clone();
+        union(&mut fst3, &fst1)?;
+        assert!(equivalent(&fst1, &fst3, KDELTA).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,129 @@
+use anyhow::Result;
+
+use crate::algorithms::weight_converters::ExpectationConverter;
+use crate::algorithms::{shortest_distance, weight_convert};
+use crate::fst_impls::VectorFst;
+use crate::fst_traits::ExpandedFst;
+use crate::semirings::{
+    DivideType, ExpectationWeight, SemimoduleWeight, Semiring, WeaklyDivisibleSemiring,
+};
+use crate::Tr;
+
+/// Computes the expected values of the features of the transitions of an FST, the weight
+/// of a path being its probability.
+///
+/// The FST is mapped into the expectation semiring with [`ExpectationConverter`] and
+/// its total weight `<Z, E>` is computed with `shortest_distance`. `Z` is the sum of the
+/// weights of all the paths and `E` the sum of these weights times the sum of the features
+/// returned by `feature` along the paths. The returned value is `E / Z`, the expected value
+/// of the features : the expected number of occurrences of the transitions if `feature`
+/// returns one for the transitions to count and zero otherwise. The weights don't need to
+/// be normalized. An error is returned if the FST has no successful path.
+///
+/// # Example
+/// ```
+/// # use anyhow::Result;
+/// # use rustfst::algorithms::expected_values;
+/// # use rustfst::algorithms::union::union;
+/// # use rustfst::fst_impls::VectorFst;
+/// # use rustfst::semirings::{ProbabilityWeight, Semiring};
+/// # use rustfst::utils::acceptor;
+/// # fn main() -> Result<()> {
+/// let mut fst : VectorFst<ProbabilityWeight> = acceptor(&[1], ProbabilityWeight::new(0.25));
+/// let other : VectorFst<ProbabilityWeight> = acceptor(&[2, 1, 1], ProbabilityWeight::new(0.75));
+/// union(&mut fst, &other)?;
+///
+/// // Expected number of transitions with the label 1.
+/// let res = expected_values(&fst, |tr| {
+///     if tr.ilabel == 1 { ProbabilityWeight::one() } else { ProbabilityWeight::zero() }
+/// })?;
+/// assert_eq!(res, ProbabilityWeight::new(1.75));
+/// # Ok(())
+/// # }
+/// ```
+pub fn expected_values<W1, W2, F, FN>(fst: &F, feature: FN) -> Result<W2>
+where
+    W1: WeaklyDivisibleSemiring,
+    W2: SemimoduleWeight<W1>,
+    F: ExpandedFst<W1>,
+    FN: FnMut(&Tr<W1>) -> W2,
+{
+    let start = match fst.start() {
+        Some(s) => s,
+        None => bail!("expected_values : the FST has no successful path"),
+    };
+    let mut converter = ExpectationConverter::new(feature);
+    let efst: VectorFst<ExpectationWeight<W1, W2>> = weight_convert(fst, &mut converter)?;
+    let distance = shortest_distance(&efst, true)?;
+    let total = distance
+        .get(start as usize)
+        .cloned()
+        .unwrap_or_else(ExpectationWeight::zero);
+    if total.value1().is_zero() {
+        bail!("expected_values : the FST has no successful path")
+    }
+    let inv_z = W1::one().divide(total.value1(), DivideType::DivideAny)?;
+    total.value2().scalar_times(&inv_z)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::algorithms::union::union;
+    use crate::fst_traits::MutableFst;
+    use crate::semirings::{LogWeight, ProbabilityWeight};
+    use crate::utils::acceptor;
+
+    #[test]
+    fn test_expected_values_cyclic() -> Result<()> {
+        // Geometric number of loops : the expected count is p / (1 - p) with p = 0.5.
+        let mut fst = VectorFst::<LogWeight>::new();
+        let s0 = fst.add_state();
+        fst.set_start(s0)?;
+        fst.set_final(s0, LogWeight::new(2f32.ln()))?;
+        fst.add_tr(s0, Tr::new(1, 1, LogWeight::new(2f32.ln()), s0))?;
+
+        let res = expected_values(&fst, |_| LogWeight::one())?;
+        assert!(res.approx_equal(LogWeight::one(), 1e-3));
+
+        let mut fst = VectorFst::<ProbabilityWeight>::new();
+        let s0 = fst.add_state();
+        fst.set_start(s0)?;
+        fst.set_final(s0, ProbabilityWeight::new(0.5))?;
+        fst.add_tr(s0, Tr::new(1, 1, ProbabilityWeight::new(0.5), s0))?;
+        let res = expected_values(&fst, |_| ProbabilityWeight::one())?;
+        assert!(res.approx_equal(ProbabilityWeight::one(), 1e-3));
+        Ok(())
+    }
+
+    #[test]
+    fn test_expected_values_not_normalized() -> Result<()> {
+        // Z = 0.4 : the expected number of transitions with the label 1 is 0.7 / 0.4.
+        let mut fst: VectorFst<ProbabilityWeight> = acceptor(&[1], ProbabilityWeight::new(0.1));
+        let other: VectorFst<ProbabilityWeight> = acceptor(&[2, 1, 1], ProbabilityWeight::new(0.3));
+        union(&mut fst, &other)?;
+
+        let res = expected_values(&fst, |tr| {
+            if tr.ilabel == 1 {
+                ProbabilityWeight::one()
+            } else {
+                ProbabilityWeight::zero()
+            }
+        })?;
+        assert!(res.approx_equal(ProbabilityWeight::new(1.75), 1e-3));
+        Ok(())
+    }
+
+    #[test]
+    fn test_expected_values_empty() -> Result<()> {
+        let fst = VectorFst::<ProbabilityWeight>::new();
+        assert!(expected_values(&fst, |_| ProbabilityWeight::one()).is_err());
+
+        let mut fst = VectorFst::<ProbabilityWeight>::new();
+        let s0 = fst.add_state();
+        fst.set_start(s0)?;
+        assert!(expected_values(&fst, |_| ProbabilityWeight::one()).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,172 @@
+use std::borrow::Borrow;
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use anyhow::Result;
+
+use crate::algorithms::lazy::{LazyFst, SimpleVecCache};
+use crate::algorithms::tr_map::TrMapFstOp;
+use crate::algorithms::tr_mappers::InvertMapper;
+use crate::fst_properties::FstProperties;
+use crate::fst_traits::{
+    AllocableFst, CoreFst, ExpandedFst, Fst, FstIterator, MutableFst, StateIterator,
+};
+use crate::semirings::Semiring;
+use crate::{StateId, SymbolTable, TrsVec};
+
+type InnerLazyFst<W, F, B> = LazyFst<W, TrMapFstOp<W, F, B, InvertMapper>, SimpleVecCache<W>>;
+
+/// Inverts a transduction by swapping the input and output labels of every transition.
+/// This version is a Delayed FST.
+pub struct InvertFst<W: Semiring, F: ExpandedFst<W>, B: Borrow<F>>(InnerLazyFst<W, F, B>);
+
+impl<W, F, B> CoreFst<W> for InvertFst<W, F, B>
+where
+    W: Semiring,
+    F: ExpandedFst<W>,
+    B: Borrow<F>,
+{
+    type TRS = TrsVec<W>;
+
+    fn start(&self) -> Option<StateId> {
+        self.0.start()
+    }
+
+    fn final_weight(&self, state_id: StateId) -> Result<Option<W>> {
+        self.0.final_weight(state_id)
+    }
+
+    unsafe fn final_weight_unchecked(&self, state_id: StateId) -> Option<W> {
+        self.0.final_weight_unchecked(state_id)
+    }
+
+    fn num_trs(&self, s: StateId) -> Result<usize> {
+        self.0.num_trs(s)
+    }
+
+    unsafe fn num_trs_unchecked(&self, s: StateId) -> usize {
+        self.0.num_trs_unchecked(s)
+    }
+
+    fn get_trs(&self, state_id: StateId) -> Result<Self::TRS> {
+        self.0.get_trs(state_id)
+    }
+
+    unsafe fn get_trs_unchecked(&self, state_id: StateId) -> Self::TRS {
+        self.0.get_trs_unchecked(state_id)
+    }
+
+    fn properties(&self) -> FstProperties {
+        self.0.properties()
+    }
+
+    fn num_input_epsilons(&self, state: StateId) -> Result<usize> {
+        self.0.num_input_epsilons(state)
+    }
+
+    fn num_output_epsilons(&self, state: StateId) -> Result<usize> {
+        self.0.num_output_epsilons(state)
+    }
+}
+
+impl<'a, W, F, B> StateIterator<'a> for InvertFst<W, F, B>
+where
+    W: Semiring,
+    F: ExpandedFst<W> + 'a,
+    B: Borrow<F> + 'a,
+{
+    type Iter = <InnerLazyFst<W, F, B> as StateIterator<'a>>::Iter;
+
+    fn states_iter(&'a self) -> Self::Iter {
+        self.0.states_iter()
+    }
+}
+
+impl<'a, W, F, B> FstIterator<'a, W> for InvertFst<W, F, B>
+where
+    W: Semiring,
+    F: ExpandedFst<W> + 'a,
+    B: Borrow<F> + 'a,
+{
+    type FstIter = <InnerLazyFst<W, F, B> as FstIterator<'a, W>>::FstIter;
+
+    fn fst_iter(&'a self) -> Self::FstIter {
+        self.0.fst_iter()
+    }
+}
+
+impl<W, F, B> Fst<W> for InvertFst<W, F, B>
+where
+    W: Semiring,
+    F: ExpandedFst<W> + 'static,
+    B: Borrow<F> + 'static,
+{
+    fn input_symbols(&self) -> Option<&Arc<SymbolTable>> {
+        self.0.input_symbols()
+    }
+
+    fn output_symbols(&self) -> Option<&Arc<SymbolTable>> {
+        self.0.output_symbols()
+    }
+
+    fn set_input_symbols(&mut self, symt: Arc<SymbolTable>) {
+        self.0.set_input_symbols(symt)
+    }
+
+    fn set_output_symbols(&mut self, symt: Arc<SymbolTable>) {
+        self.0.set_output_symbols(symt)
+    }
+
+    fn take_input_symbols(&mut self) -> Option<Arc<SymbolTable>> {
+        self.0.take_input_symbols()
+    }
+
+    fn take_output_symbols(&mut self) -> Option<Arc<SymbolTable>> {
+        self.0.take_output_symbols()
+    }
+}
+
+impl<W, F, B> Debug for InvertFst<W, F, B>
+where
+    W: Semiring,
+    F: ExpandedFst<W>,
+    B: Borrow<F>,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<W, F, B> InvertFst<W, F, B>
+where
+    W: Semiring,
+    F: ExpandedFst<W>,
+    B: Borrow<F>,
+{
+    pub fn new(fst: B) -> Self {
+        let isymt = fst.borrow().input_symbols().cloned();
+        let osymt = fst.borrow().output_symbols().cloned();
+        let fst_op = TrMapFstOp::new(fst, InvertMapper {});
+        let fst_cache = SimpleVecCache::default();
+        let lazy_fst = LazyFst::from_op_and_cache(fst_op, fst_cache, osymt, isymt);
+        InvertFst(lazy_fst)
+    }
+
+    /// Turns the Lazy FST into a static one.
+    pub fn compute<F2: MutableFst<W> + AllocableFst<W>>(&self) -> Result<F2> {
+        self.0.compute()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fst_impls::VectorFst;
+    use crate::semirings::TropicalWeight;
+
+    #[test]
+    fn test_invert_fst_sync() {
+        fn is_sync<T: Sync>() {}
+        is_sync::<InvertFst<TropicalWeight, VectorFst<_>, VectorFst<_>>>();
+    }
+}
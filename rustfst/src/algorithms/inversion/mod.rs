@@ -0,0 +1,5 @@
+mod inversion_static;
+mod invert_fst;
+
+pub use inversion_static::invert;
+pub use invert_fst::InvertFst;
@@ -0,0 +1,508 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashSet};
+
+use anyhow::Result;
+
+use crate::algorithms::shortest_path::natural_less;
+use crate::algorithms::{fst_convert_from_ref, shortest_distance};
+use crate::fst_impls::VectorFst;
+use crate::fst_path::FstPath;
+use crate::fst_traits::{CoreFst, Fst};
+use crate::semirings::{Semiring, SemiringProperties};
+use crate::{Label, StateId, Trs, EPS_LABEL};
+
+/// Configuration for [`kbest_paths_iter_with_config`].
+#[derive(Debug, Clone, Copy, PartialOrd, PartialEq, Default)]
+pub struct KBestPathsConfig {
+    /// If true, only the best path of each (input labels, output labels) pair is returned.
+    pub unique: bool,
+}
+
+impl KBestPathsConfig {
+    pub fn new(unique: bool) -> Self {
+        Self { unique }
+    }
+
+    pub fn with_unique(self, unique: bool) -> Self {
+        Self { unique }
+    }
+}
+
+/// Node of the tree of the partial paths explored so far. Each node stores the
+/// labels of the last transition of the path and a link to the previous node.
+struct PathNode {
+    parent: Option<usize>,
+    ilabel: Label,
+    olabel: Label,
+}
+
+/// Element of the priority queue : either a partial path ending in `state` or
+/// a complete path (the final weight of its last state has been added).
+struct KBestHeapItem<W: Semiring> {
+    /// Weight of the path times the estimated distance from `state` to the final states.
+    estimate: W,
+    weight: W,
+    node: Option<usize>,
+    state: Option<StateId>,
+    // Insertion order, used to break ties and get a deterministic output.
+    id: usize,
+}
+
+impl<W: Semiring> PartialEq for KBestHeapItem<W> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<W: Semiring> Eq for KBestHeapItem<W> {}
+
+impl<W: Semiring> PartialOrd for KBestHeapItem<W> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<W: Semiring> Ord for KBestHeapItem<W> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // BinaryHeap is a max-heap, the order is reversed to pop the best estimate first.
+        if natural_less(&self.estimate, &other.estimate).unwrap_or(false) {
+            Ordering::Greater
+        } else if natural_less(&other.estimate, &self.estimate).unwrap_or(false) {
+            Ordering::Less
+        } else {
+            other.id.cmp(&self.id)
+        }
+    }
+}
+
+/// Iterator over the successful paths of an FST by increasing weight (w.r.t. the
+/// natural order). Created with [`kbest_paths_iter`], [`kbest_paths_iter_with_config`]
+/// or [`kbest_paths_iter_with_heuristic`].
+pub struct KBestPathsIterator<'a, W: Semiring, F: CoreFst<W>> {
+    fst: &'a F,
+    heuristic: Box<dyn Fn(StateId) -> W + 'a>,
+    config: KBestPathsConfig,
+    nodes: Vec<PathNode>,
+    heap: BinaryHeap<KBestHeapItem<W>>,
+    seen: HashSet<(Vec<Label>, Vec<Label>)>,
+    error: Option<anyhow::Error>,
+    num_pushed: usize,
+}
+
+impl<'a, W: Semiring, F: CoreFst<W>> KBestPathsIterator<'a, W, F> {
+    fn new(
+        fst: &'a F,
+        heuristic: Box<dyn Fn(StateId) -> W + 'a>,
+        config: KBestPathsConfig,
+    ) -> Self {
+        let mut it = Self {
+            fst,
+            heuristic,
+            config,
+            nodes: vec![],
+            heap: BinaryHeap::new(),
+            seen: HashSet::new(),
+            error: None,
+            num_pushed: 0,
+        };
+        if !W::properties().contains(SemiringProperties::PATH) {
+            it.error = Some(format_err!(
+                "KBestPaths : Weight needs to have the path property"
+            ));
+        } else if let Some(start) = fst.start() {
+            match it.estimate(&W::one(), start) {
+                Ok(Some(estimate)) => it.push(estimate, W::one(), None, Some(start)),
+                Ok(None) => {}
+                Err(e) => it.error = Some(e),
+            }
+        }
+        it
+    }
+
+    fn from_error(fst: &'a F, error: anyhow::Error) -> Self {
+        Self {
+            fst,
+            heuristic: Box::new(|_| W::zero()),
+            config: KBestPathsConfig::default(),
+            nodes: vec![],
+            heap: BinaryHeap::new(),
+            seen: HashSet::new(),
+            error: Some(error),
+            num_pushed: 0,
+        }
+    }
+
+    /// Estimated weight of the best successful path extending a partial path ending in
+    /// `state`. `None` if no final state can be reached from `state`.
+    fn estimate(&self, weight: &W, state: StateId) -> Result<Option<W>> {
+        let distance = (self.heuristic)(state);
+        if distance.is_zero() {
+            Ok(None)
+        } else {
+            Ok(Some(weight.times(distance)?))
+        }
+    }
+
+    fn push(&mut self, estimate: W, weight: W, node: Option<usize>, state: Option<StateId>) {
+        self.heap.push(KBestHeapItem {
+            estimate,
+            weight,
+            node,
+            state,
+            id: self.num_pushed,
+        });
+        self.num_pushed += 1;
+    }
+
+    fn expand(&mut self, weight: W, node: Option<usize>, state: StateId) -> Result<()> {
+        if let Some(final_weight) = self.fst.final_weight(state)? {
+            let weight = weight.times(final_weight)?;
+            self.push(weight.clone(), weight, node, None);
+        }
+        for tr in self.fst.get_trs(state)?.trs() {
+            let next_weight = weight.times(&tr.weight)?;
+            let estimate = match self.estimate(&next_weight, tr.nextstate)? {
+                Some(e) => e,
+                None => continue,
+            };
+            self.nodes.push(PathNode {
+                parent: node,
+                ilabel: tr.ilabel,
+                olabel: tr.olabel,
+            });
+            let child = self.nodes.len() - 1;
+            self.push(estimate, next_weight, Some(child), Some(tr.nextstate));
+        }
+        Ok(())
+    }
+
+    fn build_path(&self, weight: W, mut node: Option<usize>) -> FstPath<W> {
+        let mut ilabels = vec![];
+        let mut olabels = vec![];
+        while let Some(n) = node {
+            let path_node = &self.nodes[n];
+            if path_node.ilabel != EPS_LABEL {
+                ilabels.push(path_node.ilabel);
+            }
+            if path_node.olabel != EPS_LABEL {
+                olabels.push(path_node.olabel);
+            }
+            node = path_node.parent;
+        }
+        ilabels.reverse();
+        olabels.reverse();
+        FstPath::new(ilabels, olabels, weight)
+    }
+
+    fn next_path(&mut self) -> Result<Option<FstPath<W>>> {
+        while let Some(KBestHeapItem {
+            weight,
+            node,
+            state,
+            ..
+        }) = self.heap.pop()
+        {
+            match state {
+                Some(s) => self.expand(weight, node, s)?,
+                None => {
+                    let path = self.build_path(weight, node);
+                    if self.config.unique
+                        && !self
+                            .seen
+                            .insert((path.ilabels.clone(), path.olabels.clone()))
+                    {
+                        continue;
+                    }
+                    return Ok(Some(path));
+                }
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl<'a, W: Semiring, F: CoreFst<W>> Iterator for KBestPathsIterator<'a, W, F> {
+    type Item = Result<FstPath<W>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(e) = self.error.take() {
+            self.heap.clear();
+            return Some(Err(e));
+        }
+        match self.next_path() {
+            Ok(path) => path.map(Ok),
+            Err(e) => {
+                self.heap.clear();
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+/// Shortest distance from each state to the final states. The FST is expanded, which
+/// is required by `shortest_distance` and only expands the accessible part of lazy FSTs.
+fn distance_to_final<W: Semiring, F: Fst<W>>(fst: &F) -> Result<Vec<W>> {
+    let efst: VectorFst<W> = fst_convert_from_ref(fst);
+    shortest_distance(&efst, true)
+}
+
+/// Iterates over the successful paths of an FST by increasing weight, see
+/// [`kbest_paths_iter`]. With `unique`, only the best path of each
+/// (input labels, output labels) pair is returned.
+pub fn kbest_paths_iter_with_config<W, F>(
+    fst: &F,
+    config: KBestPathsConfig,
+) -> KBestPathsIterator<W, F>
+where
+    W: Semiring,
+    F: Fst<W>,
+{
+    match distance_to_final(fst) {
+        Ok(distance) => {
+            let heuristic =
+                move |s: StateId| distance.get(s as usize).cloned().unwrap_or_else(W::zero);
+            KBestPathsIterator::new(fst, Box::new(heuristic), config)
+        }
+        Err(e) => KBestPathsIterator::from_error(fst, e),
+    }
+}
+
+/// Iterates over the successful paths of an FST by increasing weight w.r.t. the
+/// natural order, without building the n-best FST.
+///
+/// The partial paths are explored by increasing weight times the shortest distance
+/// from their last state to the final states (recursive enumeration). The states from
+/// which no final state can be reached are never expanded, so the iteration stops
+/// once all the paths have been returned, and only a few states are expanded to find
+/// the next path.
+///
+/// Lazy FSTs (e.g. `ComposeFst`) are accepted, but the shortest distance requires to
+/// expand their accessible part once, when the iterator is created. Use
+/// [`kbest_paths_iter_with_heuristic`] to avoid it.
+///
+/// The weight must have the path property and extending a path must not improve
+/// its weight (e.g. no negative weights in the tropical semiring).
+///
+/// # Example
+/// ```
+/// # #[macro_use] extern crate rustfst;
+/// # use anyhow::Result;
+/// # use rustfst::utils::acceptor;
+/// # use rustfst::semirings::{Semiring, TropicalWeight};
+/// # use rustfst::fst_impls::VectorFst;
+/// # use rustfst::algorithms::kbest_paths_iter;
+/// # use rustfst::algorithms::union::union;
+/// # fn main() -> Result<()> {
+/// let mut fst : VectorFst<TropicalWeight> = fst![1, 2; 3.0];
+/// let other : VectorFst<TropicalWeight> = fst![3; 1.0];
+/// union(&mut fst, &other)?;
+/// let other : VectorFst<TropicalWeight> = fst![4, 5; 2.0];
+/// union(&mut fst, &other)?;
+///
+/// let paths = kbest_paths_iter(&fst).take(2).collect::<Result<Vec<_>>>()?;
+/// assert_eq!(paths[0].ilabels, vec![3]);
+/// assert_eq!(paths[1].ilabels, vec![4, 5]);
+/// # Ok(())
+/// # }
+/// ```
+pub fn kbest_paths_iter<W, F>(fst: &F) -> KBestPathsIterator<W, F>
+where
+    W: Semiring,
+    F: Fst<W>,
+{
+    kbest_paths_iter_with_config(fst, KBestPathsConfig::default())
+}
+
+/// Iterates over the successful paths of an FST by increasing weight, as
+/// [`kbest_paths_iter`] does, without computing the shortest distance to the final
+/// states. Only the states needed to find the next path are expanded, which is useful
+/// for large lazy FSTs such as `ComposeFst`.
+///
+/// As in [`astar_shortest_path`](crate::algorithms::astar_shortest_path), `heuristic`
+/// estimates the distance from a state to the final states. It must be admissible, i.e.
+/// never worse than the real distance (`h(s) <= d(s)` in the tropical semiring), for
+/// the paths to be returned in order. `W::zero()` marks the states from which no final
+/// state can be reached : they are not expanded.
+///
+/// As the states are not all visited, the states that can't reach a final state can only
+/// be detected through the heuristic. If the heuristic doesn't return `W::zero()` for
+/// them and a cycle among them doesn't increase the weight, the iteration doesn't
+/// terminate once the successful paths have all been returned. [`kbest_paths_iter`]
+/// doesn't have this limitation.
+pub fn kbest_paths_iter_with_heuristic<'a, W, F, H>(
+    fst: &'a F,
+    heuristic: H,
+    config: KBestPathsConfig,
+) -> KBestPathsIterator<'a, W, F>
+where
+    W: Semiring,
+    F: CoreFst<W>,
+    H: Fn(StateId) -> W + 'a,
+{
+    KBestPathsIterator::new(fst, Box::new(heuristic), config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::Arc;
+
+    use crate::algorithms::compose::ComposeFst;
+    use crate::algorithms::tr_compares::ILabelCompare;
+    use crate::algorithms::union::union;
+    use crate::algorithms::{shortest_path_with_config, tr_sort, ShortestPathConfig};
+    use crate::fst_traits::MutableFst;
+    use crate::semirings::{LogWeight, TropicalWeight};
+    use crate::utils::{acceptor, transducer};
+    use crate::Tr;
+
+    fn build_fst() -> Result<VectorFst<TropicalWeight>> {
+        let mut fst: VectorFst<TropicalWeight> = acceptor(&[1, 2], TropicalWeight::new(3.0));
+        for (labels, weight) in &[
+            (vec![3], 1.0),
+            (vec![4, 5], 2.0),
+            (vec![1, 2], 1.5),
+            (vec![6], 4.0),
+        ] {
+            let other: VectorFst<TropicalWeight> = acceptor(labels, TropicalWeight::new(*weight));
+            union(&mut fst, &other)?;
+        }
+        Ok(fst)
+    }
+
+    fn weights(paths: &[FstPath<TropicalWeight>]) -> Vec<f32> {
+        paths.iter().map(|p| *p.weight.value()).collect()
+    }
+
+    #[test]
+    fn test_kbest_paths_order() -> Result<()> {
+        let fst = build_fst()?;
+        let paths = kbest_paths_iter(&fst).collect::<Result<Vec<_>>>()?;
+        assert_eq!(weights(&paths), vec![1.0, 1.5, 2.0, 3.0, 4.0]);
+        assert_eq!(paths[0].ilabels, vec![3]);
+        assert_eq!(paths[1].ilabels, vec![1, 2]);
+
+        let nbest: VectorFst<TropicalWeight> =
+            shortest_path_with_config(&fst, ShortestPathConfig::default().with_nshortest(3))?;
+        let mut nbest_weights: Vec<_> = nbest.paths_iter().map(|p| *p.weight.value()).collect();
+        nbest_weights.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert_eq!(weights(&paths[..3]), nbest_weights);
+        Ok(())
+    }
+
+    #[test]
+    fn test_kbest_paths_unique() -> Result<()> {
+        let fst = build_fst()?;
+        let config = KBestPathsConfig::default().with_unique(true);
+        let paths = kbest_paths_iter_with_config(&fst, config).collect::<Result<Vec<_>>>()?;
+        assert_eq!(weights(&paths), vec![1.0, 1.5, 2.0, 4.0]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_kbest_paths_cyclic() -> Result<()> {
+        let mut fst = VectorFst::<TropicalWeight>::new();
+        let s0 = fst.add_state();
+        let s1 = fst.add_state();
+        fst.set_start(s0)?;
+        fst.set_final(s1, TropicalWeight::one())?;
+        fst.add_tr(s0, Tr::new(1, 1, 1.0, s1))?;
+        fst.add_tr(s1, Tr::new(2, 2, 0.5, s1))?;
+
+        let paths = kbest_paths_iter(&fst).take(3).collect::<Result<Vec<_>>>()?;
+        assert_eq!(weights(&paths), vec![1.0, 1.5, 2.0]);
+        assert_eq!(paths[2].ilabels, vec![1, 2, 2]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_kbest_paths_dead_zero_cycle() -> Result<()> {
+        // The zero-weight loop on s1 can't reach a final state : it must not be expanded.
+        let mut fst = VectorFst::<TropicalWeight>::new();
+        let s0 = fst.add_state();
+        let s1 = fst.add_state();
+        let s2 = fst.add_state();
+        fst.set_start(s0)?;
+        fst.set_final(s2, TropicalWeight::one())?;
+        fst.add_tr(s0, Tr::new(1, 1, 0.0, s1))?;
+        fst.add_tr(s1, Tr::new(3, 3, 0.0, s1))?;
+        fst.add_tr(s0, Tr::new(2, 2, 1.0, s2))?;
+
+        let paths = kbest_paths_iter(&fst).collect::<Result<Vec<_>>>()?;
+        assert_eq!(weights(&paths), vec![1.0]);
+        assert_eq!(paths[0].ilabels, vec![2]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_kbest_paths_compose_fst() -> Result<()> {
+        let mut fst1: VectorFst<TropicalWeight> =
+            transducer(&[1, 2], &[3, 4], TropicalWeight::new(1.0));
+        union(
+            &mut fst1,
+            &transducer::<_, VectorFst<_>>(&[1], &[3], TropicalWeight::new(0.5)),
+        )?;
+        let mut fst2: VectorFst<TropicalWeight> =
+            transducer(&[3, 4], &[5, 6], TropicalWeight::new(2.0));
+        union(
+            &mut fst2,
+            &transducer::<_, VectorFst<_>>(&[3], &[7], TropicalWeight::new(4.0)),
+        )?;
+        tr_sort(&mut fst2, ILabelCompare {});
+
+        let compose_fst = ComposeFst::<_, VectorFst<_>, VectorFst<_>, _, _, _, _, _>::new_auto(
+            Arc::new(fst1),
+            Arc::new(fst2),
+        )?;
+        let paths = kbest_paths_iter_with_heuristic(
+            &compose_fst,
+            |_| TropicalWeight::one(),
+            KBestPathsConfig::default(),
+        )
+        .collect::<Result<Vec<_>>>()?;
+        assert_eq!(paths.len(), 2);
+        assert_eq!(paths[0].ilabels, vec![1, 2]);
+        assert_eq!(paths[0].olabels, vec![5, 6]);
+        assert_eq!(paths[0].weight, TropicalWeight::new(3.0));
+        assert_eq!(paths[1].ilabels, vec![1]);
+        assert_eq!(paths[1].olabels, vec![7]);
+
+        let lazy_paths = kbest_paths_iter(&compose_fst).collect::<Result<Vec<_>>>()?;
+        assert_eq!(lazy_paths, paths);
+        Ok(())
+    }
+
+    #[test]
+    fn test_kbest_paths_compose_fst_dead_zero_cycle() -> Result<()> {
+        // The composition has a zero-weight loop that can't reach a final state.
+        let mut fst1 = VectorFst::<TropicalWeight>::new();
+        let s0 = fst1.add_state();
+        let s1 = fst1.add_state();
+        let s2 = fst1.add_state();
+        fst1.set_start(s0)?;
+        fst1.set_final(s2, TropicalWeight::one())?;
+        fst1.add_tr(s0, Tr::new(1, 1, 0.0, s1))?;
+        fst1.add_tr(s1, Tr::new(3, 3, 0.0, s1))?;
+        fst1.add_tr(s0, Tr::new(2, 2, 1.0, s2))?;
+        let mut fst2: VectorFst<TropicalWeight> = fst1.clone();
+        tr_sort(&mut fst2, ILabelCompare {});
+
+        let compose_fst = ComposeFst::<_, VectorFst<_>, VectorFst<_>, _, _, _, _, _>::new_auto(
+            Arc::new(fst1),
+            Arc::new(fst2),
+        )?;
+        let paths = kbest_paths_iter(&compose_fst).collect::<Result<Vec<_>>>()?;
+        assert_eq!(weights(&paths), vec![1.0]);
+        assert_eq!(paths[0].ilabels, vec![2]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_kbest_paths_non_path_semiring() {
+        let fst: VectorFst<LogWeight> = acceptor(&[1], LogWeight::one());
+        let mut it = kbest_paths_iter(&fst);
+        assert!(it.next().unwrap().is_err());
+        assert!(it.next().is_none());
+    }
+}
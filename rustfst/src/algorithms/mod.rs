@@ -1,20 +1,31 @@
 pub use self::{
     add_super_final_state::add_super_final_state,
     all_pairs_shortest_distance::all_pairs_shortest_distance,
+    astar_shortest_path::{astar_shortest_path, astar_shortest_path_with_config, AStarConfig},
+    cdrewrite::{cdrewrite, CdRewriteDirection, CdRewriteMode},
     condense::condense,
     connect::connect,
+    disambiguate::{disambiguate, disambiguate_with_config, DisambiguateConfig},
+    epsnormalize::{epsnormalize, EpsNormalizeType},
+    equivalent::equivalent,
+    expected_values::expected_values,
     fst_convert::{fst_convert, fst_convert_from_ref},
-    inversion::invert,
+    inversion::{invert, InvertFst},
     isomorphic::{isomorphic, isomorphic_with_config, IsomorphicConfig},
+    kbest_paths::{
+        kbest_paths_iter, kbest_paths_iter_with_config, kbest_paths_iter_with_heuristic,
+        KBestPathsConfig, KBestPathsIterator,
+    },
     minimize::{acceptor_minimize, minimize, minimize_with_config, MinimizeConfig},
     optimize::optimize,
-    projection::{project, ProjectType},
+    projection::{project, ProjectFst, ProjectType},
     push::{
         push, push_weights, push_weights_with_config, push_with_config, PushConfig, PushType,
         PushWeightsConfig,
     },
     queue::{Queue, QueueType},
-    relabel_pairs::relabel_pairs,
+    rand_equivalent::{rand_equivalent, rand_equivalent_with_config, RandEquivalentConfig},
+    relabel_pairs::{relabel_pairs, RelabelFst},
     reverse::reverse,
     reweight::{reweight, ReweightType},
     rm_final_epsilon::rm_final_epsilon,
@@ -22,16 +33,19 @@ pub use self::{
     shortest_path::{shortest_path, shortest_path_with_config, ShortestPathConfig},
     state_sort::state_sort,
     top_sort::top_sort,
-    tr_map::{tr_map, FinalTr, MapFinalAction, TrMapper},
+    tr_map::{tr_map, FinalTr, MapFinalAction, TrMapFst, TrMapper},
     tr_sort::tr_sort,
     tr_sum::tr_sum,
     tr_unique::tr_unique,
+    verify::{verify, FstDefect},
     weight_convert::{weight_convert, WeightConverter},
 };
 
 mod add_super_final_state;
 mod all_pairs_shortest_distance;
-/// Functions to compute Kleene closure (star or plus) of an FST.
+mod astar_shortest_path;
+mod cdrewrite;
+/// Functions to compute Kleene closure (star, plus or bounded) of an FST.
 pub mod closure;
 #[allow(clippy::type_complexity)]
 /// Functions to compose FSTs.
@@ -43,19 +57,31 @@ mod connect;
 /// Functions to determinize FSTs.
 pub mod determinize;
 pub(crate) mod dfs_visit;
+/// Functions to compute the difference between two FSAs.
+pub mod difference;
+mod disambiguate;
 /// Functions to encode FSTs as FSAs and vice versa.
 pub mod encode;
+mod epsnormalize;
+mod equivalent;
+mod expected_values;
 /// Functions to factor various weight types.
 pub mod factor_weight;
 mod fst_convert;
 mod inversion;
 mod isomorphic;
+mod kbest_paths;
 mod minimize;
 mod optimize;
 mod partition;
+/// Functions to manipulate pushdown transducers (PDTs), represented as an FST and a list of parentheses.
+pub mod pdt;
 mod projection;
+/// Functions to prune an FST w.r.t. weight and state thresholds.
+pub mod prune;
 mod push;
 mod queue;
+mod rand_equivalent;
 
 /// Functions to randomly generate paths through an Fst. A static and a delayed version are available.
 pub mod randgen;
@@ -64,13 +90,19 @@ mod relabel_pairs;
 pub mod replace;
 mod reverse;
 mod reweight;
+/// Functions to apply a rewrite rule to a string, in the fashion of Pynini's rewrite library.
+pub mod rewrite;
 
 /// Functions to remove epsilon transitions from an Fst. A static and a delayed version are available.
 pub mod rm_epsilon;
 mod rm_final_epsilon;
 mod shortest_distance;
 mod shortest_path;
+/// Functions and structures to map whole states of an FST. A static and a delayed version are available.
+pub mod state_map;
 mod state_sort;
+/// Functions to synchronize an FST with bounded delay. A static and a delayed version are available.
+pub mod synchronize;
 mod top_sort;
 mod tr_map;
 mod tr_sort;
@@ -78,6 +110,8 @@ mod tr_sum;
 pub(crate) mod tr_unique;
 /// Functions to compute the union of FSTs.
 pub mod union;
+pub(crate) mod union_find;
+mod verify;
 mod weight_convert;
 
 /// Module providing different structures implementing the `Queue` trait.
@@ -0,0 +1,60 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+
+use crate::{Label, EPS_LABEL};
+
+pub use self::pdt_compose::pdt_compose;
+pub use self::pdt_expand::{pdt_expand, pdt_expand_with_config, PdtExpandConfig};
+pub use self::pdt_replace::pdt_replace;
+pub use self::pdt_shortest_path::pdt_shortest_path;
+
+mod pdt_compose;
+mod pdt_expand;
+mod pdt_replace;
+mod pdt_shortest_path;
+
+/// Kind of a label with respect to the parentheses of a PDT.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ParenType {
+    Open(usize),
+    Close(usize),
+    NotParen,
+}
+
+/// Lookup of the parentheses of a PDT, given as a list of `(open, close)` label pairs.
+#[derive(Debug, Clone)]
+pub(crate) struct ParenIndex {
+    parens: HashMap<Label, ParenType>,
+}
+
+impl ParenIndex {
+    pub(crate) fn new(parens: &[(Label, Label)]) -> Result<Self> {
+        let mut index = HashMap::with_capacity(2 * parens.len());
+        for (idx, (open, close)) in parens.iter().enumerate() {
+            for (label, paren_type) in &[
+                (*open, ParenType::Open(idx)),
+                (*close, ParenType::Close(idx)),
+            ] {
+                if *label == EPS_LABEL {
+                    bail!("PDT : epsilon can't be used as a parenthesis")
+                }
+                if index.insert(*label, *paren_type).is_some() {
+                    bail!("PDT : label {} is used by several parentheses", label)
+                }
+            }
+        }
+        Ok(Self { parens: index })
+    }
+
+    pub(crate) fn paren_type(&self, label: Label) -> ParenType {
+        self.parens
+            .get(&label)
+            .copied()
+            .unwrap_or(ParenType::NotParen)
+    }
+
+    pub(crate) fn is_paren(&self, label: Label) -> bool {
+        self.parens.contains_key(&label)
+    }
+}
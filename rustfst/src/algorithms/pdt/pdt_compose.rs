@@ -0,0 +1,124 @@
+use anyhow::Result;
+
+use crate::algorithms::compose::compose;
+use crate::algorithms::pdt::ParenIndex;
+use crate::algorithms::tr_compares::{ILabelCompare, OLabelCompare};
+use crate::algorithms::{fst_convert_from_ref, tr_sort};
+use crate::fst_impls::VectorFst;
+use crate::fst_traits::{AllocableFst, CoreFst, ExpandedFst, MutableFst};
+use crate::semirings::Semiring;
+use crate::{Label, Tr, Trs};
+
+/// Composes a PDT with an FST. The result is a PDT using the same parentheses, whose
+/// balanced paths map the input strings of `pdt` to the output strings of `fst`.
+///
+/// The parentheses are read on the output side of `pdt` and passed through `fst`
+/// with self-loops added on each of its states. They must therefore not be used as
+/// input labels of `fst`.
+///
+/// # Example
+/// ```
+/// # use anyhow::Result;
+/// # use rustfst::algorithms::pdt::{pdt_compose, pdt_expand};
+/// # use rustfst::fst_impls::VectorFst;
+/// # use rustfst::fst_traits::{CoreFst, Fst, MutableFst};
+/// # use rustfst::semirings::{Semiring, TropicalWeight};
+/// # use rustfst::utils::transducer;
+/// # use rustfst::Tr;
+/// # fn main() -> Result<()> {
+/// let mut pdt = VectorFst::<TropicalWeight>::new();
+/// let s0 = pdt.add_state();
+/// let s1 = pdt.add_state();
+/// let s2 = pdt.add_state();
+/// pdt.set_start(s0)?;
+/// pdt.set_final(s2, TropicalWeight::one())?;
+/// pdt.add_tr(s0, Tr::new(10, 10, TropicalWeight::one(), s1))?;
+/// pdt.add_tr(s1, Tr::new(1, 1, TropicalWeight::one(), s1))?;
+/// pdt.add_tr(s1, Tr::new(11, 11, TropicalWeight::one(), s2))?;
+///
+/// let fst : VectorFst<TropicalWeight> = transducer(&[1, 1], &[2], TropicalWeight::new(3.0));
+/// let res : VectorFst<TropicalWeight> = pdt_compose(&pdt, &fst, &[(10, 11)])?;
+/// let expanded : VectorFst<TropicalWeight> = pdt_expand(&res, &[(10, 11)], 1)?;
+/// let path = expanded.paths_iter().next().unwrap();
+/// assert_eq!(path.ilabels, vec![1, 1]);
+/// assert_eq!(path.olabels, vec![2]);
+/// assert_eq!(path.weight, TropicalWeight::new(3.0));
+/// # Ok(())
+/// # }
+/// ```
+pub fn pdt_compose<W, F1, F2, F3>(pdt: &F1, fst: &F2, parens: &[(Label, Label)]) -> Result<F3>
+where
+    W: Semiring,
+    F1: ExpandedFst<W>,
+    F2: ExpandedFst<W>,
+    F3: MutableFst<W> + AllocableFst<W>,
+{
+    let paren_index = ParenIndex::new(parens)?;
+    let mut fst1: VectorFst<W> = fst_convert_from_ref(pdt);
+    let mut fst2: VectorFst<W> = fst_convert_from_ref(fst);
+
+    for state in 0..fst2.num_states() {
+        let state = state as _;
+        if let Some(tr) = fst2
+            .get_trs(state)?
+            .trs()
+            .iter()
+            .find(|tr| paren_index.is_paren(tr.ilabel))
+        {
+            bail!(
+                "PdtCompose : label {} of the FST is used as a parenthesis",
+                tr.ilabel
+            )
+        }
+        for (open, close) in parens {
+            for label in &[*open, *close] {
+                fst2.add_tr(state, Tr::new(*label, *label, W::one(), state))?;
+            }
+        }
+    }
+
+    tr_sort(&mut fst1, OLabelCompare {});
+    tr_sort(&mut fst2, ILabelCompare {});
+    compose::<_, VectorFst<_>, VectorFst<_>, _, _, _>(fst1, fst2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::algorithms::pdt::pdt_expand;
+    use crate::fst_traits::Fst;
+    use crate::semirings::TropicalWeight;
+    use crate::utils::transducer;
+
+    #[test]
+    fn test_pdt_compose_keeps_parens() -> Result<()> {
+        // Open then close the parenthesis around a single label.
+        let mut pdt = VectorFst::<TropicalWeight>::new();
+        let s0 = pdt.add_state();
+        let s1 = pdt.add_state();
+        let s2 = pdt.add_state();
+        let s3 = pdt.add_state();
+        pdt.set_start(s0)?;
+        pdt.set_final(s3, TropicalWeight::one())?;
+        pdt.add_tr(s0, Tr::new(10, 10, TropicalWeight::new(1.0), s1))?;
+        pdt.add_tr(s1, Tr::new(1, 1, TropicalWeight::one(), s2))?;
+        pdt.add_tr(s2, Tr::new(11, 11, TropicalWeight::one(), s3))?;
+
+        let fst: VectorFst<TropicalWeight> = transducer(&[1], &[5], TropicalWeight::new(2.0));
+        let res: VectorFst<TropicalWeight> = pdt_compose(&pdt, &fst, &[(10, 11)])?;
+        let paths: Vec<_> = res.paths_iter().collect();
+        assert_eq!(paths.len(), 1);
+        assert_eq!(paths[0].ilabels, vec![10, 1, 11]);
+        assert_eq!(paths[0].olabels, vec![10, 5, 11]);
+        assert_eq!(paths[0].weight, TropicalWeight::new(3.0));
+
+        let expanded: VectorFst<TropicalWeight> = pdt_expand(&res, &[(10, 11)], 1)?;
+        let paths: Vec<_> = expanded.paths_iter().collect();
+        assert_eq!(paths[0].olabels, vec![5]);
+
+        let bad_fst: VectorFst<TropicalWeight> = transducer(&[10], &[5], TropicalWeight::one());
+        assert!(pdt_compose::<_, _, _, VectorFst<_>>(&pdt, &bad_fst, &[(10, 11)]).is_err());
+        Ok(())
+    }
+}
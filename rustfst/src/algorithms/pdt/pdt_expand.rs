@@ -0,0 +1,251 @@
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, VecDeque};
+
+use anyhow::Result;
+
+use crate::algorithms::connect;
+use crate::algorithms::pdt::{ParenIndex, ParenType};
+use crate::fst_traits::{Fst, MutableFst};
+use crate::semirings::Semiring;
+use crate::{Label, StateId, Tr, Trs, EPS_LABEL};
+
+/// Configuration for [`pdt_expand_with_config`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PdtExpandConfig {
+    /// Maximum number of open parentheses on the stack. The paths going deeper are dropped.
+    pub max_stack_depth: usize,
+    /// Whether the parentheses are kept in the result or replaced by epsilons.
+    pub keep_parentheses: bool,
+}
+
+impl PdtExpandConfig {
+    pub fn new(max_stack_depth: usize, keep_parentheses: bool) -> Self {
+        Self {
+            max_stack_depth,
+            keep_parentheses,
+        }
+    }
+
+    pub fn with_max_stack_depth(self, max_stack_depth: usize) -> Self {
+        Self {
+            max_stack_depth,
+            ..self
+        }
+    }
+
+    pub fn with_keep_parentheses(self, keep_parentheses: bool) -> Self {
+        Self {
+            keep_parentheses,
+            ..self
+        }
+    }
+}
+
+/// Stacks of open parentheses stored as a trie. The stack `0` is the empty one.
+struct PdtStack {
+    // Parent stack, index of the parenthesis on top and depth of each stack.
+    nodes: Vec<(usize, usize, usize)>,
+    children: HashMap<(usize, usize), usize>,
+}
+
+impl PdtStack {
+    fn new() -> Self {
+        Self {
+            nodes: vec![(0, 0, 0)],
+            children: HashMap::new(),
+        }
+    }
+
+    fn push(&mut self, stack: usize, paren: usize, max_depth: usize) -> Option<usize> {
+        let depth = self.nodes[stack].2 + 1;
+        if depth > max_depth {
+            return None;
+        }
+        let nodes = &mut self.nodes;
+        let id = *self.children.entry((stack, paren)).or_insert_with(|| {
+            nodes.push((stack, paren, depth));
+            nodes.len() - 1
+        });
+        Some(id)
+    }
+
+    fn pop(&self, stack: usize, paren: usize) -> Option<usize> {
+        let (parent, top, depth) = self.nodes[stack];
+        if depth > 0 && top == paren {
+            Some(parent)
+        } else {
+            None
+        }
+    }
+}
+
+/// Expands a PDT into an FST by tracking the stack of open parentheses in the states.
+/// A state is final if it is final in the PDT and its stack is empty, and a closing
+/// parenthesis can only be crossed if it matches the one on top of the stack.
+///
+/// The stack of a PDT is unbounded in general, so the expansion only keeps the paths
+/// whose stack never holds more than `max_stack_depth` parentheses : the result is
+/// exact for PDTs whose language is regular with this bound. The parentheses are
+/// replaced by epsilons, see [`pdt_expand_with_config`] to keep them.
+///
+/// # Example
+/// ```
+/// # use anyhow::Result;
+/// # use rustfst::algorithms::pdt::pdt_expand;
+/// # use rustfst::fst_impls::VectorFst;
+/// # use rustfst::fst_traits::{CoreFst, Fst, MutableFst};
+/// # use rustfst::semirings::{Semiring, TropicalWeight};
+/// # use rustfst::Tr;
+/// # fn main() -> Result<()> {
+/// // a^n b^n with the parentheses 10 and 11.
+/// let mut pdt = VectorFst::<TropicalWeight>::new();
+/// let s0 = pdt.add_state();
+/// let s1 = pdt.add_state();
+/// let s2 = pdt.add_state();
+/// let s3 = pdt.add_state();
+/// pdt.set_start(s0)?;
+/// pdt.set_final(s0, TropicalWeight::one())?;
+/// pdt.set_final(s3, TropicalWeight::one())?;
+/// pdt.add_tr(s0, Tr::new(1, 1, TropicalWeight::one(), s1))?;
+/// pdt.add_tr(s1, Tr::new(10, 10, TropicalWeight::one(), s0))?;
+/// pdt.add_tr(s0, Tr::new(11, 11, TropicalWeight::one(), s2))?;
+/// pdt.add_tr(s3, Tr::new(11, 11, TropicalWeight::one(), s2))?;
+/// pdt.add_tr(s2, Tr::new(2, 2, TropicalWeight::one(), s3))?;
+///
+/// let fst : VectorFst<TropicalWeight> = pdt_expand(&pdt, &[(10, 11)], 2)?;
+/// let mut paths : Vec<_> = fst.paths_iter().map(|p| p.ilabels).collect();
+/// paths.sort();
+/// assert_eq!(paths, vec![vec![], vec![1, 1, 2, 2], vec![1, 2]]);
+/// # Ok(())
+/// # }
+/// ```
+pub fn pdt_expand<W, F1, F2>(
+    pdt: &F1,
+    parens: &[(Label, Label)],
+    max_stack_depth: usize,
+) -> Result<F2>
+where
+    W: Semiring,
+    F1: Fst<W>,
+    F2: MutableFst<W>,
+{
+    pdt_expand_with_config(pdt, parens, PdtExpandConfig::new(max_stack_depth, false))
+}
+
+/// Expands a PDT into an FST, see [`pdt_expand`].
+pub fn pdt_expand_with_config<W, F1, F2>(
+    pdt: &F1,
+    parens: &[(Label, Label)],
+    config: PdtExpandConfig,
+) -> Result<F2>
+where
+    W: Semiring,
+    F1: Fst<W>,
+    F2: MutableFst<W>,
+{
+    let parens = ParenIndex::new(parens)?;
+    let mut ofst = F2::new();
+    ofst.set_symts_from_fst(pdt);
+    let start = match pdt.start() {
+        Some(s) => s,
+        None => return Ok(ofst),
+    };
+
+    let mut stacks = PdtStack::new();
+    let mut states: HashMap<(StateId, usize), StateId> = HashMap::new();
+    let mut queue = VecDeque::new();
+    let ostart = ofst.add_state();
+    ofst.set_start(ostart)?;
+    states.insert((start, 0), ostart);
+    queue.push_back((start, 0));
+
+    while let Some((state, stack)) = queue.pop_front() {
+        let ostate = states[&(state, stack)];
+        if stack == 0 {
+            if let Some(final_weight) = pdt.final_weight(state)? {
+                ofst.set_final(ostate, final_weight)?;
+            }
+        }
+        for tr in pdt.get_trs(state)?.trs() {
+            let paren_type = parens.paren_type(tr.ilabel);
+            let next_stack = match paren_type {
+                ParenType::NotParen => Some(stack),
+                ParenType::Open(idx) => stacks.push(stack, idx, config.max_stack_depth),
+                ParenType::Close(idx) => stacks.pop(stack, idx),
+            };
+            let next_stack = match next_stack {
+                Some(s) => s,
+                None => continue,
+            };
+            let nextstate = match states.entry((tr.nextstate, next_stack)) {
+                Entry::Occupied(e) => *e.get(),
+                Entry::Vacant(e) => {
+                    queue.push_back((tr.nextstate, next_stack));
+                    *e.insert(ofst.add_state())
+                }
+            };
+            let (ilabel, olabel) = if paren_type == ParenType::NotParen || config.keep_parentheses {
+                (tr.ilabel, tr.olabel)
+            } else {
+                (EPS_LABEL, EPS_LABEL)
+            };
+            ofst.add_tr(
+                ostate,
+                Tr::new(ilabel, olabel, tr.weight.clone(), nextstate),
+            )?;
+        }
+    }
+
+    connect(&mut ofst)?;
+    Ok(ofst)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::fst_impls::VectorFst;
+    use crate::fst_traits::ExpandedFst;
+    use crate::semirings::TropicalWeight;
+
+    #[test]
+    fn test_pdt_expand_mismatched_parens() -> Result<()> {
+        // Two pairs of parentheses, only the matching ones lead to the final state.
+        let mut pdt = VectorFst::<TropicalWeight>::new();
+        let s0 = pdt.add_state();
+        let s1 = pdt.add_state();
+        let s2 = pdt.add_state();
+        let s3 = pdt.add_state();
+        pdt.set_start(s0)?;
+        pdt.set_final(s3, TropicalWeight::new(0.5))?;
+        pdt.add_tr(s0, Tr::new(10, 10, TropicalWeight::new(1.0), s1))?;
+        pdt.add_tr(s0, Tr::new(20, 20, TropicalWeight::new(2.0), s1))?;
+        pdt.add_tr(s1, Tr::new(1, 2, TropicalWeight::one(), s2))?;
+        pdt.add_tr(s2, Tr::new(11, 11, TropicalWeight::one(), s3))?;
+
+        let parens = [(10, 11), (20, 21)];
+        let fst: VectorFst<TropicalWeight> = pdt_expand(&pdt, &parens, 1)?;
+        let paths: Vec<_> = fst.paths_iter().collect();
+        assert_eq!(paths.len(), 1);
+        assert_eq!(paths[0].ilabels, vec![1]);
+        assert_eq!(paths[0].olabels, vec![2]);
+        assert_eq!(paths[0].weight, TropicalWeight::new(1.5));
+
+        let config = PdtExpandConfig::new(1, true);
+        let fst: VectorFst<TropicalWeight> = pdt_expand_with_config(&pdt, &parens, config)?;
+        let paths: Vec<_> = fst.paths_iter().collect();
+        assert_eq!(paths.len(), 1);
+        assert_eq!(paths[0].ilabels, vec![10, 1, 11]);
+
+        let fst: VectorFst<TropicalWeight> = pdt_expand(&pdt, &parens, 0)?;
+        assert_eq!(fst.num_states(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_pdt_expand_invalid_parens() {
+        let pdt = VectorFst::<TropicalWeight>::new();
+        assert!(pdt_expand::<_, _, VectorFst<_>>(&pdt, &[(1, 2), (2, 3)], 1).is_err());
+        assert!(pdt_expand::<_, _, VectorFst<_>>(&pdt, &[(0, 1)], 1).is_err());
+    }
+}
@@ -0,0 +1,236 @@
+use std::borrow::Borrow;
+use std::collections::HashMap;
+
+use anyhow::Result;
+
+use crate::fst_traits::{Fst, MutableFst};
+use crate::semirings::Semiring;
+use crate::{Label, StateId, Tr, Trs, EPS_LABEL};
+
+/// Replaces the nonterminals of a set of FSTs by calls through parentheses, as
+/// [`ReplaceFst::new`](crate::algorithms::replace::ReplaceFst::new) does with a stack, and
+/// returns the resulting PDT along with its parentheses. Contrary to `ReplaceFst`, the
+/// FSTs may be recursive.
+///
+/// Each transition whose output label is one of the nonterminals of `fst_list` calls the
+/// corresponding FST. A pair of parentheses is allocated for each of these transitions,
+/// with labels greater than all the labels of the FSTs : the open parenthesis goes to the
+/// start state of the called FST and the close parenthesis goes from each of its final
+/// states, with the final weight, back to the destination of the call. The input label of
+/// the call transition is kept before the open parenthesis unless `epsilon_on_replace` is set.
+///
+/// # Example
+/// ```
+/// # use anyhow::Result;
+/// # use rustfst::algorithms::pdt::{pdt_expand, pdt_replace};
+/// # use rustfst::fst_impls::VectorFst;
+/// # use rustfst::fst_traits::{CoreFst, Fst, MutableFst};
+/// # use rustfst::semirings::{Semiring, TropicalWeight};
+/// # use rustfst::Tr;
+/// # fn main() -> Result<()> {
+/// // S -> a S b | <eps>
+/// let mut fst = VectorFst::<TropicalWeight>::new();
+/// let s0 = fst.add_state();
+/// let s1 = fst.add_state();
+/// let s2 = fst.add_state();
+/// let s3 = fst.add_state();
+/// fst.set_start(s0)?;
+/// fst.set_final(s0, TropicalWeight::one())?;
+/// fst.set_final(s3, TropicalWeight::one())?;
+/// fst.add_tr(s0, Tr::new(1, 1, TropicalWeight::one(), s1))?;
+/// fst.add_tr(s1, Tr::new(0, 100, TropicalWeight::one(), s2))?;
+/// fst.add_tr(s2, Tr::new(2, 2, TropicalWeight::one(), s3))?;
+///
+/// let (pdt, parens) : (VectorFst<TropicalWeight>, _) =
+///     pdt_replace::<_, VectorFst<_>, _, _>(vec![(100, &fst)], 100, true)?;
+/// assert_eq!(parens, vec![(101, 102)]);
+/// let expanded : VectorFst<TropicalWeight> = pdt_expand(&pdt, &parens, 2)?;
+/// let mut paths : Vec<_> = expanded.paths_iter().map(|p| p.ilabels).collect();
+/// paths.sort();
+/// assert_eq!(paths, vec![vec![], vec![1, 1, 2, 2], vec![1, 2]]);
+/// # Ok(())
+/// # }
+/// ```
+pub fn pdt_replace<W, F1, B, F2>(
+    fst_list: Vec<(Label, B)>,
+    root: Label,
+    epsilon_on_replace: bool,
+) -> Result<(F2, Vec<(Label, Label)>)>
+where
+    W: Semiring,
+    F1: Fst<W>,
+    B: Borrow<F1>,
+    F2: MutableFst<W>,
+{
+    let nonterminals: HashMap<Label, usize> = fst_list
+        .iter()
+        .enumerate()
+        .map(|(idx, (label, _))| (*label, idx))
+        .collect();
+    if !nonterminals.contains_key(&root) {
+        bail!(
+            "PdtReplace: No FST corresponding to root label {} in the input tuple vector",
+            root
+        )
+    }
+
+    let mut max_label = nonterminals.keys().copied().max().unwrap_or(0);
+    for (_, fst) in fst_list.iter() {
+        let fst = fst.borrow();
+        for state in fst.states_iter() {
+            for tr in fst.get_trs(state)?.trs() {
+                max_label = max_label.max(tr.ilabel).max(tr.olabel);
+            }
+        }
+    }
+
+    let mut ofst = F2::new();
+    if let Some((_, fst)) = fst_list.first() {
+        ofst.set_symts_from_fst(fst.borrow());
+    }
+    // Offsets of the states of each FST in the PDT.
+    let mut offsets = Vec::with_capacity(fst_list.len());
+    for (_, fst) in fst_list.iter() {
+        offsets.push(ofst.num_states() as StateId);
+        for _ in fst.borrow().states_iter() {
+            ofst.add_state();
+        }
+    }
+
+    let mut parens = vec![];
+    for (idx, (label, fst)) in fst_list.iter().enumerate() {
+        let fst = fst.borrow();
+        let offset = offsets[idx];
+        for state in fst.states_iter() {
+            let ostate = state + offset;
+            if *label == root {
+                if let Some(final_weight) = fst.final_weight(state)? {
+                    ofst.set_final(ostate, final_weight)?;
+                }
+            }
+            for tr in fst.get_trs(state)?.trs() {
+                let nextstate = tr.nextstate + offset;
+                let callee_idx = match nonterminals.get(&tr.olabel) {
+                    Some(callee_idx) => *callee_idx,
+                    None => {
+                        ofst.add_tr(
+                            ostate,
+                            Tr::new(tr.ilabel, tr.olabel, tr.weight.clone(), nextstate),
+                        )?;
+                        continue;
+                    }
+                };
+                // Calls to an empty FST can't succeed.
+                let callee = fst_list[callee_idx].1.borrow();
+                let callee_start = match callee.start() {
+                    Some(s) => s + offsets[callee_idx],
+                    None => continue,
+                };
+                let open = max_label + 2 * parens.len() as Label + 1;
+                let close = open + 1;
+                parens.push((open, close));
+
+                if epsilon_on_replace {
+                    ofst.add_tr(ostate, Tr::new(open, open, tr.weight.clone(), callee_start))?;
+                } else {
+                    let call_state = ofst.add_state();
+                    ofst.add_tr(
+                        ostate,
+                        Tr::new(tr.ilabel, EPS_LABEL, tr.weight.clone(), call_state),
+                    )?;
+                    ofst.add_tr(call_state, Tr::new(open, open, W::one(), callee_start))?;
+                }
+                for final_state in callee.final_states_iter() {
+                    if let Some(final_weight) = callee.final_weight(final_state)? {
+                        ofst.add_tr(
+                            final_state + offsets[callee_idx],
+                            Tr::new(close, close, final_weight, nextstate),
+                        )?;
+                    }
+                }
+            }
+        }
+    }
+
+    if let Some(start) = fst_list[nonterminals[&root]].1.borrow().start() {
+        ofst.set_start(start + offsets[nonterminals[&root]])?;
+    }
+    Ok((ofst, parens))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::algorithms::pdt::{pdt_expand, pdt_shortest_path};
+    use crate::algorithms::replace::ReplaceFst;
+    use crate::fst_impls::VectorFst;
+    use crate::semirings::TropicalWeight;
+    use crate::utils::{decode_linear_fst, transducer};
+
+    fn sorted_paths(fst: &VectorFst<TropicalWeight>) -> Vec<(Vec<Label>, Vec<Label>)> {
+        let mut paths: Vec<_> = fst
+            .paths_iter()
+            .map(|p| {
+                let ilabels = p.ilabels.into_iter().filter(|l| *l != EPS_LABEL).collect();
+                let olabels = p.olabels.into_iter().filter(|l| *l != EPS_LABEL).collect();
+                (ilabels, olabels)
+            })
+            .collect();
+        paths.sort();
+        paths
+    }
+
+    #[test]
+    fn test_pdt_replace_same_as_replace_fst() -> Result<()> {
+        let mut root: VectorFst<TropicalWeight> =
+            transducer(&[1, 2], &[1, 10], TropicalWeight::new(1.0));
+        root.add_tr(1, Tr::new(3, 20, TropicalWeight::new(2.0), 2))?;
+        let fst1: VectorFst<TropicalWeight> = transducer(&[4], &[5], TropicalWeight::new(0.5));
+        let fst2: VectorFst<TropicalWeight> = transducer(&[6, 7], &[9, 8], TropicalWeight::one());
+        let fst_list = vec![(100, &root), (10, &fst1), (20, &fst2)];
+
+        for epsilon_on_replace in &[true, false] {
+            let replaced: VectorFst<TropicalWeight> =
+                ReplaceFst::<_, VectorFst<_>, _>::new(fst_list.clone(), 100, *epsilon_on_replace)?
+                    .compute()?;
+            let (pdt, parens): (VectorFst<TropicalWeight>, _) =
+                pdt_replace::<_, VectorFst<_>, _, _>(fst_list.clone(), 100, *epsilon_on_replace)?;
+            assert_eq!(parens, vec![(101, 102), (103, 104)]);
+            let expanded: VectorFst<TropicalWeight> = pdt_expand(&pdt, &parens, 2)?;
+            assert_eq!(sorted_paths(&expanded), sorted_paths(&replaced));
+        }
+
+        assert!(pdt_replace::<_, VectorFst<_>, _, VectorFst<_>>(fst_list, 1, true).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_pdt_replace_recursive() -> Result<()> {
+        // S -> a S b <1> | c <3>
+        let mut fst = VectorFst::<TropicalWeight>::new();
+        let s0 = fst.add_state();
+        let s1 = fst.add_state();
+        let s2 = fst.add_state();
+        let s3 = fst.add_state();
+        fst.set_start(s0)?;
+        fst.set_final(s3, TropicalWeight::one())?;
+        fst.add_tr(s0, Tr::new(1, 1, TropicalWeight::new(1.0), s1))?;
+        fst.add_tr(s1, Tr::new(EPS_LABEL, 100, TropicalWeight::one(), s2))?;
+        fst.add_tr(s2, Tr::new(2, 2, TropicalWeight::one(), s3))?;
+        fst.add_tr(s0, Tr::new(3, 3, TropicalWeight::new(3.0), s3))?;
+
+        let (pdt, parens): (VectorFst<TropicalWeight>, _) =
+            pdt_replace::<_, VectorFst<_>, _, _>(vec![(100, &fst)], 100, true)?;
+        let expanded: VectorFst<TropicalWeight> = pdt_expand(&pdt, &parens, 2)?;
+        let paths = sorted_paths(&expanded);
+        let ilabels: Vec<_> = paths.into_iter().map(|p| p.0).collect();
+        assert_eq!(ilabels, vec![vec![1, 1, 3, 2, 2], vec![1, 3, 2], vec![3]]);
+
+        let res: VectorFst<TropicalWeight> = pdt_shortest_path(&pdt, &parens)?;
+        let path = decode_linear_fst(&res)?;
+        assert_eq!(path.ilabels, vec![3]);
+        assert_eq!(path.weight, TropicalWeight::new(3.0));
+        Ok(())
+    }
+}
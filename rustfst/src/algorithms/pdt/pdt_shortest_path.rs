@@ -0,0 +1,387 @@
+use std::cmp::Ordering;
+use std::collections::hash_map::Entry;
+use std::collections::{BinaryHeap, HashMap};
+
+use anyhow::Result;
+
+use crate::algorithms::pdt::{ParenIndex, ParenType};
+use crate::algorithms::shortest_path::natural_less;
+use crate::fst_properties::mutable_properties::shortest_path_properties;
+use crate::fst_properties::FstProperties;
+use crate::fst_traits::{Fst, MutableFst};
+use crate::semirings::{Semiring, SemiringProperties};
+use crate::{Label, StateId, Tr, Trs};
+
+/// How the best balanced path of an item was reached.
+enum PdtBackPointer<W: Semiring> {
+    /// First state of the context.
+    Start,
+    /// Through a transition which isn't a parenthesis from the previous item.
+    Tr(usize, Tr<W>),
+    /// From the previous item through an open parenthesis, a balanced path of the
+    /// inner item and the matching close parenthesis.
+    Call(usize, Tr<W>, usize, Tr<W>),
+}
+
+/// Best balanced path found so far from the context state to the state of an item.
+struct PdtEntry<W: Semiring> {
+    distance: W,
+    settled: bool,
+    back: PdtBackPointer<W>,
+}
+
+struct PdtHeapItem<W: Semiring> {
+    distance: W,
+    item: usize,
+    // Insertion order, used to break ties and get a deterministic output.
+    id: usize,
+}
+
+impl<W: Semiring> PartialEq for PdtHeapItem<W> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<W: Semiring> Eq for PdtHeapItem<W> {}
+
+impl<W: Semiring> PartialOrd for PdtHeapItem<W> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<W: Semiring> Ord for PdtHeapItem<W> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // BinaryHeap is a max-heap, the order is reversed to pop the best distance first.
+        if natural_less(&self.distance, &other.distance).unwrap_or(false) {
+            Ordering::Greater
+        } else if natural_less(&other.distance, &self.distance).unwrap_or(false) {
+            Ordering::Less
+        } else {
+            other.id.cmp(&self.id)
+        }
+    }
+}
+
+/// Items are pairs `(context, state)` where the context is either the start state
+/// or the destination of an open parenthesis.
+struct PdtShortestPathState<W: Semiring> {
+    entries: Vec<PdtEntry<W>>,
+    keys: Vec<(StateId, StateId)>,
+    index: HashMap<(StateId, StateId), usize>,
+    heap: BinaryHeap<PdtHeapItem<W>>,
+    num_pushed: usize,
+}
+
+impl<W: Semiring> PdtShortestPathState<W> {
+    fn new() -> Self {
+        Self {
+            entries: vec![],
+            keys: vec![],
+            index: HashMap::new(),
+            heap: BinaryHeap::new(),
+            num_pushed: 0,
+        }
+    }
+
+    fn relax(
+        &mut self,
+        key: (StateId, StateId),
+        distance: W,
+        back: PdtBackPointer<W>,
+    ) -> Result<()> {
+        let item = match self.index.entry(key) {
+            Entry::Occupied(e) => {
+                let entry = &mut self.entries[*e.get()];
+                if entry.settled || !natural_less(&distance, &entry.distance)? {
+                    return Ok(());
+                }
+                entry.distance = distance.clone();
+                entry.back = back;
+                *e.get()
+            }
+            Entry::Vacant(e) => {
+                self.entries.push(PdtEntry {
+                    distance: distance.clone(),
+                    settled: false,
+                    back,
+                });
+                self.keys.push(key);
+                *e.insert(self.entries.len() - 1)
+            }
+        };
+        self.heap.push(PdtHeapItem {
+            distance,
+            item,
+            id: self.num_pushed,
+        });
+        self.num_pushed += 1;
+        Ok(())
+    }
+
+    /// Transitions of the best balanced path of an item, from the last one to the first one.
+    fn backtrace(&self, item: usize) -> Vec<Tr<W>> {
+        enum Step<'a, W: Semiring> {
+            Item(usize),
+            Tr(&'a Tr<W>),
+        }
+        let mut trs = vec![];
+        let mut todo = vec![Step::Item(item)];
+        while let Some(step) = todo.pop() {
+            match step {
+                Step::Tr(tr) => trs.push(tr.clone()),
+                Step::Item(item) => match &self.entries[item].back {
+                    PdtBackPointer::Start => {}
+                    PdtBackPointer::Tr(prev, tr) => {
+                        trs.push(tr.clone());
+                        todo.push(Step::Item(*prev));
+                    }
+                    PdtBackPointer::Call(prev, open, inner, close) => {
+                        trs.push(close.clone());
+                        todo.push(Step::Item(*prev));
+                        todo.push(Step::Tr(open));
+                        todo.push(Step::Item(*inner));
+                    }
+                },
+            }
+        }
+        trs
+    }
+}
+
+/// Computes the shortest balanced path of a PDT, i.e. the best path from the start
+/// state to a final state whose parentheses are well nested. The result is a linear
+/// FST which keeps the parentheses, or an empty FST if there is no balanced path.
+///
+/// The weight must have the path property and extending a path must not improve its
+/// weight (e.g. no negative weights in the tropical semiring).
+///
+/// # Example
+/// ```
+/// # use anyhow::Result;
+/// # use rustfst::algorithms::pdt::pdt_shortest_path;
+/// # use rustfst::fst_impls::VectorFst;
+/// # use rustfst::fst_traits::{CoreFst, MutableFst};
+/// # use rustfst::semirings::{Semiring, TropicalWeight};
+/// # use rustfst::utils::decode_linear_fst;
+/// # use rustfst::Tr;
+/// # fn main() -> Result<()> {
+/// let mut pdt = VectorFst::<TropicalWeight>::new();
+/// let s0 = pdt.add_state();
+/// let s1 = pdt.add_state();
+/// let s2 = pdt.add_state();
+/// let s3 = pdt.add_state();
+/// pdt.set_start(s0)?;
+/// pdt.add_tr(s0, Tr::new(10, 10, TropicalWeight::one(), s1))?;
+/// pdt.add_tr(s1, Tr::new(1, 1, TropicalWeight::new(1.0), s2))?;
+/// pdt.add_tr(s2, Tr::new(11, 11, TropicalWeight::new(0.5), s3))?;
+/// pdt.set_final(s3, TropicalWeight::one())?;
+/// // Cheaper but the parenthesis is never closed.
+/// pdt.set_final(s2, TropicalWeight::one())?;
+///
+/// let res : VectorFst<TropicalWeight> = pdt_shortest_path(&pdt, &[(10, 11)])?;
+/// let path = decode_linear_fst(&res)?;
+/// assert_eq!(path.ilabels, vec![10, 1, 11]);
+/// assert_eq!(path.weight, TropicalWeight::new(1.5));
+/// # Ok(())
+/// # }
+/// ```
+pub fn pdt_shortest_path<W, F1, F2>(pdt: &F1, parens: &[(Label, Label)]) -> Result<F2>
+where
+    W: Semiring,
+    F1: Fst<W>,
+    F2: MutableFst<W>,
+{
+    if !W::properties().contains(SemiringProperties::PATH | SemiringProperties::RIGHT_SEMIRING) {
+        bail!("PdtShortestPath: Weight needs to have the path property and be right distributive")
+    }
+    let parens = ParenIndex::new(parens)?;
+    let mut ofst = F2::new();
+    ofst.set_symts_from_fst(pdt);
+    let start = match pdt.start() {
+        Some(s) => s,
+        None => return Ok(ofst),
+    };
+
+    let mut state = PdtShortestPathState::new();
+    // Items waiting for a close parenthesis of each context, with the open transition.
+    let mut callers: HashMap<StateId, Vec<(usize, Tr<W>)>> = HashMap::new();
+    // Settled items of each context.
+    let mut settled: HashMap<StateId, Vec<usize>> = HashMap::new();
+    // Best final item along with the weight of the path ending there.
+    let mut best: Option<(usize, W, W)> = None;
+
+    state.relax((start, start), W::one(), PdtBackPointer::Start)?;
+    while let Some(PdtHeapItem { distance, item, .. }) = state.heap.pop() {
+        if state.entries[item].settled || natural_less(&state.entries[item].distance, &distance)? {
+            continue;
+        }
+        state.entries[item].settled = true;
+        let (context, q) = state.keys[item];
+        settled.entry(context).or_default().push(item);
+
+        if context == start {
+            if let Some(final_weight) = pdt.final_weight(q)? {
+                let weight = distance.times(&final_weight)?;
+                let improves = match &best {
+                    Some((_, w, _)) => natural_less(&weight, w)?,
+                    None => true,
+                };
+                if improves {
+                    best = Some((item, weight, final_weight));
+                }
+            }
+        }
+
+        for tr in pdt.get_trs(q)?.trs() {
+            match parens.paren_type(tr.ilabel) {
+                ParenType::NotParen => {
+                    state.relax(
+                        (context, tr.nextstate),
+                        distance.times(&tr.weight)?,
+                        PdtBackPointer::Tr(item, tr.clone()),
+                    )?;
+                }
+                ParenType::Open(idx) => {
+                    let inner_context = tr.nextstate;
+                    state.relax(
+                        (inner_context, inner_context),
+                        W::one(),
+                        PdtBackPointer::Start,
+                    )?;
+                    callers
+                        .entry(inner_context)
+                        .or_default()
+                        .push((item, tr.clone()));
+                    // Balanced paths of the inner context already found.
+                    let inner_items = settled.get(&inner_context).cloned().unwrap_or_default();
+                    let prefix = distance.times(&tr.weight)?;
+                    for inner in inner_items {
+                        let (_, t) = state.keys[inner];
+                        let inner_distance = prefix.times(&state.entries[inner].distance)?;
+                        for close in pdt.get_trs(t)?.trs() {
+                            if parens.paren_type(close.ilabel) == ParenType::Close(idx) {
+                                state.relax(
+                                    (context, close.nextstate),
+                                    inner_distance.times(&close.weight)?,
+                                    PdtBackPointer::Call(item, tr.clone(), inner, close.clone()),
+                                )?;
+                            }
+                        }
+                    }
+                }
+                ParenType::Close(idx) => {
+                    let context_callers = callers.get(&context).cloned().unwrap_or_default();
+                    for (caller, open) in context_callers {
+                        if parens.paren_type(open.ilabel) != ParenType::Open(idx) {
+                            continue;
+                        }
+                        let (caller_context, _) = state.keys[caller];
+                        let weight = state.entries[caller]
+                            .distance
+                            .times(&open.weight)?
+                            .times(&distance)?
+                            .times(&tr.weight)?;
+                        state.relax(
+                            (caller_context, tr.nextstate),
+                            weight,
+                            PdtBackPointer::Call(caller, open, item, tr.clone()),
+                        )?;
+                    }
+                }
+            }
+        }
+    }
+
+    let (item, final_weight) = match best {
+        Some((item, _, final_weight)) => (item, final_weight),
+        None => return Ok(ofst),
+    };
+    // The states are numbered from the final state to the start state, as in `shortest_path`.
+    let mut s_p = ofst.add_state();
+    ofst.set_final(s_p, final_weight)?;
+    for mut tr in state.backtrace(item) {
+        let d_p = s_p;
+        s_p = ofst.add_state();
+        tr.nextstate = d_p;
+        ofst.add_tr(s_p, tr)?;
+    }
+    ofst.set_start(s_p)?;
+    ofst.set_properties_with_mask(
+        shortest_path_properties(ofst.properties(), true),
+        FstProperties::all_properties(),
+    );
+    Ok(ofst)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::algorithms::pdt::pdt_expand;
+    use crate::algorithms::shortest_path;
+    use crate::fst_impls::VectorFst;
+    use crate::fst_traits::ExpandedFst;
+    use crate::semirings::{LogWeight, TropicalWeight};
+    use crate::utils::{acceptor, decode_linear_fst};
+
+    /// S -> a S b <1> | c <3>, where S is called through the parentheses 10 and 11.
+    fn build_pdt() -> Result<VectorFst<TropicalWeight>> {
+        let mut pdt = VectorFst::<TropicalWeight>::new();
+        let s0 = pdt.add_state();
+        let s1 = pdt.add_state();
+        let s2 = pdt.add_state();
+        let s3 = pdt.add_state();
+        pdt.set_start(s0)?;
+        pdt.set_final(s3, TropicalWeight::one())?;
+        pdt.add_tr(s0, Tr::new(1, 1, TropicalWeight::new(1.0), s1))?;
+        pdt.add_tr(s1, Tr::new(10, 10, TropicalWeight::one(), s0))?;
+        pdt.add_tr(s3, Tr::new(11, 11, TropicalWeight::one(), s2))?;
+        pdt.add_tr(s2, Tr::new(2, 2, TropicalWeight::one(), s3))?;
+        pdt.add_tr(s0, Tr::new(3, 3, TropicalWeight::new(3.0), s3))?;
+        Ok(pdt)
+    }
+
+    #[test]
+    fn test_pdt_shortest_path_recursive() -> Result<()> {
+        let mut pdt = build_pdt()?;
+        let res: VectorFst<TropicalWeight> = pdt_shortest_path(&pdt, &[(10, 11)])?;
+        let path = decode_linear_fst(&res)?;
+        assert_eq!(path.ilabels, vec![3]);
+        assert_eq!(path.weight, TropicalWeight::new(3.0));
+
+        // A new start state forcing at least one call of S.
+        let start = pdt.add_state();
+        pdt.set_start(start)?;
+        pdt.add_tr(start, Tr::new(1, 1, TropicalWeight::new(0.5), 1))?;
+        pdt.add_tr(3, Tr::new(4, 4, TropicalWeight::new(0.25), 3))?;
+        let res: VectorFst<TropicalWeight> = pdt_shortest_path(&pdt, &[(10, 11)])?;
+        let path = decode_linear_fst(&res)?;
+        assert_eq!(path.ilabels, vec![1, 10, 3, 11, 2]);
+        assert_eq!(path.weight, TropicalWeight::new(3.5));
+
+        let expanded: VectorFst<TropicalWeight> = pdt_expand(&pdt, &[(10, 11)], 3)?;
+        let ref_path = decode_linear_fst(&shortest_path::<_, _, VectorFst<_>>(&expanded)?)?;
+        assert_eq!(ref_path.ilabels, vec![1, 3, 2]);
+        assert_eq!(ref_path.weight, path.weight);
+        Ok(())
+    }
+
+    #[test]
+    fn test_pdt_shortest_path_unbalanced() -> Result<()> {
+        let mut pdt = VectorFst::<TropicalWeight>::new();
+        let s0 = pdt.add_state();
+        let s1 = pdt.add_state();
+        pdt.set_start(s0)?;
+        pdt.set_final(s1, TropicalWeight::one())?;
+        pdt.add_tr(s0, Tr::new(10, 10, TropicalWeight::one(), s1))?;
+        let res: VectorFst<TropicalWeight> = pdt_shortest_path(&pdt, &[(10, 11)])?;
+        assert_eq!(res.num_states(), 0);
+
+        let fst: VectorFst<LogWeight> = acceptor(&[1], LogWeight::one());
+        let res: Result<VectorFst<LogWeight>> = pdt_shortest_path(&fst, &[(10, 11)]);
+        assert!(res.is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,5 @@
+mod project_fst;
+mod projection_static;
+
+pub use project_fst::ProjectFst;
+pub use projection_static::{project, ProjectType};
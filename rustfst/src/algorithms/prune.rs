@@ -0,0 +1,512 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+use anyhow::Result;
+
+use crate::algorithms::shortest_path::natural_less;
+use crate::algorithms::{shortest_distance_with_config, ShortestDistanceConfig};
+use crate::fst_traits::{CoreFst, ExpandedFst, MutableFst};
+use crate::semirings::{Semiring, SemiringProperties};
+use crate::{StateId, Trs, KSHORTESTDELTA};
+
+/// Configuration for pruning.
+#[derive(Debug, Clone, PartialOrd, PartialEq)]
+pub struct PruneConfig<W: Semiring> {
+    /// Pruning weight threshold. Paths with a weight worse than the weight
+    /// of the best path times this threshold are removed. `W::zero()` disables it.
+    pub weight_threshold: W,
+    /// Maximum number of states to keep in the output.
+    pub state_threshold: Option<StateId>,
+    /// Comparison / quantization delta used for the shortest distance computation.
+    pub delta: f32,
+}
+
+impl<W: Semiring> Default for PruneConfig<W> {
+    fn default() -> Self {
+        Self {
+            weight_threshold: W::zero(),
+            state_threshold: None,
+            delta: KSHORTESTDELTA,
+        }
+    }
+}
+
+impl<W: Semiring> PruneConfig<W> {
+    pub fn new(weight_threshold: W, state_threshold: Option<StateId>, delta: f32) -> Self {
+        Self {
+            weight_threshold,
+            state_threshold,
+            delta,
+        }
+    }
+
+    pub fn with_weight_threshold(self, weight_threshold: W) -> Self {
+        Self {
+            weight_threshold,
+            ..self
+        }
+    }
+
+    pub fn with_state_threshold(self, state_threshold: Option<StateId>) -> Self {
+        Self {
+            state_threshold,
+            ..self
+        }
+    }
+
+    pub fn with_delta(self, delta: f32) -> Self {
+        Self { delta, ..self }
+    }
+}
+
+/// Element of the priority queue used to visit the states : the best states
+/// (w.r.t. the natural order) are popped first.
+struct PruneHeapItem<W: Semiring> {
+    weight: W,
+    state: StateId,
+}
+
+impl<W: Semiring> PartialEq for PruneHeapItem<W> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<W: Semiring> Eq for PruneHeapItem<W> {}
+
+impl<W: Semiring> PartialOrd for PruneHeapItem<W> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<W: Semiring> Ord for PruneHeapItem<W> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // BinaryHeap is a max-heap, the order is reversed to pop the best weight first.
+        if natural_less(&self.weight, &other.weight).unwrap_or(false) {
+            Ordering::Greater
+        } else if natural_less(&other.weight, &self.weight).unwrap_or(false) {
+            Ordering::Less
+        } else {
+            other.state.cmp(&self.state)
+        }
+    }
+}
+
+fn check_path_property<W: Semiring>() -> Result<()> {
+    if !W::properties().contains(SemiringProperties::PATH) {
+        bail!("Prune: Weight needs to have the path property")
+    }
+    Ok(())
+}
+
+fn push_state<W: Semiring>(
+    heap: &mut BinaryHeap<PruneHeapItem<W>>,
+    state: StateId,
+    idistance: &W,
+    fdistance: W,
+) -> Result<()> {
+    let weight = idistance.times(fdistance)?;
+    heap.push(PruneHeapItem { weight, state });
+    Ok(())
+}
+
+fn distance_or_zero<W: Semiring>(distance: &[W], state: StateId) -> W {
+    distance
+        .get(state as usize)
+        .cloned()
+        .unwrap_or_else(W::zero)
+}
+
+/// Prunes an FST in place. Deletes the states and transitions that do not
+/// belong to a successful path whose weight is no more than the weight of
+/// the shortest path times `weight_threshold`. When `state_threshold` is set,
+/// at most this number of states are kept.
+///
+/// The weight must have the path property.
+///
+/// # Example
+/// ```
+/// # #[macro_use] extern crate rustfst;
+/// # use anyhow::Result;
+/// # use rustfst::utils::acceptor;
+/// # use rustfst::semirings::{Semiring, TropicalWeight};
+/// # use rustfst::fst_impls::VectorFst;
+/// # use rustfst::fst_traits::Fst;
+/// # use rustfst::algorithms::prune::{prune, PruneConfig};
+/// # use rustfst::algorithms::union::union;
+/// # fn main() -> Result<()> {
+/// let mut fst : VectorFst<TropicalWeight> = fst![1,2; 1.0];
+/// let other : VectorFst<TropicalWeight> = fst![3; 5.0];
+/// union(&mut fst, &other)?;
+///
+/// prune(&mut fst, PruneConfig::default().with_weight_threshold(TropicalWeight::new(2.0)))?;
+/// let paths : Vec<_> = fst.paths_iter().collect();
+/// assert_eq!(paths.len(), 1);
+/// assert_eq!(paths[0].ilabels, vec![1, 2]);
+/// # Ok(())
+/// # }
+/// ```
+pub fn prune<W: Semiring, F: MutableFst<W>>(fst: &mut F, config: PruneConfig<W>) -> Result<()> {
+    check_path_property::<W>()?;
+    let start = match fst.start() {
+        Some(s) => s,
+        None => return Ok(()),
+    };
+    let fdistance =
+        shortest_distance_with_config(fst, true, ShortestDistanceConfig::new(config.delta))?;
+    if config.state_threshold == Some(0)
+        || fdistance.len() <= start as usize
+        || fdistance[start as usize].is_zero()
+    {
+        fst.del_all_states();
+        return Ok(());
+    }
+
+    let num_states = fst.num_states();
+    let limit = if config.weight_threshold.is_zero() {
+        W::zero()
+    } else {
+        fdistance[start as usize].times(&config.weight_threshold)?
+    };
+
+    let mut idistance = vec![W::zero(); num_states];
+    let mut visited = vec![false; num_states];
+    let mut enqueued = vec![false; num_states];
+    let mut heap = BinaryHeap::new();
+    let mut num_visited = 0;
+
+    if !natural_less(&limit, &fdistance[start as usize])? {
+        idistance[start as usize] = W::one();
+        push_state(
+            &mut heap,
+            start,
+            &idistance[start as usize],
+            distance_or_zero(&fdistance, start),
+        )?;
+        enqueued[start as usize] = true;
+        num_visited += 1;
+    }
+
+    while let Some(PruneHeapItem { state, .. }) = heap.pop() {
+        if visited[state as usize] {
+            continue;
+        }
+        visited[state as usize] = true;
+        let s = state as usize;
+
+        if let Some(final_weight) = fst.final_weight(state)? {
+            if natural_less(&limit, &idistance[s].times(&final_weight)?)? {
+                fst.delete_final_weight(state)?;
+            }
+        }
+
+        let trs = fst.pop_trs(state)?;
+        let mut kept_trs = Vec::with_capacity(trs.len());
+        for tr in trs {
+            let nextstate = tr.nextstate as usize;
+            let idistance_next = idistance[s].times(&tr.weight)?;
+            let weight = idistance_next.times(distance_or_zero(&fdistance, tr.nextstate))?;
+            if natural_less(&limit, &weight)? {
+                continue;
+            }
+            if natural_less(&idistance_next, &idistance[nextstate])? {
+                idistance[nextstate] = idistance_next;
+            }
+            kept_trs.push(tr);
+            if visited[nextstate] {
+                continue;
+            }
+            if let Some(state_threshold) = config.state_threshold {
+                if !enqueued[nextstate] && num_visited >= state_threshold as usize {
+                    continue;
+                }
+            }
+            if !enqueued[nextstate] {
+                enqueued[nextstate] = true;
+                num_visited += 1;
+            }
+            push_state(
+                &mut heap,
+                nextstate as StateId,
+                &idistance[nextstate],
+                distance_or_zero(&fdistance, nextstate as StateId),
+            )?;
+        }
+        unsafe { fst.set_trs_unchecked(state, kept_trs) };
+    }
+
+    let dead_states: Vec<_> = (0..num_states)
+        .filter(|s| !visited[*s])
+        .map(|s| s as StateId)
+        .collect();
+    fst.del_states(dead_states)?;
+    Ok(())
+}
+
+/// Prunes an FST. The input is left untouched and the pruned FST is returned.
+/// Only the states visited while pruning are expanded in the output.
+///
+/// See [`prune`](fn.prune.html) for the semantic of the thresholds.
+pub fn prune_to<W, FI, FO>(ifst: &FI, config: PruneConfig<W>) -> Result<FO>
+where
+    W: Semiring,
+    FI: ExpandedFst<W>,
+    FO: MutableFst<W>,
+{
+    check_path_property::<W>()?;
+    let fdistance = if ifst.start().is_some() {
+        shortest_distance_with_config(ifst, true, ShortestDistanceConfig::new(config.delta))?
+    } else {
+        vec![]
+    };
+    let mut ofst: FO =
+        prune_to_with_distance(ifst, config, |s| Ok(distance_or_zero(&fdistance, s)))?;
+    ofst.set_symts_from_fst(ifst);
+    Ok(ofst)
+}
+
+/// Same as [`prune_to`] but the distance from each state to the final states is
+/// provided by `fdistance`. It is only queried for states reached while pruning,
+/// which allows to prune a lazy FST without expanding it entirely. The symbol
+/// tables are not copied.
+pub(crate) fn prune_to_with_distance<W, FI, FO, D>(
+    ifst: &FI,
+    config: PruneConfig<W>,
+    fdistance: D,
+) -> Result<FO>
+where
+    W: Semiring,
+    FI: CoreFst<W>,
+    FO: MutableFst<W>,
+    D: Fn(StateId) -> Result<W>,
+{
+    let (ofst, _) = prune_to_with_projection(ifst, config, fdistance, |w: &W| w.clone())?;
+    Ok(ofst)
+}
+
+/// Same as [`prune_to_with_distance`] but the thresholds are applied on the
+/// projection of the weights of `ifst` in the semiring `P`, which must have the
+/// path property while `W` doesn't need to. Also returns the state of `ifst`
+/// each state of the output has been copied from.
+pub(crate) fn prune_to_with_projection<W, P, FI, FO, D, M>(
+    ifst: &FI,
+    config: PruneConfig<P>,
+    fdistance: D,
+    project: M,
+) -> Result<(FO, Vec<StateId>)>
+where
+    W: Semiring,
+    P: Semiring,
+    FI: CoreFst<W>,
+    FO: MutableFst<W>,
+    D: Fn(StateId) -> Result<P>,
+    M: Fn(&W) -> P,
+{
+    check_path_property::<P>()?;
+    let mut ofst = FO::new();
+    let mut origin = vec![];
+    let start = match ifst.start() {
+        Some(s) => s,
+        None => return Ok((ofst, origin)),
+    };
+    if natural_less(&config.weight_threshold, &P::one())? || config.state_threshold == Some(0) {
+        return Ok((ofst, origin));
+    }
+    let start_distance = fdistance(start)?;
+    if start_distance.is_zero() {
+        return Ok((ofst, origin));
+    }
+
+    let limit = if config.weight_threshold.is_zero() {
+        P::zero()
+    } else {
+        start_distance.times(&config.weight_threshold)?
+    };
+
+    let mut idistance: HashMap<StateId, P> = HashMap::new();
+    let mut visited: HashSet<StateId> = HashSet::new();
+    let mut copy: HashMap<StateId, StateId> = HashMap::new();
+    let mut heap = BinaryHeap::new();
+
+    let ostart = ofst.add_state();
+    ofst.set_start(ostart)?;
+    copy.insert(start, ostart);
+    origin.push(start);
+    idistance.insert(start, P::one());
+    push_state(&mut heap, start, &P::one(), start_distance)?;
+
+    while let Some(PruneHeapItem { state, .. }) = heap.pop() {
+        if !visited.insert(state) {
+            continue;
+        }
+        let ostate = copy[&state];
+        let idistance_state = idistance[&state].clone();
+
+        if let Some(final_weight) = ifst.final_weight(state)? {
+            if !natural_less(&limit, &idistance_state.times(project(&final_weight))?)? {
+                ofst.set_final(ostate, final_weight)?;
+            }
+        }
+
+        for tr in ifst.get_trs(state)?.trs() {
+            let idistance_next = idistance_state.times(project(&tr.weight))?;
+            let fdistance_next = fdistance(tr.nextstate)?;
+            let weight = idistance_next.times(&fdistance_next)?;
+            if natural_less(&limit, &weight)? {
+                continue;
+            }
+            if let Some(state_threshold) = config.state_threshold {
+                if !copy.contains_key(&tr.nextstate)
+                    && ofst.num_states() >= state_threshold as usize
+                {
+                    continue;
+                }
+            }
+            let idistance_entry = idistance.entry(tr.nextstate).or_insert_with(P::zero);
+            if natural_less(&idistance_next, idistance_entry)? {
+                *idistance_entry = idistance_next;
+            }
+            let onextstate = match copy.get(&tr.nextstate) {
+                Some(q) => *q,
+                None => {
+                    let q = ofst.add_state();
+                    copy.insert(tr.nextstate, q);
+                    origin.push(tr.nextstate);
+                    q
+                }
+            };
+            let mut otr = tr.clone();
+            otr.nextstate = onextstate;
+            ofst.add_tr(ostate, otr)?;
+            if !visited.contains(&tr.nextstate) {
+                push_state(
+                    &mut heap,
+                    tr.nextstate,
+                    &idistance[&tr.nextstate],
+                    fdistance_next,
+                )?;
+            }
+        }
+    }
+
+    Ok((ofst, origin))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::algorithms::union::union;
+    use crate::fst_impls::VectorFst;
+    use crate::fst_traits::Fst;
+    use crate::semirings::{LogWeight, TropicalWeight};
+    use crate::utils::acceptor;
+    use crate::Tr;
+
+    fn build_fst() -> Result<VectorFst<TropicalWeight>> {
+        let mut fst: VectorFst<TropicalWeight> = acceptor(&[1, 2], TropicalWeight::new(1.0));
+        for (labels, weight) in &[(vec![3], 2.5), (vec![4, 5, 6], 4.0), (vec![7], 10.0)] {
+            let other: VectorFst<TropicalWeight> = acceptor(labels, TropicalWeight::new(*weight));
+            union(&mut fst, &other)?;
+        }
+        Ok(fst)
+    }
+
+    fn sorted_ilabels(fst: &VectorFst<TropicalWeight>) -> Vec<Vec<crate::Label>> {
+        let mut paths: Vec<_> = fst.paths_iter().map(|p| p.ilabels).collect();
+        paths.sort();
+        paths
+    }
+
+    #[test]
+    fn test_prune_weight_threshold() -> Result<()> {
+        let mut fst = build_fst()?;
+        let config = PruneConfig::default().with_weight_threshold(TropicalWeight::new(3.0));
+
+        let fst_pruned: VectorFst<_> = prune_to(&fst, config.clone())?;
+        prune(&mut fst, config)?;
+
+        assert_eq!(
+            sorted_ilabels(&fst),
+            vec![vec![1, 2], vec![3], vec![4, 5, 6]]
+        );
+        assert_eq!(sorted_ilabels(&fst_pruned), sorted_ilabels(&fst));
+        assert_eq!(fst.num_states(), fst_pruned.num_states());
+        Ok(())
+    }
+
+    #[test]
+    fn test_prune_no_threshold() -> Result<()> {
+        let mut fst = build_fst()?;
+        let fst_ref = fst.clone();
+
+        let fst_pruned: VectorFst<_> = prune_to(&fst, PruneConfig::default())?;
+        prune(&mut fst, PruneConfig::default())?;
+
+        assert_eq!(fst, fst_ref);
+        assert_eq!(sorted_ilabels(&fst_pruned), sorted_ilabels(&fst_ref));
+        Ok(())
+    }
+
+    #[test]
+    fn test_prune_state_threshold() -> Result<()> {
+        let mut fst: VectorFst<TropicalWeight> = VectorFst::new();
+        let s0 = fst.add_state();
+        let s1 = fst.add_state();
+        let s2 = fst.add_state();
+        fst.set_start(s0)?;
+        fst.add_tr(s0, Tr::new(1, 1, TropicalWeight::new(1.0), s1))?;
+        fst.add_tr(s0, Tr::new(2, 2, TropicalWeight::new(5.0), s2))?;
+        fst.set_final(s1, TropicalWeight::one())?;
+        fst.set_final(s2, TropicalWeight::one())?;
+        let config = PruneConfig::default().with_state_threshold(Some(2));
+
+        let fst_pruned: VectorFst<_> = prune_to(&fst, config.clone())?;
+        prune(&mut fst, config)?;
+
+        assert_eq!(fst.num_states(), 2);
+        assert_eq!(fst_pruned.num_states(), 2);
+        assert_eq!(sorted_ilabels(&fst), vec![vec![1]]);
+        assert_eq!(sorted_ilabels(&fst_pruned), vec![vec![1]]);
+
+        prune(
+            &mut fst,
+            PruneConfig::default().with_state_threshold(Some(0)),
+        )?;
+        assert_eq!(fst.num_states(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_prune_state_threshold_copied_state() -> Result<()> {
+        // Once the threshold is reached, transitions to states that are already
+        // kept must still be kept.
+        let mut fst: VectorFst<TropicalWeight> = VectorFst::new();
+        let s0 = fst.add_state();
+        let s1 = fst.add_state();
+        let s2 = fst.add_state();
+        fst.set_start(s0)?;
+        fst.add_tr(s0, Tr::new(1, 1, TropicalWeight::new(1.0), s1))?;
+        fst.add_tr(s0, Tr::new(2, 2, TropicalWeight::new(2.0), s1))?;
+        fst.add_tr(s0, Tr::new(3, 3, TropicalWeight::new(3.0), s2))?;
+        fst.set_final(s1, TropicalWeight::one())?;
+        fst.set_final(s2, TropicalWeight::one())?;
+        let config = PruneConfig::default().with_state_threshold(Some(2));
+
+        let fst_pruned: VectorFst<_> = prune_to(&fst, config.clone())?;
+        prune(&mut fst, config)?;
+
+        assert_eq!(fst_pruned.num_states(), 2);
+        assert_eq!(sorted_ilabels(&fst), vec![vec![1], vec![2]]);
+        assert_eq!(sorted_ilabels(&fst_pruned), vec![vec![1], vec![2]]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_prune_non_path_semiring() {
+        let mut fst: VectorFst<LogWeight> = acceptor(&[1, 2], LogWeight::new(1.0));
+        assert!(prune(&mut fst, PruneConfig::default()).is_err());
+        assert!(prune_to::<_, _, VectorFst<_>>(&fst, PruneConfig::default()).is_err());
+    }
+}
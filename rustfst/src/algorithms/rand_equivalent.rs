@@ -0,0 +1,286 @@
+use anyhow::Result;
+use rand::Rng;
+use rand_chacha::rand_core::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use crate::algorithms::compose::compose;
+use crate::algorithms::randgen::{TrSelector, UniformTrSelector};
+use crate::algorithms::tr_compares::{ILabelCompare, OLabelCompare};
+use crate::algorithms::{
+    connect, fst_convert_from_ref, project, shortest_distance, tr_sort, ProjectType,
+};
+use crate::fst_impls::VectorFst;
+use crate::fst_properties::{compute_fst_properties, FstProperties};
+use crate::fst_traits::{CoreFst, ExpandedFst, MutableFst};
+use crate::semirings::{Semiring, SemiringProperties};
+use crate::{Tr, Trs, KDELTA};
+
+/// Configuration for [`rand_equivalent_with_config`].
+#[derive(Debug, Clone, Copy, PartialOrd, PartialEq)]
+pub struct RandEquivalentConfig {
+    /// Number of random paths to check.
+    pub npath: usize,
+    /// Seed of the random generator used to select the paths.
+    pub seed: u64,
+    /// Comparison delta of the weights.
+    pub delta: f32,
+    /// Maximum length of the random paths.
+    pub max_length: usize,
+}
+
+impl RandEquivalentConfig {
+    pub fn new(npath: usize, seed: u64) -> Self {
+        Self {
+            npath,
+            seed,
+            delta: KDELTA,
+            max_length: usize::MAX,
+        }
+    }
+
+    pub fn with_delta(self, delta: f32) -> Self {
+        Self { delta, ..self }
+    }
+
+    pub fn with_max_length(self, max_length: usize) -> Self {
+        Self { max_length, ..self }
+    }
+}
+
+/// Sum of the weights of all the successful paths of the FST. Returns `None` if
+/// it can't be computed because the FST has cycles in a non-idempotent semiring.
+fn total_weight<W: Semiring>(fst: &VectorFst<W>) -> Result<Option<W>> {
+    if !W::properties().contains(SemiringProperties::IDEMPOTENT) {
+        let mut known = FstProperties::empty();
+        let props = compute_fst_properties(fst, FstProperties::CYCLIC, &mut known, true)?;
+        if props.contains(FstProperties::CYCLIC) {
+            return Ok(None);
+        }
+    }
+    let dists = shortest_distance(fst, false)?;
+    let mut sum = W::zero();
+    for (state, dist) in dists.iter().enumerate() {
+        if let Some(final_weight) = fst.final_weight(state as _)? {
+            sum.plus_assign(dist.times(final_weight)?)?;
+        }
+    }
+    Ok(Some(sum))
+}
+
+/// Sum of the weights of all the paths of `fst` matching the labels of `path`.
+fn path_weight<W: Semiring>(
+    ipath: &VectorFst<W>,
+    fst: &VectorFst<W>,
+    opath: &VectorFst<W>,
+) -> Result<Option<W>> {
+    let mut cfst: VectorFst<W> = compose::<_, VectorFst<_>, VectorFst<_>, _, _, _>(ipath, fst)?;
+    tr_sort(&mut cfst, OLabelCompare {});
+    let pfst: VectorFst<W> = compose::<_, VectorFst<_>, VectorFst<_>, _, _, _>(&cfst, opath)?;
+    total_weight(&pfst)
+}
+
+/// Generates a random successful path of `fst` as a linear FST with unit weights, the
+/// transitions being chosen with `selector`. Returns `None` if the path would be longer
+/// than `max_length` or if a state without any transition nor final weight is reached.
+fn random_path<W: Semiring, S: TrSelector>(
+    fst: &VectorFst<W>,
+    selector: &mut S,
+    max_length: usize,
+) -> Result<Option<VectorFst<W>>> {
+    let mut state = match fst.start() {
+        Some(s) => s,
+        None => return Ok(None),
+    };
+    let mut path = VectorFst::new();
+    let mut path_state = path.add_state();
+    path.set_start(path_state)?;
+    let mut length = 0;
+    loop {
+        let trs = fst.get_trs(state)?;
+        let num_trs = trs.trs().len();
+        if num_trs == 0 && !fst.is_final(state)? {
+            return Ok(None);
+        }
+        let selected = selector.select_tr(fst, state)?;
+        if selected == num_trs {
+            path.set_final(path_state, W::one())?;
+            return Ok(Some(path));
+        }
+        if length == max_length {
+            return Ok(None);
+        }
+        let tr = &trs.trs()[selected];
+        let next_path_state = path.add_state();
+        path.add_tr(
+            path_state,
+            Tr::new(tr.ilabel, tr.olabel, W::one(), next_path_state),
+        )?;
+        path_state = next_path_state;
+        state = tr.nextstate;
+        length += 1;
+    }
+}
+
+/// Tests if two FSTs are equivalent by checking if random strings from one FST
+/// are transduced the same by both FSTs. The paths are randomly generated with
+/// the uniform distribution over the transitions, alternatively in both FSTs,
+/// and the weights they get in both FSTs are compared with `shortest_distance`.
+///
+/// Paths going through a cycle in a non-idempotent semiring are not checked.
+pub fn rand_equivalent_with_config<W, F1, F2>(
+    fst1: &F1,
+    fst2: &F2,
+    config: RandEquivalentConfig,
+) -> Result<bool>
+where
+    W: Semiring,
+    F1: ExpandedFst<W>,
+    F2: ExpandedFst<W>,
+{
+    let mut sfst1: VectorFst<W> = fst_convert_from_ref(fst1);
+    let mut sfst2: VectorFst<W> = fst_convert_from_ref(fst2);
+    connect(&mut sfst1)?;
+    connect(&mut sfst2)?;
+    tr_sort(&mut sfst1, ILabelCompare {});
+    tr_sort(&mut sfst2, ILabelCompare {});
+
+    // An empty FST can't be sampled.
+    match (sfst1.start(), sfst2.start()) {
+        (None, None) => return Ok(true),
+        (Some(_), Some(_)) => {}
+        _ => return Ok(false),
+    };
+
+    let mut rng = ChaCha8Rng::seed_from_u64(config.seed);
+    for _ in 0..config.npath {
+        let fst = if rng.gen::<bool>() { &sfst1 } else { &sfst2 };
+        let mut selector = UniformTrSelector::from_seed(rng.gen());
+        let path = match random_path(fst, &mut selector, config.max_length)? {
+            Some(path) => path,
+            None => continue,
+        };
+
+        let mut ipath = path.clone();
+        let mut opath = path;
+        project(&mut ipath, ProjectType::ProjectInput);
+        project(&mut opath, ProjectType::ProjectOutput);
+
+        let sum1 = match path_weight(&ipath, &sfst1, &opath)? {
+            Some(w) => w,
+            None => continue,
+        };
+        let sum2 = match path_weight(&ipath, &sfst2, &opath)? {
+            Some(w) => w,
+            None => continue,
+        };
+        if !sum1.approx_equal(&sum2, config.delta) {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// Tests if two FSTs are equivalent by checking if `npath` random strings are
+/// transduced the same by both FSTs. `seed` is used to generate the paths.
+///
+/// # Example
+/// ```
+/// # #[macro_use] extern crate rustfst;
+/// # use anyhow::Result;
+/// # use rustfst::utils::acceptor;
+/// # use rustfst::semirings::{Semiring, TropicalWeight};
+/// # use rustfst::fst_impls::VectorFst;
+/// # use rustfst::algorithms::{determinize::determinize, rand_equivalent};
+/// # use rustfst::algorithms::union::union;
+/// # fn main() -> Result<()> {
+/// let mut fst : VectorFst<TropicalWeight> = fst![1, 2; 1.0];
+/// let other : VectorFst<TropicalWeight> = fst![1, 3; 2.0];
+/// union(&mut fst, &other)?;
+///
+/// let det_fst : VectorFst<TropicalWeight> = determinize(&fst)?;
+/// assert!(rand_equivalent(&fst, &det_fst, 10, 42)?);
+///
+/// let other : VectorFst<TropicalWeight> = fst![1, 2; 3.0];
+/// assert!(!rand_equivalent(&fst, &other, 10, 42)?);
+/// # Ok(())
+/// # }
+/// ```
+pub fn rand_equivalent<W, F1, F2>(fst1: &F1, fst2: &F2, npath: usize, seed: u64) -> Result<bool>
+where
+    W: Semiring,
+    F1: ExpandedFst<W>,
+    F2: ExpandedFst<W>,
+{
+    rand_equivalent_with_config(fst1, fst2, RandEquivalentConfig::new(npath, seed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::algorithms::determinize::determinize;
+    use crate::algorithms::union::union;
+    use crate::algorithms::{minimize, push_weights, ReweightType};
+    use crate::semirings::{LogWeight, ProductWeight, TropicalWeight};
+    use crate::utils::transducer;
+
+    fn build_fst<W: Semiring<Type = f32>>() -> Result<VectorFst<W>> {
+        let mut fst: VectorFst<W> = transducer(&[1, 2], &[3, 4], W::new(1.0));
+        union(
+            &mut fst,
+            &transducer::<_, VectorFst<_>>(&[1, 3], &[3, 5], W::new(2.0)),
+        )?;
+        union(
+            &mut fst,
+            &transducer::<_, VectorFst<_>>(&[2], &[6], W::new(0.5)),
+        )?;
+        Ok(fst)
+    }
+
+    #[test]
+    fn test_rand_equivalent_tropical() -> Result<()> {
+        let fst = build_fst::<TropicalWeight>()?;
+        let mut fst_opt: VectorFst<TropicalWeight> = determinize(&fst)?;
+        minimize(&mut fst_opt)?;
+        assert!(rand_equivalent(&fst, &fst_opt, 20, 2022)?);
+
+        let mut other = fst.clone();
+        let start = other.start().unwrap();
+        other.add_tr(start, Tr::new(4, 4, TropicalWeight::one(), start))?;
+        assert!(!rand_equivalent(&fst, &other, 20, 2022)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_rand_equivalent_log() -> Result<()> {
+        let fst = build_fst::<LogWeight>()?;
+        let mut fst_pushed = fst.clone();
+        push_weights(&mut fst_pushed, ReweightType::ReweightToInitial)?;
+        assert!(rand_equivalent(&fst, &fst_pushed, 20, 2022)?);
+
+        let other = build_fst::<LogWeight>()?;
+        let mut other_doubled = other.clone();
+        union(&mut other_doubled, &other)?;
+        assert!(!rand_equivalent(&fst, &other_doubled, 20, 2022)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_rand_equivalent_product() -> Result<()> {
+        type W = ProductWeight<TropicalWeight, LogWeight>;
+        let w = |v: f32| W::new((TropicalWeight::new(v), LogWeight::new(v)));
+        let mut fst: VectorFst<W> = transducer(&[1, 2], &[3, 4], w(1.0));
+        union(&mut fst, &transducer::<_, VectorFst<_>>(&[2], &[6], w(0.5)))?;
+
+        // A non-accessible state doesn't change the language.
+        let mut fst_dead = fst.clone();
+        let s = fst_dead.add_state();
+        fst_dead.set_final(s, W::one())?;
+        assert!(rand_equivalent(&fst, &fst_dead, 20, 2022)?);
+
+        let mut fst_doubled = fst.clone();
+        union(&mut fst_doubled, &fst)?;
+        assert!(!rand_equivalent(&fst, &fst_doubled, 20, 2022)?);
+        Ok(())
+    }
+}
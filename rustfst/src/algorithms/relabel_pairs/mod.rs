@@ -0,0 +1,6 @@
+mod relabel_fst;
+mod relabel_mapper;
+mod relabel_pairs_static;
+
+pub use relabel_fst::RelabelFst;
+pub use relabel_pairs_static::relabel_pairs;
@@ -7,7 +7,7 @@ use crate::fst_traits::MutableFst;
 use crate::semirings::Semiring;
 use crate::StateId;
 
-fn iterator_to_hashmap<I>(pairs: I) -> Result<HashMap<StateId, StateId>>
+pub(crate) fn iterator_to_hashmap<I>(pairs: I) -> Result<HashMap<StateId, StateId>>
 where
     I: IntoIterator<Item = (StateId, StateId)>,
 {
@@ -80,7 +80,8 @@ where
 
 #[cfg(test)]
 mod tests {
-    use crate::fst_impls::VectorFst;
+    use crate::algorithms::RelabelFst;
+    use crate::fst_impls::{ConstFst, VectorFst};
     use crate::semirings::{IntegerWeight, Semiring};
     use crate::tr::Tr;
 
@@ -120,6 +121,12 @@ mod tests {
         let ipairs = vec![(3, 45), (5, 75)];
         let opairs = vec![(18, 51), (5, 75), (7, 85)];
 
+        let const_fst: ConstFst<_> = fst.clone().into();
+        let lazy_fst: VectorFst<_> =
+            RelabelFst::<_, ConstFst<_>, _>::new(&const_fst, ipairs.clone(), opairs.clone())?
+                .compute()?;
+        assert_eq!(lazy_fst, expected_fst);
+
         relabel_pairs(&mut fst, ipairs, opairs)?;
         assert_eq!(fst, expected_fst);
 
@@ -0,0 +1,320 @@
+use anyhow::Result;
+
+use crate::algorithms::compose::compose;
+use crate::algorithms::determinize::determinize;
+use crate::algorithms::rm_epsilon::rm_epsilon;
+use crate::algorithms::shortest_path::natural_less;
+use crate::algorithms::{connect, project, shortest_path, ProjectType};
+use crate::fst_impls::VectorFst;
+use crate::fst_properties::{compute_fst_properties, FstProperties};
+use crate::fst_traits::{CoreFst, ExpandedFst, Fst};
+use crate::semirings::{Semiring, WeaklyDivisibleSemiring, WeightQuantize};
+use crate::utils::{acceptor, decode_linear_fst};
+use crate::{Label, SymbolTable, KDELTA};
+
+/// String given to or returned by the rewrite functions. It is either a sequence
+/// of labels (`Vec<Label>`) or a `String` of symbols separated by whitespaces. The
+/// symbols of the input strings are looked up in the input `SymbolTable` of the rule
+/// and those of the output strings in its output `SymbolTable`.
+pub trait RewriteString: Sized {
+    /// Converts the string to a sequence of labels.
+    fn to_labels(&self, symt: Option<&SymbolTable>) -> Result<Vec<Label>>;
+    /// Builds a string from a sequence of labels.
+    fn from_labels(labels: &[Label], symt: Option<&SymbolTable>) -> Result<Self>;
+}
+
+impl RewriteString for Vec<Label> {
+    fn to_labels(&self, _symt: Option<&SymbolTable>) -> Result<Vec<Label>> {
+        Ok(self.clone())
+    }
+
+    fn from_labels(labels: &[Label], _symt: Option<&SymbolTable>) -> Result<Self> {
+        Ok(labels.to_vec())
+    }
+}
+
+impl RewriteString for String {
+    fn to_labels(&self, symt: Option<&SymbolTable>) -> Result<Vec<Label>> {
+        let symt = symt.ok_or_else(|| format_err!("Rewrite : missing symbol table"))?;
+        self.split_whitespace()
+            .map(|sym| {
+                symt.get_label(sym)
+                    .ok_or_else(|| format_err!("Rewrite : unknown symbol {:?}", sym))
+            })
+            .collect()
+    }
+
+    fn from_labels(labels: &[Label], symt: Option<&SymbolTable>) -> Result<Self> {
+        let symt = symt.ok_or_else(|| format_err!("Rewrite : missing symbol table"))?;
+        let symbols = labels
+            .iter()
+            .map(|label| {
+                symt.get_symbol(*label)
+                    .ok_or_else(|| format_err!("Rewrite : unknown label {}", label))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(symbols.join(" "))
+    }
+}
+
+/// Converts the input of a rewrite to labels with the input symbol table of the rule.
+fn input_labels<W: Semiring, F: Fst<W>, S: RewriteString>(
+    input: &S,
+    rule: &F,
+) -> Result<Vec<Label>> {
+    input.to_labels(rule.input_symbols().map(|s| s.as_ref()))
+}
+
+/// Converts labels to an output of a rewrite with the output symbol table of the rule.
+fn output_string<W: Semiring, F: Fst<W>, S: RewriteString>(
+    labels: &[Label],
+    rule: &F,
+) -> Result<S> {
+    S::from_labels(labels, rule.output_symbols().map(|s| s.as_ref()))
+}
+
+/// Builds the acceptor of all the outputs of the rule for the input, without epsilons.
+fn rewrite_lattice<W, F>(input: &[Label], rule: &F) -> Result<Option<VectorFst<W>>>
+where
+    W: Semiring,
+    F: ExpandedFst<W>,
+{
+    let ifst: VectorFst<W> = acceptor(input, W::one());
+    let mut lattice: VectorFst<W> = compose::<_, VectorFst<_>, F, _, _, _>(&ifst, rule)?;
+    project(&mut lattice, ProjectType::ProjectOutput);
+    rm_epsilon(&mut lattice)?;
+    if lattice.start().is_none() {
+        return Ok(None);
+    }
+    Ok(Some(lattice))
+}
+
+fn rewrite_lattice_or_fail<W, F>(input: &[Label], rule: &F) -> Result<VectorFst<W>>
+where
+    W: Semiring,
+    F: ExpandedFst<W>,
+{
+    rewrite_lattice(input, rule)?
+        .ok_or_else(|| format_err!("Rewrite : the rule doesn't accept the input"))
+}
+
+/// Lists the distinct output strings of a lattice along with their weights.
+fn lattice_to_strings<W>(lattice: &VectorFst<W>) -> Result<Vec<(Vec<Label>, W)>>
+where
+    W: WeaklyDivisibleSemiring + WeightQuantize,
+{
+    let mut known = FstProperties::empty();
+    let props = compute_fst_properties(lattice, FstProperties::CYCLIC, &mut known, true)?;
+    if props.contains(FstProperties::CYCLIC) {
+        bail!("Rewrite : the rule produces an infinite number of rewrites")
+    }
+    let dfa: VectorFst<W> = determinize(lattice)?;
+    Ok(dfa.paths_iter().map(|p| (p.olabels, p.weight)).collect())
+}
+
+/// Returns the output of the shortest path of the rule for the input. Ties are
+/// resolved arbitrarily, see [`one_top_rewrite`] to detect them.
+///
+/// Fails if the rule doesn't accept the input.
+///
+/// # Example
+/// ```
+/// # use anyhow::Result;
+/// # use rustfst::semirings::{Semiring, TropicalWeight};
+/// # use rustfst::fst_impls::VectorFst;
+/// # use rustfst::algorithms::rewrite::top_rewrite;
+/// # use rustfst::algorithms::union::union;
+/// # use rustfst::utils::transducer;
+/// # fn main() -> Result<()> {
+/// let mut rule : VectorFst<TropicalWeight> = transducer(&[1, 2], &[3], TropicalWeight::new(2.0));
+/// let other : VectorFst<TropicalWeight> = transducer(&[1, 2], &[4, 5], TropicalWeight::new(1.0));
+/// union(&mut rule, &other)?;
+///
+/// assert_eq!(top_rewrite(&vec![1, 2], &rule)?, vec![4, 5]);
+/// # Ok(())
+/// # }
+/// ```
+pub fn top_rewrite<W, F, S>(input: &S, rule: &F) -> Result<S>
+where
+    W: Semiring
+        + WeightQuantize
+        + Into<<W as Semiring>::ReverseWeight>
+        + From<<W as Semiring>::ReverseWeight>,
+    <W as Semiring>::ReverseWeight: WeightQuantize + WeaklyDivisibleSemiring,
+    F: ExpandedFst<W>,
+    S: RewriteString,
+{
+    let lattice = rewrite_lattice_or_fail(&input_labels(input, rule)?, rule)?;
+    let best: VectorFst<W> = shortest_path(&lattice)?;
+    let path = decode_linear_fst(&best)?;
+    output_string(&path.olabels, rule)
+}
+
+/// Returns all the distinct outputs of the rule for the input, in no particular order.
+///
+/// Fails if the rule doesn't accept the input or if there is an infinite number of outputs.
+pub fn rewrites<W, F, S>(input: &S, rule: &F) -> Result<Vec<S>>
+where
+    W: WeaklyDivisibleSemiring + WeightQuantize,
+    F: ExpandedFst<W>,
+    S: RewriteString,
+{
+    let lattice = rewrite_lattice_or_fail(&input_labels(input, rule)?, rule)?;
+    lattice_to_strings(&lattice)?
+        .into_iter()
+        .map(|(labels, _)| output_string(&labels, rule))
+        .collect()
+}
+
+/// Same as [`rewrites`] but the rule is optional : if it doesn't accept the input,
+/// the input is returned unchanged instead of failing.
+pub fn optional_rewrites<W, F, S>(input: &S, rule: &F) -> Result<Vec<S>>
+where
+    W: WeaklyDivisibleSemiring + WeightQuantize,
+    F: ExpandedFst<W>,
+    S: RewriteString + Clone,
+{
+    match rewrite_lattice(&input_labels(input, rule)?, rule)? {
+        Some(lattice) => lattice_to_strings(&lattice)?
+            .into_iter()
+            .map(|(labels, _)| output_string(&labels, rule))
+            .collect(),
+        None => Ok(vec![input.clone()]),
+    }
+}
+
+/// Returns all the outputs of the rule for the input whose weight is the best one.
+///
+/// Fails if the rule doesn't accept the input or if there is an infinite number of outputs.
+/// The weight must have the path property.
+pub fn optimal_rewrites<W, F, S>(input: &S, rule: &F) -> Result<Vec<S>>
+where
+    W: WeaklyDivisibleSemiring + WeightQuantize,
+    F: ExpandedFst<W>,
+    S: RewriteString,
+{
+    let lattice = rewrite_lattice_or_fail(&input_labels(input, rule)?, rule)?;
+    let strings = lattice_to_strings(&lattice)?;
+    let mut best = W::zero();
+    for (_, weight) in strings.iter() {
+        if natural_less(weight, &best)? {
+            best = weight.clone();
+        }
+    }
+    strings
+        .into_iter()
+        .filter(|(_, weight)| weight.approx_equal(&best, KDELTA))
+        .map(|(labels, _)| output_string(&labels, rule))
+        .collect()
+}
+
+/// Returns the output of the shortest path of the rule for the input.
+///
+/// Fails if the rule doesn't accept the input or if several outputs share the best weight.
+pub fn one_top_rewrite<W, F, S>(input: &S, rule: &F) -> Result<S>
+where
+    W: WeaklyDivisibleSemiring + WeightQuantize,
+    F: ExpandedFst<W>,
+    S: RewriteString,
+{
+    let mut top_rewrites = optimal_rewrites(input, rule)?;
+    if top_rewrites.len() > 1 {
+        bail!(
+            "Rewrite : multiple top rewrites found ({})",
+            top_rewrites.len()
+        )
+    }
+    top_rewrites
+        .pop()
+        .ok_or_else(|| format_err!("Rewrite : no rewrite found"))
+}
+
+/// Returns whether the rule rewrites the input into the output.
+pub fn matches<W, F, S>(input: &S, output: &S, rule: &F) -> Result<bool>
+where
+    W: Semiring,
+    F: ExpandedFst<W>,
+    S: RewriteString,
+{
+    let ifst: VectorFst<W> = acceptor(&input_labels(input, rule)?, W::one());
+    let ofst: VectorFst<W> = acceptor(
+        &output.to_labels(rule.output_symbols().map(|s| s.as_ref()))?,
+        W::one(),
+    );
+    let lattice: VectorFst<W> = compose::<_, VectorFst<_>, F, _, _, _>(&ifst, rule)?;
+    let mut res: VectorFst<W> = compose::<_, VectorFst<_>, VectorFst<_>, _, _, _>(&lattice, &ofst)?;
+    connect(&mut res)?;
+    Ok(res.start().is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::Arc;
+
+    use crate::algorithms::union::union;
+    use crate::semirings::TropicalWeight;
+    use crate::symt;
+    use crate::utils::transducer;
+
+    // Rewrites "a b" into "c" (weight 2), "d e" (weight 1) or "e" (weight 1).
+    fn build_rule() -> Result<VectorFst<TropicalWeight>> {
+        let mut rule: VectorFst<TropicalWeight> =
+            transducer(&[1, 2], &[3], TropicalWeight::new(2.0));
+        for (olabels, weight) in &[(vec![4, 5], 1.0), (vec![5], 1.0)] {
+            let other: VectorFst<TropicalWeight> =
+                transducer(&[1, 2], olabels, TropicalWeight::new(*weight));
+            union(&mut rule, &other)?;
+        }
+        let symt = Arc::new(symt!["a", "b", "c", "d", "e"]);
+        rule.set_input_symbols(Arc::clone(&symt));
+        rule.set_output_symbols(symt);
+        Ok(rule)
+    }
+
+    #[test]
+    fn test_rewrites() -> Result<()> {
+        let rule = build_rule()?;
+        let mut res = rewrites(&vec![1, 2], &rule)?;
+        res.sort();
+        assert_eq!(res, vec![vec![3], vec![4, 5], vec![5]]);
+
+        let mut res = rewrites(&"a b".to_string(), &rule)?;
+        res.sort();
+        assert_eq!(res, vec!["c", "d e", "e"]);
+
+        assert!(rewrites(&vec![1], &rule).is_err());
+        assert_eq!(optional_rewrites(&vec![1], &rule)?, vec![vec![1]]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_top_rewrites() -> Result<()> {
+        let mut rule = build_rule()?;
+        let top = top_rewrite(&"a b".to_string(), &rule)?;
+        assert!(top == "d e" || top == "e");
+
+        let mut res = optimal_rewrites(&vec![1, 2], &rule)?;
+        res.sort();
+        assert_eq!(res, vec![vec![4, 5], vec![5]]);
+        assert!(one_top_rewrite(&vec![1, 2], &rule).is_err());
+
+        let other: VectorFst<TropicalWeight> = transducer(&[1, 2], &[4], TropicalWeight::new(0.5));
+        union(&mut rule, &other)?;
+        assert_eq!(one_top_rewrite(&"a b".to_string(), &rule)?, "d");
+        assert_eq!(top_rewrite(&vec![1, 2], &rule)?, vec![4]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_matches() -> Result<()> {
+        let rule = build_rule()?;
+        assert!(matches(&"a b".to_string(), &"d e".to_string(), &rule)?);
+        assert!(matches(&vec![1, 2], &vec![3], &rule)?);
+        assert!(!matches(&vec![1, 2], &vec![4], &rule)?);
+        assert!(!matches(&vec![2], &vec![3], &rule)?);
+        assert!(matches(&"a f".to_string(), &"c".to_string(), &rule).is_err());
+        Ok(())
+    }
+}
@@ -1,6 +1,7 @@
 use anyhow::Result;
 
 use crate::algorithms::dfs_visit::dfs_visit;
+use crate::algorithms::prune::{prune, PruneConfig};
 use crate::algorithms::queues::AutoQueue;
 use crate::algorithms::rm_epsilon::{RmEpsilonInternalConfig, RmEpsilonState};
 use crate::algorithms::top_sort::TopOrderVisitor;
@@ -75,6 +76,7 @@ pub(crate) fn rm_epsilon_with_internal_config<W: Semiring, F: MutableFst<W>, Q:
     let connect = opts.connect;
     let weight_threshold = opts.weight_threshold.clone();
     let state_threshold = opts.state_threshold;
+    let delta = opts.sd_opts.delta;
 
     let start_state = match fst.start() {
         None => return Ok(()),
@@ -167,10 +169,11 @@ pub(crate) fn rm_epsilon_with_internal_config<W: Semiring, F: MutableFst<W>, Q:
     fst.set_properties(rmepsilon_properties(fst.properties(), false));
 
     if weight_threshold != W::zero() || state_threshold.is_some() {
-        todo!("Implement Prune!")
-    }
-
-    if connect && weight_threshold == W::zero() && state_threshold.is_none() {
+        prune(
+            fst,
+            PruneConfig::new(weight_threshold, state_threshold, delta),
+        )?;
+    } else if connect {
         crate::algorithms::connect(fst)?;
     }
     Ok(())
@@ -0,0 +1,10 @@
+pub use state_map_fst::StateMapFst;
+pub use state_map_static::state_map;
+pub use state_mapper::StateMapper;
+pub use state_mappers::{IdentityStateMapper, TrSumMapper, TrUniqueMapper};
+
+mod state_map_fst;
+mod state_map_op;
+mod state_map_static;
+mod state_mapper;
+mod state_mappers;
@@ -0,0 +1,68 @@
+use std::borrow::Borrow;
+use std::fmt::Debug;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use anyhow::Result;
+
+use crate::algorithms::lazy::FstOp;
+use crate::algorithms::state_map::StateMapper;
+use crate::fst_properties::FstProperties;
+use crate::fst_traits::Fst;
+use crate::semirings::Semiring;
+use crate::{StateId, Trs, TrsVec};
+
+pub struct StateMapFstOp<W: Semiring, F: Fst<W>, B: Borrow<F>, M: StateMapper<W>> {
+    fst: B,
+    mapper: M,
+    properties: FstProperties,
+    w: PhantomData<(W, F)>,
+}
+
+impl<W: Semiring, F: Fst<W>, B: Borrow<F>, M: StateMapper<W>> Debug for StateMapFstOp<W, F, B, M> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "StateMapFstOp {{ fst: {:?}, properties: {:?} }}",
+            self.fst.borrow(),
+            self.properties
+        )
+    }
+}
+
+impl<W: Semiring, F: Fst<W>, B: Borrow<F>, M: StateMapper<W>> StateMapFstOp<W, F, B, M> {
+    pub fn new(fst: B, mapper: M) -> Self {
+        let properties = mapper.properties(fst.borrow().properties());
+        Self {
+            fst,
+            mapper,
+            properties,
+            w: PhantomData,
+        }
+    }
+}
+
+impl<W: Semiring, F: Fst<W>, B: Borrow<F>, M: StateMapper<W>> FstOp<W>
+    for StateMapFstOp<W, F, B, M>
+{
+    fn compute_start(&self) -> Result<Option<StateId>> {
+        Ok(self.fst.borrow().start())
+    }
+
+    fn compute_trs(&self, state: StateId) -> Result<TrsVec<W>> {
+        let mut trs = self.fst.borrow().get_trs(state)?.trs().to_vec();
+        self.mapper.map_trs(&mut trs)?;
+        Ok(TrsVec(Arc::new(trs)))
+    }
+
+    fn compute_final_weight(&self, state: StateId) -> Result<Option<W>> {
+        let mut final_weight = self.fst.borrow().final_weight(state)?;
+        let trs = self.fst.borrow().get_trs(state)?;
+        self.mapper.map_final_weight(&mut final_weight, trs.trs())?;
+        Ok(final_weight)
+    }
+
+    fn properties(&self) -> FstProperties {
+        self.properties
+    }
+}
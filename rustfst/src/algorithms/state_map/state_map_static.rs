@@ -0,0 +1,123 @@
+use anyhow::Result;
+
+use crate::algorithms::state_map::StateMapper;
+use crate::fst_properties::FstProperties;
+use crate::fst_traits::MutableFst;
+use crate::semirings::Semiring;
+use crate::StateId;
+
+/// Maps every state of the FST using a `StateMapper` object : the trs leaving
+/// each state and its final weight are mapped at once.
+///
+/// # Example
+/// ```
+/// # use anyhow::Result;
+/// # use rustfst::semirings::{Semiring, ProbabilityWeight};
+/// # use rustfst::fst_impls::VectorFst;
+/// # use rustfst::fst_traits::{CoreFst, MutableFst};
+/// # use rustfst::algorithms::state_map::{state_map, TrSumMapper};
+/// # use rustfst::Tr;
+/// # fn main() -> Result<()> {
+/// let mut fst = VectorFst::<ProbabilityWeight>::new();
+/// let s0 = fst.add_state();
+/// let s1 = fst.add_state();
+/// fst.set_start(s0)?;
+/// fst.add_tr(s0, Tr::new(1, 2, ProbabilityWeight::new(0.3), s1))?;
+/// fst.add_tr(s0, Tr::new(1, 2, ProbabilityWeight::new(0.5), s1))?;
+/// fst.set_final(s1, ProbabilityWeight::one())?;
+///
+/// state_map(&mut fst, &TrSumMapper {})?;
+///
+/// assert_eq!(fst.num_trs(s0)?, 1);
+/// assert_eq!(fst.get_trs(s0)?[0].weight, ProbabilityWeight::new(0.8));
+/// # Ok(())
+/// # }
+/// ```
+pub fn state_map<W, F, M>(ifst: &mut F, mapper: &M) -> Result<()>
+where
+    W: Semiring,
+    F: MutableFst<W>,
+    M: StateMapper<W>,
+{
+    if ifst.start().is_none() {
+        return Ok(());
+    }
+
+    let inprops = ifst.properties();
+
+    for state in 0..(ifst.num_states() as StateId) {
+        let mut trs = ifst.pop_trs(state)?;
+        let mut final_weight = ifst.final_weight(state)?;
+        mapper.map_final_weight(&mut final_weight, &trs)?;
+        mapper.map_trs(&mut trs)?;
+        unsafe { ifst.set_trs_unchecked(state, trs) };
+        match final_weight {
+            Some(w) => ifst.set_final(state, w)?,
+            None => ifst.delete_final_weight(state)?,
+        };
+    }
+
+    ifst.set_properties_with_mask(mapper.properties(inprops), FstProperties::all_properties());
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::algorithms::state_map::{IdentityStateMapper, TrSumMapper, TrUniqueMapper};
+    use crate::algorithms::{tr_sum, tr_unique};
+    use crate::fst_impls::VectorFst;
+    use crate::fst_traits::CoreFst;
+    use crate::semirings::{ProbabilityWeight, Semiring};
+    use crate::Tr;
+
+    fn build_fst() -> Result<VectorFst<ProbabilityWeight>> {
+        let mut fst = VectorFst::<ProbabilityWeight>::new();
+        let s0 = fst.add_state();
+        let s1 = fst.add_state();
+        fst.set_start(s0)?;
+        fst.add_tr(s0, Tr::new(0, 0, ProbabilityWeight::new(0.3), s1))?;
+        fst.add_tr(s0, Tr::new(0, 1, ProbabilityWeight::new(0.3), s1))?;
+        fst.add_tr(s0, Tr::new(1, 0, ProbabilityWeight::new(0.3), s1))?;
+        fst.add_tr(s0, Tr::new(0, 0, ProbabilityWeight::new(0.3), s1))?;
+        fst.add_tr(s0, Tr::new(0, 0, ProbabilityWeight::new(0.1), s1))?;
+        fst.add_tr(s1, Tr::new(2, 2, ProbabilityWeight::new(0.5), s1))?;
+        fst.set_final(s1, ProbabilityWeight::new(0.7))?;
+        Ok(fst)
+    }
+
+    #[test]
+    fn test_state_map_identity() -> Result<()> {
+        let fst = build_fst()?;
+        let mut fst_res = fst.clone();
+        state_map(&mut fst_res, &IdentityStateMapper {})?;
+        assert_eq!(fst_res, fst);
+        Ok(())
+    }
+
+    #[test]
+    fn test_state_map_tr_sum() -> Result<()> {
+        let mut fst_ref = build_fst()?;
+        tr_sum(&mut fst_ref);
+
+        let mut fst_res = build_fst()?;
+        state_map(&mut fst_res, &TrSumMapper {})?;
+        assert_eq!(fst_res, fst_ref);
+        assert_eq!(fst_res.num_trs(0)?, 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_state_map_tr_unique() -> Result<()> {
+        let mut fst_ref = build_fst()?;
+        tr_unique(&mut fst_ref);
+
+        let mut fst_res = build_fst()?;
+        state_map(&mut fst_res, &TrUniqueMapper {})?;
+        assert_eq!(fst_res, fst_ref);
+        assert_eq!(fst_res.num_trs(0)?, 4);
+        Ok(())
+    }
+}
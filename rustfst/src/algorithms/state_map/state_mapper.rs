@@ -0,0 +1,35 @@
+use std::ops::Deref;
+
+use anyhow::Result;
+
+use crate::fst_properties::FstProperties;
+use crate::semirings::Semiring;
+use crate::Tr;
+
+/// The StateMapper interface defines how the trs leaving a state and its final
+/// weight are mapped. Contrary to `TrMapper`, all the trs of a state are mapped at
+/// once which is useful to implement operations that change the number of trs.
+pub trait StateMapper<W: Semiring> {
+    /// How to modify the final weight of a state. `None` means the state is not final.
+    /// The trs leaving the state before they are mapped are provided.
+    fn map_final_weight(&self, weight: &mut Option<W>, trs: &[Tr<W>]) -> Result<()>;
+
+    /// How to modify the trs leaving a state.
+    fn map_trs(&self, trs: &mut Vec<Tr<W>>) -> Result<()>;
+
+    fn properties(&self, inprops: FstProperties) -> FstProperties;
+}
+
+impl<W: Semiring, M: StateMapper<W>, MP: Deref<Target = M>> StateMapper<W> for MP {
+    fn map_final_weight(&self, weight: &mut Option<W>, trs: &[Tr<W>]) -> Result<()> {
+        self.deref().map_final_weight(weight, trs)
+    }
+
+    fn map_trs(&self, trs: &mut Vec<Tr<W>>) -> Result<()> {
+        self.deref().map_trs(trs)
+    }
+
+    fn properties(&self, inprops: FstProperties) -> FstProperties {
+        self.deref().properties(inprops)
+    }
+}
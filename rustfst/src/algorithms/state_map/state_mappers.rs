@@ -0,0 +1,80 @@
+use anyhow::Result;
+
+use crate::algorithms::state_map::StateMapper;
+use crate::algorithms::tr_unique::tr_compare;
+use crate::fst_properties::FstProperties;
+use crate::semirings::Semiring;
+use crate::Tr;
+
+/// Mapper that returns its input.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct IdentityStateMapper {}
+
+impl<W: Semiring> StateMapper<W> for IdentityStateMapper {
+    fn map_final_weight(&self, _weight: &mut Option<W>, _trs: &[Tr<W>]) -> Result<()> {
+        Ok(())
+    }
+
+    fn map_trs(&self, _trs: &mut Vec<Tr<W>>) -> Result<()> {
+        Ok(())
+    }
+
+    fn properties(&self, inprops: FstProperties) -> FstProperties {
+        inprops
+    }
+}
+
+/// Mapper that Plus-sums the weights of the trs leaving the same state, going to
+/// the same state and with the same input and output labels.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TrSumMapper {}
+
+impl<W: Semiring> StateMapper<W> for TrSumMapper {
+    fn map_final_weight(&self, _weight: &mut Option<W>, _trs: &[Tr<W>]) -> Result<()> {
+        Ok(())
+    }
+
+    fn map_trs(&self, trs: &mut Vec<Tr<W>>) -> Result<()> {
+        trs.sort_by(tr_compare);
+        let mut n_trs: usize = 0;
+        for i in 0..trs.len() {
+            if n_trs > 0 && tr_compare(&trs[i], &trs[n_trs - 1]).is_eq() {
+                let (left, right) = trs.split_at_mut(i);
+                left[n_trs - 1].weight.plus_assign(&right[0].weight)?;
+            } else {
+                trs.swap(n_trs, i);
+                n_trs += 1;
+            }
+        }
+        trs.truncate(n_trs);
+        Ok(())
+    }
+
+    fn properties(&self, inprops: FstProperties) -> FstProperties {
+        inprops
+            & FstProperties::arcsort_properties()
+            & FstProperties::delete_arcs_properties()
+            & FstProperties::weight_invariant_properties()
+    }
+}
+
+/// Mapper that keeps a single instance of the trs leaving the same state, going
+/// to the same state and with the same input labels, output labels and weight.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TrUniqueMapper {}
+
+impl<W: Semiring> StateMapper<W> for TrUniqueMapper {
+    fn map_final_weight(&self, _weight: &mut Option<W>, _trs: &[Tr<W>]) -> Result<()> {
+        Ok(())
+    }
+
+    fn map_trs(&self, trs: &mut Vec<Tr<W>>) -> Result<()> {
+        trs.sort_by(tr_compare);
+        trs.dedup();
+        Ok(())
+    }
+
+    fn properties(&self, inprops: FstProperties) -> FstProperties {
+        inprops & FstProperties::arcsort_properties() & FstProperties::delete_arcs_properties()
+    }
+}
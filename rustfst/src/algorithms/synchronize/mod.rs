@@ -0,0 +1,6 @@
+mod synchronize_fst;
+mod synchronize_op;
+mod synchronize_static;
+
+pub use synchronize_fst::SynchronizeFst;
+pub use synchronize_static::synchronize;
@@ -0,0 +1,187 @@
+use std::borrow::Borrow;
+use std::fmt::Debug;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use anyhow::Result;
+
+use crate::algorithms::lazy::{FstOp, StateTable};
+use crate::fst_properties::mutable_properties::synchronization_properties;
+use crate::fst_properties::FstProperties;
+use crate::fst_traits::ExpandedFst;
+use crate::semirings::Semiring;
+use crate::{Label, StateId, Tr, Trs, TrsVec, EPS_LABEL};
+
+/// Element of the state table : a state of the input FST along with the
+/// residual input and output labels that still have to be emitted.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SynchronizeElement {
+    /// State of the input FST. `None` for the states used to flush the residual labels.
+    pub state: Option<StateId>,
+    pub istring: Vec<Label>,
+    pub ostring: Vec<Label>,
+}
+
+impl SynchronizeElement {
+    pub fn new(state: Option<StateId>, istring: Vec<Label>, ostring: Vec<Label>) -> Self {
+        Self {
+            state,
+            istring,
+            ostring,
+        }
+    }
+}
+
+pub struct SynchronizeFstOp<W: Semiring, F: ExpandedFst<W>, B: Borrow<F>> {
+    fst: B,
+    state_table: StateTable<SynchronizeElement>,
+    /// The delay of a bounded delay FST can't exceed its number of states.
+    max_delay: usize,
+    properties: FstProperties,
+    w: PhantomData<(W, F)>,
+}
+
+impl<W: Semiring, F: ExpandedFst<W>, B: Borrow<F>> Debug for SynchronizeFstOp<W, F, B> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "SynchronizeFstOp {{ fst : {:?}, state_table : {:?}, max_delay : {:?} }}",
+            self.fst.borrow(),
+            self.state_table,
+            self.max_delay
+        )
+    }
+}
+
+/// Returns the first label of the concatenation of `s` and `l`.
+fn car(s: &[Label], l: Label) -> Label {
+    s.first().cloned().unwrap_or(l)
+}
+
+/// Returns the concatenation of `s` and `l` without its first label.
+fn cdr(s: &[Label], l: Label) -> Vec<Label> {
+    let mut r: Vec<Label> = s.iter().skip(1).cloned().collect();
+    if l != EPS_LABEL && !s.is_empty() {
+        r.push(l);
+    }
+    r
+}
+
+/// Returns the concatenation of `s` and `l`.
+fn concat(s: &[Label], l: Label) -> Vec<Label> {
+    let mut r = s.to_vec();
+    if l != EPS_LABEL {
+        r.push(l);
+    }
+    r
+}
+
+/// Returns true if the concatenation of `s` and `l` is empty.
+fn is_empty(s: &[Label], l: Label) -> bool {
+    s.is_empty() && l == EPS_LABEL
+}
+
+impl<W: Semiring, F: ExpandedFst<W>, B: Borrow<F>> SynchronizeFstOp<W, F, B> {
+    pub fn new(fst: B) -> Self {
+        let properties = synchronization_properties(fst.borrow().properties());
+        let max_delay = fst.borrow().num_states();
+        Self {
+            fst,
+            state_table: StateTable::new(),
+            max_delay,
+            properties,
+            w: PhantomData,
+        }
+    }
+
+    fn find_state(&self, element: SynchronizeElement) -> Result<StateId> {
+        if element.istring.len() > self.max_delay || element.ostring.len() > self.max_delay {
+            bail!(
+                "SynchronizeFst: the delay of the input FST is unbounded (residual strings longer than {} labels)",
+                self.max_delay
+            )
+        }
+        Ok(self.state_table.find_id(element))
+    }
+
+    fn input_final_weight(&self, element: &SynchronizeElement) -> Result<Option<W>> {
+        match element.state {
+            None => Ok(Some(W::one())),
+            Some(s) => self.fst.borrow().final_weight(s),
+        }
+    }
+}
+
+impl<W: Semiring, F: ExpandedFst<W>, B: Borrow<F>> FstOp<W> for SynchronizeFstOp<W, F, B> {
+    fn compute_start(&self) -> Result<Option<StateId>> {
+        match self.fst.borrow().start() {
+            None => Ok(None),
+            Some(s) => Ok(Some(self.find_state(SynchronizeElement::new(
+                Some(s),
+                vec![],
+                vec![],
+            ))?)),
+        }
+    }
+
+    fn compute_trs(&self, id: StateId) -> Result<TrsVec<W>> {
+        let element = self.state_table.find_tuple(id);
+        let mut trs = vec![];
+        if let Some(state) = element.state {
+            for tr in self.fst.borrow().get_trs(state)?.trs() {
+                let (ilabel, olabel, istring, ostring) = if !is_empty(&element.istring, tr.ilabel)
+                    && !is_empty(&element.ostring, tr.olabel)
+                {
+                    (
+                        car(&element.istring, tr.ilabel),
+                        car(&element.ostring, tr.olabel),
+                        cdr(&element.istring, tr.ilabel),
+                        cdr(&element.ostring, tr.olabel),
+                    )
+                } else {
+                    (
+                        EPS_LABEL,
+                        EPS_LABEL,
+                        concat(&element.istring, tr.ilabel),
+                        concat(&element.ostring, tr.olabel),
+                    )
+                };
+                let nextstate = self.find_state(SynchronizeElement::new(
+                    Some(tr.nextstate),
+                    istring,
+                    ostring,
+                ))?;
+                trs.push(Tr::new(ilabel, olabel, tr.weight.clone(), nextstate));
+            }
+        }
+        if let Some(final_weight) = self.input_final_weight(&element)? {
+            if !element.istring.is_empty() || !element.ostring.is_empty() {
+                let nextstate = self.find_state(SynchronizeElement::new(
+                    None,
+                    cdr(&element.istring, EPS_LABEL),
+                    cdr(&element.ostring, EPS_LABEL),
+                ))?;
+                trs.push(Tr::new(
+                    car(&element.istring, EPS_LABEL),
+                    car(&element.ostring, EPS_LABEL),
+                    final_weight,
+                    nextstate,
+                ));
+            }
+        }
+        Ok(TrsVec(Arc::new(trs)))
+    }
+
+    fn compute_final_weight(&self, id: StateId) -> Result<Option<W>> {
+        let element = self.state_table.find_tuple(id);
+        if element.istring.is_empty() && element.ostring.is_empty() {
+            self.input_final_weight(&element)
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn properties(&self) -> FstProperties {
+        self.properties
+    }
+}
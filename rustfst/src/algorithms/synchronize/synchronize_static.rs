@@ -0,0 +1,140 @@
+use anyhow::Result;
+
+use crate::algorithms::synchronize::SynchronizeFst;
+use crate::fst_traits::{AllocableFst, ExpandedFst, MutableFst};
+use crate::semirings::Semiring;
+
+/// Synchronizes an FST with bounded delay. The output is an equivalent FST in which,
+/// on every path, each transition has non-epsilon input and output labels until one
+/// of the two sides is exhausted. Only the labels are moved : the weights
+/// and the number of paths are preserved.
+///
+/// An error is returned if the delay of the input FST is unbounded.
+///
+/// # Example
+/// ```
+/// # use anyhow::Result;
+/// # use rustfst::semirings::{Semiring, TropicalWeight};
+/// # use rustfst::fst_impls::VectorFst;
+/// # use rustfst::fst_traits::{FstIterator, MutableFst};
+/// # use rustfst::algorithms::synchronize::synchronize;
+/// # use rustfst::{Tr, Trs, EPS_LABEL};
+/// # fn main() -> Result<()> {
+/// let mut fst = VectorFst::<TropicalWeight>::new();
+/// let s0 = fst.add_state();
+/// let s1 = fst.add_state();
+/// let s2 = fst.add_state();
+/// fst.set_start(s0)?;
+/// fst.add_tr(s0, Tr::new(1, EPS_LABEL, TropicalWeight::one(), s1))?;
+/// fst.add_tr(s1, Tr::new(EPS_LABEL, 2, TropicalWeight::one(), s2))?;
+/// fst.set_final(s2, TropicalWeight::one())?;
+///
+/// let fst_sync: VectorFst<_> = synchronize(&fst)?;
+///
+/// // Labels 1 and 2 are now carried by the same transition.
+/// let trs: Vec<_> = fst_sync
+///     .fst_iter()
+///     .flat_map(|data| data.trs.trs().to_vec())
+///     .filter(|tr| tr.ilabel != EPS_LABEL || tr.olabel != EPS_LABEL)
+///     .collect();
+/// assert_eq!(trs.len(), 1);
+/// assert_eq!((trs[0].ilabel, trs[0].olabel), (1, 2));
+/// # Ok(())
+/// # }
+/// ```
+pub fn synchronize<W, F1, F2>(ifst: &F1) -> Result<F2>
+where
+    W: Semiring,
+    F1: ExpandedFst<W>,
+    F2: MutableFst<W> + AllocableFst<W>,
+{
+    let fst: SynchronizeFst<_, F1, _> = SynchronizeFst::new(ifst);
+    fst.compute()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::fst_impls::VectorFst;
+    use crate::fst_traits::CoreFst;
+    use crate::semirings::TropicalWeight;
+    use crate::{StateId, Tr, Trs, EPS_LABEL};
+
+    // Returns true if, on every path, once a side only has epsilon labels it stays so.
+    fn is_synchronized<F: ExpandedFst<TropicalWeight>>(
+        fst: &F,
+        state: StateId,
+        ieps: bool,
+        oeps: bool,
+    ) -> Result<bool> {
+        for tr in fst.get_trs(state)?.trs() {
+            let tr_ieps = tr.ilabel == EPS_LABEL;
+            let tr_oeps = tr.olabel == EPS_LABEL;
+            if (ieps && !tr_ieps) || (oeps && !tr_oeps) {
+                return Ok(false);
+            }
+            let (next_ieps, next_oeps) = if tr_ieps && tr_oeps {
+                (ieps, oeps)
+            } else {
+                (tr_ieps, tr_oeps)
+            };
+            if !is_synchronized(fst, tr.nextstate, next_ieps, next_oeps)? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    fn sorted_paths<F: ExpandedFst<TropicalWeight>>(fst: &F) -> Vec<String> {
+        let mut paths: Vec<_> = fst
+            .paths_iter()
+            .map(|p| format!("{:?} {:?} {:?}", p.ilabels, p.olabels, p.weight))
+            .collect();
+        paths.sort();
+        paths
+    }
+
+    #[test]
+    fn test_synchronize_bounded_delay() -> Result<()> {
+        let mut fst = VectorFst::<TropicalWeight>::new();
+        let s0 = fst.add_state();
+        let s1 = fst.add_state();
+        let s2 = fst.add_state();
+        let s3 = fst.add_state();
+        fst.set_start(s0)?;
+        fst.add_tr(s0, Tr::new(1, EPS_LABEL, TropicalWeight::new(1.0), s1))?;
+        fst.add_tr(s1, Tr::new(2, 3, TropicalWeight::new(2.0), s2))?;
+        fst.add_tr(s2, Tr::new(EPS_LABEL, 4, TropicalWeight::new(0.5), s3))?;
+        fst.add_tr(s0, Tr::new(EPS_LABEL, 5, TropicalWeight::new(1.5), s2))?;
+        fst.set_final(s1, TropicalWeight::new(0.5))?;
+        fst.set_final(s3, TropicalWeight::new(1.0))?;
+        let start = fst.start().unwrap();
+        assert!(!is_synchronized(&fst, start, false, false)?);
+
+        let fst_sync: VectorFst<_> = synchronize(&fst)?;
+        let start = fst_sync.start().unwrap();
+        assert!(is_synchronized(&fst_sync, start, false, false)?);
+        assert_eq!(sorted_paths(&fst_sync), sorted_paths(&fst));
+
+        let lazy_fst = SynchronizeFst::<_, VectorFst<_>, _>::new(&fst);
+        let fst_lazy: VectorFst<_> = lazy_fst.compute()?;
+        assert_eq!(fst_lazy, fst_sync);
+        Ok(())
+    }
+
+    #[test]
+    fn test_synchronize_unbounded_delay() -> Result<()> {
+        // Transduces 1* to epsilon : the delay grows with the length of the input.
+        let mut fst = VectorFst::<TropicalWeight>::new();
+        let s0 = fst.add_state();
+        fst.set_start(s0)?;
+        fst.add_tr(s0, Tr::new(1, EPS_LABEL, TropicalWeight::one(), s0))?;
+        fst.add_tr(s0, Tr::new(EPS_LABEL, 2, TropicalWeight::one(), s0))?;
+        fst.set_final(s0, TropicalWeight::one())?;
+
+        let res: Result<VectorFst<_>> = synchronize(&fst);
+        assert!(res.is_err());
+        Ok(())
+    }
+}
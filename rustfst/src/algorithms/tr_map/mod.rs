@@ -0,0 +1,7 @@
+mod tr_map_fst;
+mod tr_map_op;
+mod tr_map_static;
+
+pub use tr_map_fst::TrMapFst;
+pub(crate) use tr_map_op::TrMapFstOp;
+pub use tr_map_static::{tr_map, FinalTr, MapFinalAction, TrMapper};
@@ -0,0 +1,146 @@
+use std::borrow::Borrow;
+use std::fmt::Debug;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use anyhow::Result;
+
+use crate::algorithms::lazy::FstOp;
+use crate::algorithms::{FinalTr, MapFinalAction, TrMapper};
+use crate::fst_properties::FstProperties;
+use crate::fst_traits::ExpandedFst;
+use crate::semirings::Semiring;
+use crate::{StateId, Tr, Trs, TrsVec, EPS_LABEL};
+
+pub struct TrMapFstOp<W: Semiring, F: ExpandedFst<W>, B: Borrow<F>, M: TrMapper<W>> {
+    fst: B,
+    mapper: M,
+    final_action: MapFinalAction,
+    /// The superfinal state, if any, is appended after the states of the input FST.
+    superfinal: StateId,
+    properties: FstProperties,
+    w: PhantomData<(W, F)>,
+}
+
+impl<W: Semiring, F: ExpandedFst<W>, B: Borrow<F>, M: TrMapper<W>> Debug
+    for TrMapFstOp<W, F, B, M>
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "TrMapFstOp {{ fst: {:?}, superfinal: {:?}, properties: {:?} }}",
+            self.fst.borrow(),
+            self.superfinal,
+            self.properties
+        )
+    }
+}
+
+impl<W: Semiring, F: ExpandedFst<W>, B: Borrow<F>, M: TrMapper<W>> TrMapFstOp<W, F, B, M> {
+    pub fn new(fst: B, mapper: M) -> Self {
+        let superfinal = fst.borrow().num_states() as StateId;
+        let properties = if fst.borrow().start().is_none() {
+            FstProperties::null_properties()
+        } else {
+            mapper.properties(fst.borrow().properties())
+        };
+        let final_action = mapper.final_action();
+        Self {
+            fst,
+            mapper,
+            final_action,
+            superfinal,
+            properties,
+            w: PhantomData,
+        }
+    }
+
+    /// Maps the final weight of a state of the input FST, if it is final.
+    fn map_final_tr(&self, state: StateId) -> Result<Option<FinalTr<W>>> {
+        match self.fst.borrow().final_weight(state)? {
+            Some(weight) => {
+                let mut final_tr = FinalTr {
+                    ilabel: EPS_LABEL,
+                    olabel: EPS_LABEL,
+                    weight,
+                };
+                self.mapper.final_tr_map(&mut final_tr)?;
+                Ok(Some(final_tr))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+impl<W: Semiring, F: ExpandedFst<W>, B: Borrow<F>, M: TrMapper<W>> FstOp<W>
+    for TrMapFstOp<W, F, B, M>
+{
+    fn compute_start(&self) -> Result<Option<StateId>> {
+        Ok(self.fst.borrow().start())
+    }
+
+    fn compute_trs(&self, state: StateId) -> Result<TrsVec<W>> {
+        if state == self.superfinal {
+            return Ok(TrsVec::default());
+        }
+        let trs_owner = self.fst.borrow().get_trs(state)?;
+        let mut trs = Vec::with_capacity(trs_owner.trs().len() + 1);
+        for tr in trs_owner.trs() {
+            let mut mapped_tr = tr.clone();
+            self.mapper.tr_map(&mut mapped_tr)?;
+            trs.push(mapped_tr);
+        }
+        if let Some(final_tr) = self.map_final_tr(state)? {
+            let needs_superfinal = match self.final_action {
+                MapFinalAction::MapNoSuperfinal => false,
+                MapFinalAction::MapAllowSuperfinal => {
+                    final_tr.ilabel != EPS_LABEL || final_tr.olabel != EPS_LABEL
+                }
+                MapFinalAction::MapRequireSuperfinal => {
+                    final_tr.ilabel != EPS_LABEL
+                        || final_tr.olabel != EPS_LABEL
+                        || !final_tr.weight.is_zero()
+                }
+            };
+            if needs_superfinal {
+                trs.push(Tr::new(
+                    final_tr.ilabel,
+                    final_tr.olabel,
+                    final_tr.weight,
+                    self.superfinal,
+                ));
+            }
+        }
+        Ok(TrsVec(Arc::new(trs)))
+    }
+
+    fn compute_final_weight(&self, state: StateId) -> Result<Option<W>> {
+        if state == self.superfinal {
+            return Ok(Some(W::one()));
+        }
+        let final_tr = match self.map_final_tr(state)? {
+            Some(final_tr) => final_tr,
+            None => return Ok(None),
+        };
+        match self.final_action {
+            MapFinalAction::MapNoSuperfinal => {
+                if final_tr.ilabel != EPS_LABEL || final_tr.olabel != EPS_LABEL {
+                    bail!("TrMapFst: Non-zero tr labels for superfinal tr")
+                }
+                Ok(Some(final_tr.weight))
+            }
+            MapFinalAction::MapAllowSuperfinal => {
+                if final_tr.ilabel != EPS_LABEL || final_tr.olabel != EPS_LABEL {
+                    Ok(None)
+                } else {
+                    Ok(Some(final_tr.weight))
+                }
+            }
+            MapFinalAction::MapRequireSuperfinal => Ok(None),
+        }
+    }
+
+    fn properties(&self) -> FstProperties {
+        self.properties
+    }
+}
@@ -183,3 +183,93 @@ where
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::algorithms::TrMapFst;
+    use crate::fst_impls::{ConstFst, VectorFst};
+    use crate::fst_traits::CoreFst;
+    use crate::semirings::TropicalWeight;
+
+    /// Moves the final weights on a transition labeled `label` when they are not One.
+    struct FinalLabelMapper {
+        label: Label,
+        final_action: fn() -> MapFinalAction,
+    }
+
+    impl TrMapper<TropicalWeight> for FinalLabelMapper {
+        fn tr_map(&self, _tr: &mut Tr<TropicalWeight>) -> Result<()> {
+            Ok(())
+        }
+
+        fn final_tr_map(&self, final_tr: &mut FinalTr<TropicalWeight>) -> Result<()> {
+            if !final_tr.weight.is_one() {
+                final_tr.ilabel = self.label;
+                final_tr.olabel = self.label;
+            }
+            Ok(())
+        }
+
+        fn final_action(&self) -> MapFinalAction {
+            (self.final_action)()
+        }
+
+        fn properties(&self, _inprops: FstProperties) -> FstProperties {
+            FstProperties::empty()
+        }
+    }
+
+    fn build_fst() -> Result<VectorFst<TropicalWeight>> {
+        let mut fst = VectorFst::<TropicalWeight>::new();
+        let s0 = fst.add_state();
+        let s1 = fst.add_state();
+        let s2 = fst.add_state();
+        fst.set_start(s0)?;
+        fst.add_tr(s0, Tr::new(1, 2, TropicalWeight::new(1.0), s1))?;
+        fst.add_tr(s0, Tr::new(3, 4, TropicalWeight::new(2.0), s2))?;
+        fst.set_final(s1, TropicalWeight::new(3.0))?;
+        fst.set_final(s2, TropicalWeight::one())?;
+        Ok(fst)
+    }
+
+    fn check_lazy_tr_map(final_action: fn() -> MapFinalAction) -> Result<()> {
+        let mapper = FinalLabelMapper {
+            label: 5,
+            final_action,
+        };
+        let fst: ConstFst<_> = build_fst()?.into();
+
+        let mut fst_static: VectorFst<_> = build_fst()?;
+        tr_map(&mut fst_static, &mapper)?;
+        let fst_lazy: VectorFst<_> =
+            TrMapFst::<_, ConstFst<_>, _, _>::new(&fst, &mapper).compute()?;
+
+        assert_eq!(fst_lazy, fst_static);
+        Ok(())
+    }
+
+    #[test]
+    fn test_tr_map_fst_allow_superfinal() -> Result<()> {
+        check_lazy_tr_map(|| MapFinalAction::MapAllowSuperfinal)
+    }
+
+    #[test]
+    fn test_tr_map_fst_require_superfinal() -> Result<()> {
+        check_lazy_tr_map(|| MapFinalAction::MapRequireSuperfinal)
+    }
+
+    #[test]
+    fn test_tr_map_fst_no_superfinal() -> Result<()> {
+        let mapper = FinalLabelMapper {
+            label: 5,
+            final_action: || MapFinalAction::MapNoSuperfinal,
+        };
+        let fst = build_fst()?;
+        let fst_lazy = TrMapFst::<_, VectorFst<_>, _, _>::new(&fst, &mapper);
+        assert!(fst_lazy.final_weight(1).is_err());
+        assert_eq!(fst_lazy.final_weight(2)?, Some(TropicalWeight::one()));
+        Ok(())
+    }
+}
@@ -3,18 +3,22 @@ mod tr_mapper_to_weight_convert_mapper;
 
 mod identity_tr_mapper;
 mod input_epsilon_mapper;
+mod invert_mapper;
 mod invert_weight_mapper;
 mod output_epsilon_mapper;
 mod plus_mapper;
+mod project_mapper;
 mod quantize_mapper;
 mod rm_weight_mapper;
 mod times_mapper;
 
 pub use self::identity_tr_mapper::IdentityTrMapper;
 pub use self::input_epsilon_mapper::InputEpsilonMapper;
+pub use self::invert_mapper::InvertMapper;
 pub use self::invert_weight_mapper::InvertWeightMapper;
 pub use self::output_epsilon_mapper::OutputEpsilonMapper;
 pub use self::plus_mapper::PlusMapper;
+pub use self::project_mapper::ProjectMapper;
 pub use self::quantize_mapper::QuantizeMapper;
 pub use self::rm_weight_mapper::RmWeightMapper;
 pub use self::times_mapper::TimesMapper;
@@ -0,0 +1,51 @@
+use crate::StateId;
+
+/// Union-Find (disjoint sets) structure over state ids with union by rank
+/// and path compression.
+#[derive(Debug, Clone)]
+pub(crate) struct UnionFind {
+    parent: Vec<StateId>,
+    rank: Vec<usize>,
+}
+
+impl UnionFind {
+    /// Creates `n` singleton sets.
+    pub fn new(n: usize) -> Self {
+        Self {
+            parent: (0..n as StateId).collect(),
+            rank: vec![0; n],
+        }
+    }
+
+    /// Returns the representative of the set containing `x`.
+    pub fn find_set(&mut self, x: StateId) -> StateId {
+        let mut root = x;
+        while self.parent[root as usize] != root {
+            root = self.parent[root as usize];
+        }
+        let mut current = x;
+        while self.parent[current as usize] != root {
+            let next = self.parent[current as usize];
+            self.parent[current as usize] = root;
+            current = next;
+        }
+        root
+    }
+
+    /// Merges the sets containing `x` and `y`.
+    pub fn union(&mut self, x: StateId, y: StateId) {
+        let x = self.find_set(x);
+        let y = self.find_set(y);
+        if x == y {
+            return;
+        }
+        if self.rank[x as usize] < self.rank[y as usize] {
+            self.parent[x as usize] = y;
+        } else {
+            self.parent[y as usize] = x;
+            if self.rank[x as usize] == self.rank[y as usize] {
+                self.rank[x as usize] += 1;
+            }
+        }
+    }
+}
@@ -0,0 +1,205 @@
+use std::fmt;
+
+use anyhow::Result;
+
+use crate::fst_properties::{compat_properties, compute_fst_properties, FstProperties};
+use crate::fst_traits::ExpandedFst;
+use crate::semirings::Semiring;
+use crate::{StateId, Trs};
+
+/// Inconsistency found in an FST by [`verify`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FstDefect {
+    /// The start state is not a state of the FST.
+    InvalidStartState { start: StateId, num_states: usize },
+    /// The destination of the transition is not a state of the FST.
+    InvalidNextState {
+        state: StateId,
+        tr_index: usize,
+        nextstate: StateId,
+        num_states: usize,
+    },
+    /// The properties stored in the FST contradict the ones computed from its structure.
+    IncompatibleProperties {
+        stored: FstProperties,
+        computed: FstProperties,
+    },
+}
+
+impl fmt::Display for FstDefect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FstDefect::InvalidStartState { start, num_states } => write!(
+                f,
+                "Start state {} is out of range (number of states : {})",
+                start, num_states
+            ),
+            FstDefect::InvalidNextState {
+                state,
+                tr_index,
+                nextstate,
+                num_states,
+            } => write!(
+                f,
+                "Transition {} of state {} goes to state {} which is out of range (number of states : {})",
+                tr_index, state, nextstate, num_states
+            ),
+            FstDefect::IncompatibleProperties { stored, computed } => write!(
+                f,
+                "Stored properties {:?} are incompatible with the computed properties {:?}",
+                stored, computed
+            ),
+        }
+    }
+}
+
+/// Checks the sanity of an FST and returns the list of defects found : start
+/// state or destination of a transition out of range and stored properties that
+/// contradict the structure of the FST. An empty list means the FST is valid.
+///
+/// The properties are only checked if the FST is structurally valid, as computing
+/// them requires all the states to be in range.
+///
+/// # Example
+/// ```
+/// # use anyhow::Result;
+/// # use rustfst::semirings::{Semiring, TropicalWeight};
+/// # use rustfst::fst_impls::VectorFst;
+/// # use rustfst::fst_traits::MutableFst;
+/// # use rustfst::algorithms::{verify, FstDefect};
+/// # use rustfst::Tr;
+/// # fn main() -> Result<()> {
+/// let mut fst = VectorFst::<TropicalWeight>::new();
+/// let s0 = fst.add_state();
+/// let s1 = fst.add_state();
+/// fst.set_start(s0)?;
+/// fst.add_tr(s0, Tr::new(1, 1, TropicalWeight::one(), s1))?;
+/// fst.set_final(s1, TropicalWeight::one())?;
+/// assert!(verify(&fst)?.is_empty());
+///
+/// fst.add_tr(s1, Tr::new(2, 2, TropicalWeight::one(), 5))?;
+/// assert_eq!(
+///     verify(&fst)?,
+///     vec![FstDefect::InvalidNextState { state: s1, tr_index: 0, nextstate: 5, num_states: 2 }]
+/// );
+/// # Ok(())
+/// # }
+/// ```
+pub fn verify<W: Semiring, F: ExpandedFst<W>>(fst: &F) -> Result<Vec<FstDefect>> {
+    let mut defects = vec![];
+    let num_states = fst.num_states();
+
+    if let Some(start) = fst.start() {
+        if start as usize >= num_states {
+            defects.push(FstDefect::InvalidStartState { start, num_states });
+        }
+    }
+
+    for state in 0..(num_states as StateId) {
+        for (tr_index, tr) in fst.get_trs(state)?.trs().iter().enumerate() {
+            if tr.nextstate as usize >= num_states {
+                defects.push(FstDefect::InvalidNextState {
+                    state,
+                    tr_index,
+                    nextstate: tr.nextstate,
+                    num_states,
+                });
+            }
+        }
+    }
+
+    if defects.is_empty() {
+        let stored = fst.properties();
+        let mut known = FstProperties::empty();
+        let computed =
+            compute_fst_properties(fst, FstProperties::all_properties(), &mut known, false)?;
+        if !compat_properties(stored, computed) {
+            defects.push(FstDefect::IncompatibleProperties { stored, computed });
+        }
+    }
+
+    Ok(defects)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::fst_impls::VectorFst;
+    use crate::fst_traits::MutableFst;
+    use crate::semirings::TropicalWeight;
+    use crate::Tr;
+
+    fn build_fst() -> Result<VectorFst<TropicalWeight>> {
+        let mut fst = VectorFst::<TropicalWeight>::new();
+        let s0 = fst.add_state();
+        let s1 = fst.add_state();
+        fst.set_start(s0)?;
+        fst.add_tr(s0, Tr::new(1, 2, TropicalWeight::new(1.0), s1))?;
+        fst.add_tr(s1, Tr::new(3, 3, TropicalWeight::new(2.0), s0))?;
+        fst.set_final(s1, TropicalWeight::one())?;
+        Ok(fst)
+    }
+
+    #[test]
+    fn test_verify_valid() -> Result<()> {
+        let fst = build_fst()?;
+        assert!(verify(&fst)?.is_empty());
+        assert!(verify(&VectorFst::<TropicalWeight>::new())?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_verify_out_of_range() -> Result<()> {
+        let mut fst = build_fst()?;
+        fst.add_tr(0, Tr::new(1, 1, TropicalWeight::one(), 2))?;
+        fst.add_tr(1, Tr::new(1, 1, TropicalWeight::one(), 7))?;
+        unsafe { fst.set_start_unchecked(3) };
+
+        assert_eq!(
+            verify(&fst)?,
+            vec![
+                FstDefect::InvalidStartState {
+                    start: 3,
+                    num_states: 2
+                },
+                FstDefect::InvalidNextState {
+                    state: 0,
+                    tr_index: 1,
+                    nextstate: 2,
+                    num_states: 2
+                },
+                FstDefect::InvalidNextState {
+                    state: 1,
+                    tr_index: 1,
+                    nextstate: 7,
+                    num_states: 2
+                },
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_verify_properties() -> Result<()> {
+        let mut fst = build_fst()?;
+        fst.set_properties_with_mask(
+            FstProperties::ACCEPTOR | FstProperties::ACYCLIC,
+            FstProperties::ACCEPTOR
+                | FstProperties::NOT_ACCEPTOR
+                | FstProperties::ACYCLIC
+                | FstProperties::CYCLIC,
+        );
+
+        let defects = verify(&fst)?;
+        assert_eq!(defects.len(), 1);
+        match &defects[0] {
+            FstDefect::IncompatibleProperties { stored, computed } => {
+                assert!(stored.contains(FstProperties::ACCEPTOR));
+                assert!(computed.contains(FstProperties::NOT_ACCEPTOR | FstProperties::CYCLIC));
+            }
+            d => panic!("Unexpected defect {:?}", d),
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,118 @@
+use anyhow::Result;
+
+use crate::algorithms::{FinalTr, MapFinalAction, WeightConverter};
+use crate::fst_properties::FstProperties;
+use crate::semirings::{BooleanWeight, Semiring, TropicalWeight};
+use crate::Tr;
+
+/// Converter between `BooleanWeight` and `TropicalWeight`, in both directions.
+///
+/// `true` is mapped to the tropical `one` and `false` to the tropical `zero`. The other
+/// way around, every tropical weight except `zero` is mapped to `true`.
+pub struct BooleanWeightConverter {}
+
+fn boolean_to_tropical(weight: &BooleanWeight) -> TropicalWeight {
+    if *weight.value() {
+        TropicalWeight::one()
+    } else {
+        TropicalWeight::zero()
+    }
+}
+
+fn tropical_to_boolean(weight: &TropicalWeight) -> BooleanWeight {
+    BooleanWeight::new(!weight.is_zero())
+}
+
+impl WeightConverter<BooleanWeight, TropicalWeight> for BooleanWeightConverter {
+    fn tr_map(&mut self, tr: &Tr<BooleanWeight>) -> Result<Tr<TropicalWeight>> {
+        Ok(Tr::new(
+            tr.ilabel,
+            tr.olabel,
+            boolean_to_tropical(&tr.weight),
+            tr.nextstate,
+        ))
+    }
+
+    fn final_tr_map(
+        &mut self,
+        final_tr: &FinalTr<BooleanWeight>,
+    ) -> Result<FinalTr<TropicalWeight>> {
+        Ok(FinalTr {
+            ilabel: final_tr.ilabel,
+            olabel: final_tr.olabel,
+            weight: boolean_to_tropical(&final_tr.weight),
+        })
+    }
+
+    fn final_action(&self) -> MapFinalAction {
+        MapFinalAction::MapNoSuperfinal
+    }
+
+    fn properties(&self, inprops: FstProperties) -> FstProperties {
+        inprops
+    }
+}
+
+impl WeightConverter<TropicalWeight, BooleanWeight> for BooleanWeightConverter {
+    fn tr_map(&mut self, tr: &Tr<TropicalWeight>) -> Result<Tr<BooleanWeight>> {
+        Ok(Tr::new(
+            tr.ilabel,
+            tr.olabel,
+            tropical_to_boolean(&tr.weight),
+            tr.nextstate,
+        ))
+    }
+
+    fn final_tr_map(
+        &mut self,
+        final_tr: &FinalTr<TropicalWeight>,
+    ) -> Result<FinalTr<BooleanWeight>> {
+        Ok(FinalTr {
+            ilabel: final_tr.ilabel,
+            olabel: final_tr.olabel,
+            weight: tropical_to_boolean(&final_tr.weight),
+        })
+    }
+
+    fn final_action(&self) -> MapFinalAction {
+        MapFinalAction::MapNoSuperfinal
+    }
+
+    fn properties(&self, inprops: FstProperties) -> FstProperties {
+        inprops & FstProperties::weight_invariant_properties()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::algorithms::weight_convert;
+    use crate::fst_impls::VectorFst;
+    use crate::fst_traits::{MutableFst, SerializableFst};
+
+    #[test]
+    fn test_boolean_weight_converter() -> Result<()> {
+        let mut fst = VectorFst::<BooleanWeight>::new();
+        let s0 = fst.add_state();
+        let s1 = fst.add_state();
+        fst.set_start(s0)?;
+        fst.set_final(s1, BooleanWeight::one())?;
+        fst.add_tr(s0, Tr::new(1, 2, BooleanWeight::one(), s1))?;
+        fst.add_tr(s0, Tr::new(3, 3, BooleanWeight::zero(), s1))?;
+
+        let tropical_fst: VectorFst<TropicalWeight> =
+            weight_convert(&fst, &mut BooleanWeightConverter {})?;
+        let fst_back: VectorFst<BooleanWeight> =
+            weight_convert(&tropical_fst, &mut BooleanWeightConverter {})?;
+        assert_eq!(fst_back, fst);
+
+        let fst_text = VectorFst::<BooleanWeight>::from_text_string(&fst.text()?)?;
+        assert_eq!(fst_text, fst);
+
+        let mut fst_bin = vec![];
+        fst.store(&mut fst_bin)?;
+        assert_eq!(VectorFst::<BooleanWeight>::load(&fst_bin)?, fst);
+        Ok(())
+    }
+}
@@ -0,0 +1,55 @@
+use anyhow::Result;
+
+use crate::algorithms::{FinalTr, MapFinalAction, WeightConverter};
+use crate::fst_properties::FstProperties;
+use crate::semirings::{ExpectationWeight, SemimoduleWeight, Semiring};
+use crate::Tr;
+
+/// Converter mapping the weight `p` of each transition to the `ExpectationWeight`
+/// `<p, p * f>`, where `f` is the feature of the transition returned by the closure.
+/// The final weights `p` are mapped to `<p, 0>`.
+pub struct ExpectationConverter<FN> {
+    feature: FN,
+}
+
+impl<FN> ExpectationConverter<FN> {
+    pub fn new(feature: FN) -> Self {
+        Self { feature }
+    }
+}
+
+impl<W1, W2, FN> WeightConverter<W1, ExpectationWeight<W1, W2>> for ExpectationConverter<FN>
+where
+    W1: Semiring,
+    W2: SemimoduleWeight<W1>,
+    FN: FnMut(&Tr<W1>) -> W2,
+{
+    fn tr_map(&mut self, tr: &Tr<W1>) -> Result<Tr<ExpectationWeight<W1, W2>>> {
+        let value = (self.feature)(tr).scalar_times(&tr.weight)?;
+        Ok(Tr::new(
+            tr.ilabel,
+            tr.olabel,
+            ExpectationWeight::new((tr.weight.clone(), value)),
+            tr.nextstate,
+        ))
+    }
+
+    fn final_tr_map(
+        &mut self,
+        final_tr: &FinalTr<W1>,
+    ) -> Result<FinalTr<ExpectationWeight<W1, W2>>> {
+        Ok(FinalTr {
+            ilabel: final_tr.ilabel,
+            olabel: final_tr.olabel,
+            weight: ExpectationWeight::new((final_tr.weight.clone(), W2::zero())),
+        })
+    }
+
+    fn final_action(&self) -> MapFinalAction {
+        MapFinalAction::MapNoSuperfinal
+    }
+
+    fn properties(&self, inprops: FstProperties) -> FstProperties {
+        inprops & FstProperties::weight_invariant_properties()
+    }
+}
@@ -1,7 +1,11 @@
+mod boolean_weight_converter;
+mod expectation_converter;
 mod from_gallic_mapper;
 mod simple_weight_converter;
 mod to_gallic_converter;
 
+pub use self::boolean_weight_converter::BooleanWeightConverter;
+pub use self::expectation_converter::ExpectationConverter;
 pub use self::from_gallic_mapper::FromGallicConverter;
 pub use self::simple_weight_converter::SimpleWeightConverter;
 pub use self::to_gallic_converter::ToGallicConverter;
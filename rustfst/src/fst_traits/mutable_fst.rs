@@ -419,6 +419,14 @@ pub trait MutableFst<W: Semiring>: ExpandedFst<W> {
         crate::algorithms::closure::closure(self, closure_type)
     }
 
+    /// This operation computes the bounded concatenative closure.
+    /// If A transduces string `x` to `y` with weight `a`, then the closure transduces
+    /// `x^n` to `y^n` with weight `a^n` for every `n` between `lower` and `upper`
+    /// (without upper bound if `upper` is `None`).
+    fn closure_range(&mut self, lower: usize, upper: Option<usize>) -> Result<()> {
+        crate::algorithms::closure::closure_range(self, lower, upper)
+    }
+
     /// Maps a transition using a `TrMapper` object.
     fn tr_map<M: TrMapper<W>>(&mut self, mapper: &mut M) -> Result<()> {
         crate::algorithms::tr_map(self, mapper)
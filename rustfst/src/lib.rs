@@ -246,6 +246,10 @@ pub mod algorithms;
 /// Provides the `FstProperties` struct and some utils functions around it.
 /// Useful to assert some properties on a Fst.
 pub mod fst_properties;
+
+/// Provides `compile_regex` to build wFSTs from weighted regular expressions.
+pub mod regex;
+
 /// Implementation of the transitions inside a Fst.
 mod tr;
 
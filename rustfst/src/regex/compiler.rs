@@ -0,0 +1,115 @@
+use anyhow::Result;
+
+use crate::algorithms::closure::{closure, ClosureType};
+use crate::algorithms::concat::concat;
+use crate::algorithms::tr_mappers::{InputEpsilonMapper, OutputEpsilonMapper};
+use crate::algorithms::union::union;
+use crate::fst_impls::VectorFst;
+use crate::fst_traits::{CoreFst, Fst, MutableFst};
+use crate::regex::parser::RegexNode;
+use crate::semirings::Semiring;
+use crate::utils::acceptor;
+use crate::{Label, Tr};
+
+fn epsilon<W: Semiring>() -> VectorFst<W> {
+    acceptor(&[], W::one())
+}
+
+fn optional<W: Semiring>(mut fst: VectorFst<W>) -> Result<VectorFst<W>> {
+    union(&mut fst, &epsilon())?;
+    Ok(fst)
+}
+
+/// Builds an FST with two states accepting each of the sequences of labels.
+fn compile_class<W: Semiring>(members: &[Vec<Label>]) -> Result<VectorFst<W>> {
+    let mut fst = VectorFst::new();
+    let start = fst.add_state();
+    let end = fst.add_state();
+    fst.set_start(start)?;
+    fst.set_final(end, W::one())?;
+    for member in members {
+        let mut state = start;
+        for (idx, label) in member.iter().enumerate() {
+            let nextstate = if idx + 1 == member.len() {
+                end
+            } else {
+                fst.add_state()
+            };
+            fst.add_tr(state, Tr::new(*label, *label, W::one(), nextstate))?;
+            state = nextstate;
+        }
+    }
+    Ok(fst)
+}
+
+/// Compiles the tree of a regular expression into an FST with the Thompson construction.
+pub(crate) fn compile_regex_node<W: Semiring>(node: &RegexNode<W>) -> Result<VectorFst<W>> {
+    match node {
+        RegexNode::Labels(labels) => Ok(acceptor(labels, W::one())),
+        RegexNode::Class(members) => compile_class(members),
+        RegexNode::Concat(items) => {
+            let mut fst = epsilon();
+            for item in items {
+                concat(&mut fst, &compile_regex_node(item)?)?;
+            }
+            Ok(fst)
+        }
+        RegexNode::Union(alternatives) => {
+            let mut fst = compile_regex_node(&alternatives[0])?;
+            for alternative in &alternatives[1..] {
+                union(&mut fst, &compile_regex_node(alternative)?)?;
+            }
+            Ok(fst)
+        }
+        RegexNode::Closure(node, closure_type) => {
+            let mut fst = compile_regex_node(node)?;
+            closure(&mut fst, *closure_type);
+            Ok(fst)
+        }
+        RegexNode::Optional(node) => optional(compile_regex_node(node)?),
+        RegexNode::Repeat(node, min, max) => {
+            let item = compile_regex_node(node)?;
+            let mut fst = epsilon();
+            for _ in 0..*min {
+                concat(&mut fst, &item)?;
+            }
+            let tail = match max {
+                // Nested rather than consecutive optional items to keep the FST unambiguous.
+                Some(max) => {
+                    let mut tail = epsilon();
+                    for _ in *min..*max {
+                        let mut item = item.clone();
+                        concat(&mut item, &tail)?;
+                        tail = optional(item)?;
+                    }
+                    tail
+                }
+                None => {
+                    let mut tail = item;
+                    closure(&mut tail, ClosureType::ClosureStar);
+                    tail
+                }
+            };
+            concat(&mut fst, &tail)?;
+            Ok(fst)
+        }
+        RegexNode::Cross(input, output) => {
+            let mut fst = compile_regex_node(input)?;
+            fst.tr_map(&mut OutputEpsilonMapper {})?;
+            let mut output = compile_regex_node(output)?;
+            output.tr_map(&mut InputEpsilonMapper {})?;
+            concat(&mut fst, &output)?;
+            Ok(fst)
+        }
+        RegexNode::Weight(node, weight) => {
+            let mut fst = compile_regex_node(node)?;
+            let final_states: Vec<_> = fst.final_states_iter().collect();
+            for s in final_states {
+                if let Some(final_weight) = fst.final_weight(s)? {
+                    fst.set_final(s, final_weight.times(weight)?)?;
+                }
+            }
+            Ok(fst)
+        }
+    }
+}
@@ -0,0 +1,172 @@
+//! Compiles weighted regular expressions into FSTs.
+//!
+//! The supported syntax is, from the tightest to the loosest binding :
+//!
+//! - `a`, `\*` : a token. With `TokenType::Symbols`, a token is a symbol of the `SymbolTable`
+//!   and whitespaces separate the tokens. Otherwise, it is a single character (possibly
+//!   made of several bytes with `TokenType::Byte`) and whitespaces are regular characters.
+//!   Special characters are escaped with `\`.
+//! - `(r)` : a group.
+//! - `[abc]`, `[a-z]`, `[^abc]`, `.` : a class of tokens. Ranges are only supported on characters.
+//!   Negated classes and `.` match the whole alphabet, which is either all the non-zero bytes
+//!   or all the symbols of the `SymbolTable` (they are not supported with `TokenType::Utf8`).
+//! - `r*`, `r+`, `r?`, `r{m}`, `r{m,}`, `r{m,n}` : repetitions.
+//! - `r<w>` : multiplies the weight of `r` by `w`, written in the text format of the semiring.
+//! - `r:s` : the cross product, mapping the strings of `r` to the strings of `s`.
+//! - `rs` : the concatenation.
+//! - `r|s` : the union. An empty alternative matches the empty string.
+//!
+//! # Example
+//!
+//! ```
+//! # use anyhow::Result;
+//! # use rustfst::fst_impls::VectorFst;
+//! # use rustfst::algorithms::rewrite::matches;
+//! # use rustfst::regex::compile_regex;
+//! # use rustfst::semirings::TropicalWeight;
+//! # use rustfst::utils::TokenType;
+//! # use rustfst::Label;
+//! # fn main() -> Result<()> {
+//! let fst : VectorFst<TropicalWeight> = compile_regex("(ab|c)*d<1.5>", TokenType::Byte)?;
+//!
+//! let abcd : Vec<Label> = "abcd".bytes().map(|b| b as Label).collect();
+//! assert!(matches(&abcd, &abcd, &fst)?);
+//! let abc : Vec<Label> = "abc".bytes().map(|b| b as Label).collect();
+//! assert!(!matches(&abc, &abc, &fst)?);
+//! # Ok(())
+//! # }
+//! ```
+
+use anyhow::Result;
+
+use crate::fst_impls::VectorFst;
+use crate::semirings::SerializableSemiring;
+use crate::utils::TokenType;
+
+use self::compiler::compile_regex_node;
+use self::parser::RegexParser;
+
+mod compiler;
+mod parser;
+
+/// Compiles a regular expression into an FST, splitting the tokens according to `token_type`.
+/// See the module documentation for the supported syntax.
+pub fn compile_regex<W: SerializableSemiring>(
+    regex: &str,
+    token_type: TokenType,
+) -> Result<VectorFst<W>> {
+    let node = RegexParser::parse::<W>(regex, token_type)?;
+    compile_regex_node(&node)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::algorithms::compose::compose;
+    use crate::algorithms::rewrite::{matches, rewrites};
+    use crate::algorithms::shortest_path;
+    use crate::fst_traits::Fst;
+    use crate::semirings::{Semiring, TropicalWeight};
+    use crate::symt;
+    use crate::utils::{acceptor, decode_linear_fst, print_string};
+    use crate::{FstPath, Label, SymbolTable};
+
+    fn accepts(fst: &VectorFst<TropicalWeight>, text: &str) -> Result<bool> {
+        let labels: Vec<Label> = text.bytes().map(|b| b as Label).collect();
+        matches(&labels, &labels, fst)
+    }
+
+    fn best_path(
+        fst: &VectorFst<TropicalWeight>,
+        input: &[Label],
+    ) -> Result<FstPath<TropicalWeight>> {
+        let ifst: VectorFst<TropicalWeight> = acceptor(input, TropicalWeight::one());
+        let lattice: VectorFst<TropicalWeight> =
+            compose::<_, VectorFst<_>, VectorFst<_>, _, _, _>(&ifst, fst)?;
+        let best: VectorFst<TropicalWeight> = shortest_path(&lattice)?;
+        decode_linear_fst(&best)
+    }
+
+    #[test]
+    fn test_regex_operators() -> Result<()> {
+        let fst = compile_regex("(ab|c)*d", TokenType::Byte)?;
+        for text in &["d", "abd", "cabccd"] {
+            assert!(accepts(&fst, text)?, "{}", text);
+        }
+        for text in &["", "ab", "ad", "dd"] {
+            assert!(!accepts(&fst, text)?, "{}", text);
+        }
+
+        let fst = compile_regex("[a-c]{2,3}x?[^a-y]\\.", TokenType::Byte)?;
+        for text in &["abz.", "cccx~.", "aaz."] {
+            assert!(accepts(&fst, text)?, "{}", text);
+        }
+        for text in &["az.", "aaaaz.", "abb.", "abz"] {
+            assert!(!accepts(&fst, text)?, "{}", text);
+        }
+
+        let fst = compile_regex("a{2,}|(b|)", TokenType::Byte)?;
+        for text in &["aa", "aaaa", "b", ""] {
+            assert!(accepts(&fst, text)?, "{}", text);
+        }
+        assert!(!accepts(&fst, "a")?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_regex_weights_and_cross() -> Result<()> {
+        let fst: VectorFst<TropicalWeight> =
+            compile_regex("(a:(xy)<1>|a:z<0.5>) b<2>", TokenType::Utf8)?;
+        let mut res = rewrites(&vec![97, 32, 98], &fst)?;
+        res.sort();
+        assert_eq!(res, vec![vec![120, 121, 32, 98], vec![122, 32, 98]]);
+
+        let path = best_path(&fst, &[97, 32, 98])?;
+        assert_eq!(print_string(&path, TokenType::Utf8)?, "z b");
+        assert_eq!(path.weight, TropicalWeight::new(2.5));
+        Ok(())
+    }
+
+    #[test]
+    fn test_regex_symbols() -> Result<()> {
+        let symt = symt!["hello", "big", "world", "*"];
+        let fst: VectorFst<TropicalWeight> =
+            compile_regex("hello [big \\*]* world<3>", TokenType::Symbols(&symt))?;
+        assert!(matches(&vec![1, 2, 4, 3], &vec![1, 2, 4, 3], &fst)?);
+        assert!(!matches(&vec![1, 1, 3], &vec![1, 1, 3], &fst)?);
+        assert_eq!(best_path(&fst, &[1, 3])?.weight, TropicalWeight::new(3.0));
+
+        let fst: VectorFst<TropicalWeight> =
+            compile_regex("[^hello world]", TokenType::Symbols(&symt))?;
+        let mut labels: Vec<_> = fst.paths_iter().map(|p| p.ilabels).collect();
+        labels.sort();
+        assert_eq!(labels, vec![vec![2], vec![4]]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_regex_errors() {
+        let symt = symt!["a"];
+        for (regex, token_type) in &[
+            ("(ab", TokenType::Byte),
+            ("ab)", TokenType::Byte),
+            ("a{3,2}", TokenType::Byte),
+            ("a{x}", TokenType::Byte),
+            ("a<abc>", TokenType::Byte),
+            ("*a", TokenType::Byte),
+            ("[]", TokenType::Byte),
+            ("[z-a]", TokenType::Byte),
+            ("a:b:c", TokenType::Byte),
+            ("a\\", TokenType::Byte),
+            (".", TokenType::Utf8),
+            ("a b", TokenType::Symbols(&symt)),
+        ] {
+            assert!(
+                compile_regex::<TropicalWeight>(regex, *token_type).is_err(),
+                "{}",
+                regex
+            );
+        }
+    }
+}
@@ -0,0 +1,354 @@
+use anyhow::Result;
+
+use crate::algorithms::closure::ClosureType;
+use crate::semirings::SerializableSemiring;
+use crate::utils::{string_to_labels, TokenType};
+use crate::{Label, SymbolTable, EPS_LABEL};
+
+/// Abstract syntax tree of a regular expression.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum RegexNode<W> {
+    /// Sequence of labels, empty for epsilon.
+    Labels(Vec<Label>),
+    /// Any of the sequences of labels.
+    Class(Vec<Vec<Label>>),
+    Concat(Vec<RegexNode<W>>),
+    Union(Vec<RegexNode<W>>),
+    Closure(Box<RegexNode<W>>, ClosureType),
+    Optional(Box<RegexNode<W>>),
+    /// Between `min` and `max` repetitions, unbounded if `max` is `None`.
+    Repeat(Box<RegexNode<W>>, usize, Option<usize>),
+    /// Maps the strings of the first node to the strings of the second one.
+    Cross(Box<RegexNode<W>>, Box<RegexNode<W>>),
+    Weight(Box<RegexNode<W>>, W),
+}
+
+const SPECIAL_CHARS: &str = "()[]{}|*+?:<>.\\";
+
+fn is_special(c: char) -> bool {
+    SPECIAL_CHARS.contains(c)
+}
+
+/// Recursive descent parser. From the loosest to the tightest binding, the operators
+/// are the union `|`, the concatenation, the cross product `:` and the postfix operators
+/// `*`, `+`, `?`, `{m,n}` and `<w>`.
+pub(crate) struct RegexParser<'a> {
+    chars: Vec<char>,
+    pos: usize,
+    token_type: TokenType<'a>,
+}
+
+impl<'a> RegexParser<'a> {
+    pub(crate) fn parse<W: SerializableSemiring>(
+        regex: &str,
+        token_type: TokenType<'a>,
+    ) -> Result<RegexNode<W>> {
+        let mut parser = RegexParser {
+            chars: regex.chars().collect(),
+            pos: 0,
+            token_type,
+        };
+        let node = parser.parse_union()?;
+        if let Some(c) = parser.peek() {
+            bail!("Regex : unexpected {:?} at position {}", c, parser.pos)
+        }
+        Ok(node)
+    }
+
+    fn is_symbols(&self) -> bool {
+        matches!(self.token_type, TokenType::Symbols(_))
+    }
+
+    /// Whitespaces only separate the symbols with `TokenType::Symbols`. Otherwise they
+    /// are regular characters.
+    fn skip_whitespaces(&mut self) {
+        if self.is_symbols() {
+            while matches!(self.peek_raw(), Some(c) if c.is_whitespace()) {
+                self.pos += 1;
+            }
+        }
+    }
+
+    fn peek_raw(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn next_raw(&mut self) -> Option<char> {
+        let c = self.peek_raw();
+        if c.is_some() {
+            self.pos += 1;
+        }
+        c
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_whitespaces();
+        self.peek_raw()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<()> {
+        if !self.eat(c) {
+            bail!("Regex : expected {:?} at position {}", c, self.pos)
+        }
+        Ok(())
+    }
+
+    /// Reads a character, resolving the `\` escapes.
+    fn next_char(&mut self) -> Result<char> {
+        match self.next_raw() {
+            Some('\\') => self
+                .next_raw()
+                .ok_or_else(|| format_err!("Regex : dangling escape at the end")),
+            Some(c) => Ok(c),
+            None => bail!("Regex : unexpected end of expression"),
+        }
+    }
+
+    fn char_labels(&self, c: char) -> Result<Vec<Label>> {
+        string_to_labels(&c.to_string(), self.token_type)
+    }
+
+    /// Reads a symbol up to the next whitespace or special character.
+    fn next_symbol(&mut self, symt: &SymbolTable) -> Result<Label> {
+        let mut symbol = String::new();
+        while let Some(c) = self.peek_raw() {
+            if c.is_whitespace() || (is_special(c) && c != '\\') {
+                break;
+            }
+            symbol.push(self.next_char()?);
+        }
+        if symbol.is_empty() {
+            bail!("Regex : expected a symbol at position {}", self.pos)
+        }
+        symt.get_label(&symbol)
+            .ok_or_else(|| format_err!("Regex : symbol {:?} not found", symbol))
+    }
+
+    /// All the labels that `.` and the negated classes may match.
+    fn alphabet(&self) -> Result<Vec<Label>> {
+        match self.token_type {
+            TokenType::Byte => Ok((1..=u8::MAX as Label).collect()),
+            TokenType::Utf8 => {
+                bail!("Regex : '.' and negated classes are not supported with Utf8 tokens")
+            }
+            TokenType::Symbols(symt) => Ok(symt.labels().filter(|l| *l != EPS_LABEL).collect()),
+        }
+    }
+
+    fn parse_union<W: SerializableSemiring>(&mut self) -> Result<RegexNode<W>> {
+        let mut alternatives = vec![self.parse_concat()?];
+        while self.eat('|') {
+            alternatives.push(self.parse_concat()?);
+        }
+        if alternatives.len() == 1 {
+            Ok(alternatives.remove(0))
+        } else {
+            Ok(RegexNode::Union(alternatives))
+        }
+    }
+
+    fn parse_concat<W: SerializableSemiring>(&mut self) -> Result<RegexNode<W>> {
+        let mut items = vec![];
+        while let Some(c) = self.peek() {
+            if c == '|' || c == ')' {
+                break;
+            }
+            items.push(self.parse_cross()?);
+        }
+        match items.len() {
+            0 => Ok(RegexNode::Labels(vec![])),
+            1 => Ok(items.remove(0)),
+            _ => Ok(RegexNode::Concat(items)),
+        }
+    }
+
+    fn parse_cross<W: SerializableSemiring>(&mut self) -> Result<RegexNode<W>> {
+        let left = self.parse_postfix()?;
+        if self.eat(':') {
+            let right = self.parse_postfix()?;
+            Ok(RegexNode::Cross(Box::new(left), Box::new(right)))
+        } else {
+            Ok(left)
+        }
+    }
+
+    fn parse_postfix<W: SerializableSemiring>(&mut self) -> Result<RegexNode<W>> {
+        let mut node = self.parse_atom()?;
+        loop {
+            let c = match self.peek() {
+                Some(c) if "*+?{<".contains(c) => c,
+                _ => return Ok(node),
+            };
+            self.pos += 1;
+            node = match c {
+                '*' => RegexNode::Closure(Box::new(node), ClosureType::ClosureStar),
+                '+' => RegexNode::Closure(Box::new(node), ClosureType::ClosurePlus),
+                '?' => RegexNode::Optional(Box::new(node)),
+                '{' => {
+                    let (min, max) = self.parse_bounds()?;
+                    RegexNode::Repeat(Box::new(node), min, max)
+                }
+                _ => RegexNode::Weight(Box::new(node), self.parse_weight()?),
+            };
+        }
+    }
+
+    /// Parses the content of `{m}`, `{m,}` or `{m,n}`.
+    fn parse_bounds(&mut self) -> Result<(usize, Option<usize>)> {
+        let start = self.pos;
+        let mut content = String::new();
+        loop {
+            match self.next_raw() {
+                Some('}') => break,
+                Some(c) => content.push(c),
+                None => bail!("Regex : unterminated repetition at position {}", start),
+            }
+        }
+        let parse_bound = |s: &str| {
+            s.trim()
+                .parse::<usize>()
+                .map_err(|_| format_err!("Regex : invalid repetition {{{}}}", content))
+        };
+        let (min, max) = match content.split_once(',') {
+            None => {
+                let n = parse_bound(&content)?;
+                (n, Some(n))
+            }
+            Some((min, max)) if max.trim().is_empty() => (parse_bound(min)?, None),
+            Some((min, max)) => (parse_bound(min)?, Some(parse_bound(max)?)),
+        };
+        if let Some(max) = max {
+            if max < min {
+                bail!("Regex : invalid repetition {{{}}}", content)
+            }
+        }
+        Ok((min, max))
+    }
+
+    /// Parses the content of `<w>` with the text format of the weights.
+    fn parse_weight<W: SerializableSemiring>(&mut self) -> Result<W> {
+        let start = self.pos;
+        let mut content = String::new();
+        loop {
+            match self.next_raw() {
+                Some('>') => break,
+                Some(c) => content.push(c),
+                None => bail!("Regex : unterminated weight at position {}", start),
+            }
+        }
+        match W::parse_text(content.trim()) {
+            Ok(("", weight)) => Ok(weight),
+            _ => bail!("Regex : invalid weight <{}>", content),
+        }
+    }
+
+    fn parse_atom<W: SerializableSemiring>(&mut self) -> Result<RegexNode<W>> {
+        let c = match self.peek() {
+            Some(c) => c,
+            None => bail!("Regex : unexpected end of expression"),
+        };
+        match c {
+            '(' => {
+                self.pos += 1;
+                let node = self.parse_union()?;
+                self.expect(')')?;
+                Ok(node)
+            }
+            '[' => {
+                self.pos += 1;
+                self.parse_class()
+            }
+            '.' => {
+                self.pos += 1;
+                Ok(RegexNode::Class(
+                    self.alphabet()?.into_iter().map(|l| vec![l]).collect(),
+                ))
+            }
+            '\\' => self.parse_literal(),
+            c if is_special(c) => bail!("Regex : unexpected {:?} at position {}", c, self.pos),
+            _ => self.parse_literal(),
+        }
+    }
+
+    fn parse_literal<W>(&mut self) -> Result<RegexNode<W>> {
+        if let TokenType::Symbols(symt) = self.token_type {
+            Ok(RegexNode::Labels(vec![self.next_symbol(symt)?]))
+        } else {
+            let c = self.next_char()?;
+            Ok(RegexNode::Labels(self.char_labels(c)?))
+        }
+    }
+
+    /// Parses a class such as `[a-z0]` or `[^ab]` once the `[` has been consumed.
+    /// With `TokenType::Symbols`, the class is a list of symbols and ranges are not supported.
+    fn parse_class<W>(&mut self) -> Result<RegexNode<W>> {
+        let start = self.pos;
+        let negated = self.peek_raw() == Some('^');
+        if negated {
+            self.pos += 1;
+        }
+        let mut members: Vec<Vec<Label>> = vec![];
+        loop {
+            if let TokenType::Symbols(symt) = self.token_type {
+                if self.eat(']') {
+                    break;
+                }
+                if self.peek().is_none() {
+                    bail!("Regex : unterminated class at position {}", start)
+                }
+                members.push(vec![self.next_symbol(symt)?]);
+                continue;
+            }
+            match self.peek_raw() {
+                Some(']') => {
+                    self.pos += 1;
+                    break;
+                }
+                None => bail!("Regex : unterminated class at position {}", start),
+                _ => {}
+            }
+            let lower = self.next_char()?;
+            let is_range = self.peek_raw() == Some('-')
+                && !matches!(self.chars.get(self.pos + 1), None | Some(']'));
+            if is_range {
+                self.pos += 1;
+                let upper = self.next_char()?;
+                let (lower, upper) = match (
+                    self.char_labels(lower)?.as_slice(),
+                    self.char_labels(upper)?.as_slice(),
+                ) {
+                    ([lower], [upper]) if lower <= upper => (*lower, *upper),
+                    _ => bail!("Regex : invalid range {:?}-{:?}", lower, upper),
+                };
+                members.extend((lower..=upper).map(|l| vec![l]));
+            } else {
+                members.push(self.char_labels(lower)?);
+            }
+        }
+        if members.is_empty() {
+            bail!("Regex : empty class at position {}", start)
+        }
+        if negated {
+            if members.iter().any(|m| m.len() != 1) {
+                bail!("Regex : negated classes only support single-label members")
+            }
+            let excluded: Vec<Label> = members.into_iter().map(|m| m[0]).collect();
+            members = self
+                .alphabet()?
+                .into_iter()
+                .filter(|l| !excluded.contains(l))
+                .map(|l| vec![l])
+                .collect();
+        }
+        Ok(RegexNode::Class(members))
+    }
+}
@@ -1,7 +1,16 @@
 use anyhow::Result;
-
-use crate::semirings::{CompleteSemiring, ReverseBack, Semiring, SemiringProperties, StarSemiring};
+use nom::branch::alt;
+use nom::bytes::complete::tag;
+use nom::combinator::map;
+use nom::IResult;
+
+use crate::parsers::nom_utils::NomCustomError;
+use crate::parsers::{parse_bin_u8, write_bin_u8};
+use crate::semirings::{
+    CompleteSemiring, ReverseBack, Semiring, SemiringProperties, SerializableSemiring, StarSemiring,
+};
 use std::borrow::Borrow;
+use std::io::Write;
 /// Boolean semiring: (&, |, false, true).
 #[derive(Clone, Debug, PartialEq, PartialOrd, Default, Eq, Copy, Hash)]
 pub struct BooleanWeight {
@@ -77,6 +86,35 @@ impl StarSemiring for BooleanWeight {
     }
 }
 
+impl SerializableSemiring for BooleanWeight {
+    fn weight_type() -> String {
+        "boolean".to_string()
+    }
+
+    fn parse_binary(i: &[u8]) -> IResult<&[u8], Self, NomCustomError<&[u8]>> {
+        let (i, value) = parse_bin_u8(i)?;
+        Ok((i, Self::new(value != 0)))
+    }
+
+    fn write_binary<F: Write>(&self, file: &mut F) -> Result<()> {
+        write_bin_u8(file, self.value as u8)
+    }
+
+    fn parse_text(i: &str) -> IResult<&str, Self> {
+        let (i, value) = alt((
+            map(alt((tag("true"), tag("1"))), |_| true),
+            map(alt((tag("false"), tag("0"))), |_| false),
+        ))(i)?;
+        Ok((i, Self::new(value)))
+    }
+}
+
+test_semiring_serializable!(
+    tests_boolean_weight_serializable,
+    BooleanWeight,
+    BooleanWeight::one() BooleanWeight::zero()
+);
+
 impl From<bool> for BooleanWeight {
     fn from(b: bool) -> Self {
         Self::new(b)
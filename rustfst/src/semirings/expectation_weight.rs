@@ -0,0 +1,297 @@
+use std::borrow::Borrow;
+use std::fmt;
+use std::fmt::Debug;
+use std::io::Write;
+
+use anyhow::Result;
+use nom::IResult;
+
+use crate::parsers::nom_utils::NomCustomError;
+use crate::semirings::{
+    LogWeight, ProbabilityWeight, ReverseBack, Semiring, SemiringProperties, SerializableSemiring,
+    StarSemiring, TropicalWeight,
+};
+
+/// Weights forming a semimodule over the semiring `W` : they can be multiplied by
+/// the weights of `W`. Every semiring is a semimodule over itself.
+pub trait SemimoduleWeight<W: Semiring>: Semiring {
+    /// Semimodule formed by the reversed weights over the reversed semiring.
+    type ReverseModule: SemimoduleWeight<W::ReverseWeight> + ReverseBack<Self>;
+
+    fn scalar_times(&self, scalar: &W) -> Result<Self>;
+    fn reverse_module(&self) -> Result<Self::ReverseModule>;
+}
+
+impl<W: Semiring> SemimoduleWeight<W> for W {
+    type ReverseModule = W::ReverseWeight;
+
+    fn scalar_times(&self, scalar: &W) -> Result<Self> {
+        self.times(scalar)
+    }
+
+    fn reverse_module(&self) -> Result<Self::ReverseModule> {
+        self.reverse()
+    }
+}
+
+/// Expectation semiring: pairs `<p, v>` of a weight and of an element of a semimodule
+/// over it, with `<p1, v1> + <p2, v2> = <p1 + p2, v1 + v2>` and
+/// `<p1, v1> * <p2, v2> = <p1 * p2, p1 * v2 + v1 * p2>`.
+///
+/// Mapping the weight `p` of each transition to `<p, p * f>`, the total weight of an FST
+/// is `<Z, E>` where `Z` is the sum of the weights of the paths and `E` the sum of these
+/// weights times the sum of the features `f` along the paths : `E / Z` is the expected
+/// value of the features, computed by [`expected_values`](crate::algorithms::expected_values).
+#[derive(Debug, Eq, PartialOrd, PartialEq, Clone, Default, Hash)]
+pub struct ExpectationWeight<W1, W2>
+where
+    W1: Semiring,
+    W2: SemimoduleWeight<W1>,
+{
+    pub(crate) weight: (W1, W2),
+}
+
+impl<W1, W2> AsRef<Self> for ExpectationWeight<W1, W2>
+where
+    W1: Semiring,
+    W2: SemimoduleWeight<W1>,
+{
+    fn as_ref(&self) -> &ExpectationWeight<W1, W2> {
+        self
+    }
+}
+
+impl<W1, W2> Semiring for ExpectationWeight<W1, W2>
+where
+    W1: Semiring,
+    W2: SemimoduleWeight<W1>,
+{
+    type Type = (W1, W2);
+    type ReverseWeight = ExpectationWeight<W1::ReverseWeight, W2::ReverseModule>;
+
+    fn zero() -> Self {
+        Self {
+            weight: (W1::zero(), W2::zero()),
+        }
+    }
+
+    fn one() -> Self {
+        Self {
+            weight: (W1::one(), W2::zero()),
+        }
+    }
+
+    fn new(weight: <Self as Semiring>::Type) -> Self {
+        Self { weight }
+    }
+
+    fn plus_assign<P: Borrow<Self>>(&mut self, rhs: P) -> Result<()> {
+        self.weight.0.plus_assign(&rhs.borrow().weight.0)?;
+        self.weight.1.plus_assign(&rhs.borrow().weight.1)?;
+        Ok(())
+    }
+
+    fn times_assign<P: Borrow<Self>>(&mut self, rhs: P) -> Result<()> {
+        let (p2, v2) = &rhs.borrow().weight;
+        let mut v = v2.scalar_times(self.value1())?;
+        v.plus_assign(self.value2().scalar_times(p2)?)?;
+        self.weight.0.times_assign(p2)?;
+        self.weight.1 = v;
+        Ok(())
+    }
+
+    fn approx_equal<P: Borrow<Self>>(&self, rhs: P, delta: f32) -> bool {
+        self.value1().approx_equal(rhs.borrow().value1(), delta)
+            && self.value2().approx_equal(rhs.borrow().value2(), delta)
+    }
+
+    fn value(&self) -> &<Self as Semiring>::Type {
+        &self.weight
+    }
+
+    fn take_value(self) -> <Self as Semiring>::Type {
+        self.weight
+    }
+
+    fn set_value(&mut self, value: <Self as Semiring>::Type) {
+        self.set_value1(value.0);
+        self.set_value2(value.1);
+    }
+
+    fn reverse(&self) -> Result<Self::ReverseWeight> {
+        Ok(ExpectationWeight::new((
+            self.value1().reverse()?,
+            self.value2().reverse_module()?,
+        )))
+    }
+
+    fn properties() -> SemiringProperties {
+        W1::properties()
+            & W2::properties()
+            & (SemiringProperties::LEFT_SEMIRING
+                | SemiringProperties::RIGHT_SEMIRING
+                | SemiringProperties::COMMUTATIVE
+                | SemiringProperties::IDEMPOTENT)
+    }
+}
+
+impl<W1, W2> ReverseBack<ExpectationWeight<W1, W2>>
+    for <ExpectationWeight<W1, W2> as Semiring>::ReverseWeight
+where
+    W1: Semiring,
+    W2: SemimoduleWeight<W1>,
+{
+    fn reverse_back(&self) -> Result<ExpectationWeight<W1, W2>> {
+        Ok(ExpectationWeight::new((
+            self.value1().reverse_back()?,
+            self.value2().reverse_back()?,
+        )))
+    }
+}
+
+impl<W1, W2> ExpectationWeight<W1, W2>
+where
+    W1: Semiring,
+    W2: SemimoduleWeight<W1>,
+{
+    pub fn value1(&self) -> &W1 {
+        &self.weight.0
+    }
+
+    pub fn value2(&self) -> &W2 {
+        &self.weight.1
+    }
+
+    pub fn set_value1(&mut self, new_weight: W1) {
+        self.weight.0 = new_weight;
+    }
+
+    pub fn set_value2(&mut self, new_weight: W2) {
+        self.weight.1 = new_weight;
+    }
+}
+
+impl<W1, W2> From<(W1, W2)> for ExpectationWeight<W1, W2>
+where
+    W1: Semiring,
+    W2: SemimoduleWeight<W1>,
+{
+    fn from(t: (W1, W2)) -> Self {
+        Self::new(t)
+    }
+}
+
+/// `<p, v>* = <p*, p* * v * p*>`. `StarSemiring::closure` can't fail so the closure is
+/// only implemented for the float weights over themselves, for which the product is
+/// computed directly on the values.
+macro_rules! impl_expectation_star_semiring {
+    ($semiring: ty, $times: expr) => {
+        impl StarSemiring for ExpectationWeight<$semiring, $semiring> {
+            fn closure(&self) -> Self {
+                let times: fn(f32, f32) -> f32 = $times;
+                let p_star = self.value1().closure();
+                let v = times(
+                    times(*p_star.value(), *self.value2().value()),
+                    *p_star.value(),
+                );
+                Self::new((p_star, <$semiring>::new(v)))
+            }
+        }
+    };
+}
+
+fn log_times(f1: f32, f2: f32) -> f32 {
+    if f1 == f32::INFINITY || f2 == f32::INFINITY {
+        f32::INFINITY
+    } else {
+        f1 + f2
+    }
+}
+
+impl_expectation_star_semiring!(ProbabilityWeight, |f1, f2| f1 * f2);
+impl_expectation_star_semiring!(LogWeight, log_times);
+impl_expectation_star_semiring!(TropicalWeight, log_times);
+
+impl<W1, W2> fmt::Display for ExpectationWeight<W1, W2>
+where
+    W1: SerializableSemiring,
+    W2: SerializableSemiring + SemimoduleWeight<W1>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{},{}", self.value1(), self.value2())?;
+        Ok(())
+    }
+}
+
+impl<W1, W2> SerializableSemiring for ExpectationWeight<W1, W2>
+where
+    W1: SerializableSemiring,
+    W2: SerializableSemiring + SemimoduleWeight<W1>,
+{
+    fn weight_type() -> String {
+        format!("expectation_{}_{}", W1::weight_type(), W2::weight_type())
+    }
+
+    fn parse_binary(i: &[u8]) -> IResult<&[u8], Self, NomCustomError<&[u8]>> {
+        let (i, weight_1) = W1::parse_binary(i)?;
+        let (i, weight_2) = W2::parse_binary(i)?;
+        Ok((i, Self::new((weight_1, weight_2))))
+    }
+
+    fn write_binary<F: Write>(&self, file: &mut F) -> Result<()> {
+        self.value1().write_binary(file)?;
+        self.value2().write_binary(file)?;
+        Ok(())
+    }
+
+    fn parse_text(i: &str) -> IResult<&str, Self> {
+        let (i, weight_1) = W1::parse_text(i)?;
+        let (i, _) = nom::bytes::complete::tag(",")(i)?;
+        let (i, weight_2) = W2::parse_text(i)?;
+        Ok((i, Self::new((weight_1, weight_2))))
+    }
+}
+
+test_semiring_serializable!(
+    tests_expectation_weight_serializable,
+    ExpectationWeight::<LogWeight, LogWeight>,
+    ExpectationWeight::new((LogWeight::new(0.2), LogWeight::new(1.7)))
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type ProbExpectation = ExpectationWeight<ProbabilityWeight, ProbabilityWeight>;
+
+    fn exp(p: f32, v: f32) -> ProbExpectation {
+        ExpectationWeight::new((ProbabilityWeight::new(p), ProbabilityWeight::new(v)))
+    }
+
+    #[test]
+    fn test_expectation_weight() -> Result<()> {
+        assert_eq!(exp(0.5, 1.0).plus(exp(0.25, 2.0))?, exp(0.75, 3.0));
+        // <p1 * p2, p1 * v2 + v1 * p2>
+        assert_eq!(exp(0.5, 1.0).times(exp(0.25, 2.0))?, exp(0.125, 1.25));
+        assert_eq!(exp(0.5, 1.0).times(ProbExpectation::one())?, exp(0.5, 1.0));
+        assert_eq!(exp(0.5, 1.0).times(ProbExpectation::zero())?, exp(0.0, 0.0));
+        // <p*, p* * v * p*>
+        assert_eq!(exp(0.5, 1.0).closure(), exp(2.0, 4.0));
+        assert_eq!(
+            ProbExpectation::weight_type(),
+            "expectation_probability_probability"
+        );
+
+        let w: ExpectationWeight<LogWeight, LogWeight> =
+            ExpectationWeight::new((LogWeight::new(1.0), LogWeight::new(2.0)));
+        assert_eq!(w.reverse()?.reverse_back()?, w);
+
+        // The closure agrees with the products of the semiring.
+        let w: ExpectationWeight<LogWeight, LogWeight> =
+            ExpectationWeight::new((LogWeight::new(0.5), LogWeight::new(2.0)));
+        let p_star = w.value1().closure();
+        let v = p_star.times(w.value2())?.times(&p_star)?;
+        assert_eq!(w.closure(), ExpectationWeight::new((p_star, v)));
+        Ok(())
+    }
+}
@@ -5,12 +5,17 @@ mod semiring;
 mod macros;
 
 mod boolean_weight;
+mod expectation_weight;
 mod gallic_weight;
 mod integer_weight;
+mod lexicographic_weight;
 mod log_weight;
+mod minmax_weight;
 mod power_weight;
 mod probability_weight;
 mod product_weight;
+mod signed_log_weight;
+mod sparse_power_weight;
 mod string_variant;
 mod string_weight;
 mod tropical_weight;
@@ -18,17 +23,23 @@ mod union_weight;
 pub(crate) mod utils_float;
 
 pub use self::boolean_weight::BooleanWeight;
+pub use self::expectation_weight::{ExpectationWeight, SemimoduleWeight};
 pub use self::gallic_weight::{
     GallicWeight, GallicWeightLeft, GallicWeightMin, GallicWeightRestrict, GallicWeightRight,
 };
 pub use self::integer_weight::IntegerWeight;
+pub use self::lexicographic_weight::LexicographicWeight;
 pub use self::log_weight::LogWeight;
+pub use self::minmax_weight::MinMaxWeight;
+pub use self::power_weight::PowerWeight;
 pub use self::probability_weight::ProbabilityWeight;
 pub use self::product_weight::ProductWeight;
 pub use self::semiring::{
     CompleteSemiring, DivideType, ReverseBack, Semiring, SemiringProperties, SerializableSemiring,
     StarSemiring, WeaklyDivisibleSemiring, WeightQuantize,
 };
+pub use self::signed_log_weight::SignedLogWeight;
+pub use self::sparse_power_weight::SparsePowerWeight;
 pub(crate) use self::string_variant::StringWeightVariant;
 pub use self::string_weight::{
     StringType, StringWeightLeft, StringWeightRestrict, StringWeightRight,
@@ -1,21 +1,29 @@
-#![allow(unused)]
-
+use std::borrow::Borrow;
 use std::cmp::Ordering;
 use std::fmt;
-use std::fmt::Debug;
 use std::hash::Hash;
 use std::hash::Hasher;
+use std::io::Write;
 
 use anyhow::Result;
-
+use generic_array::sequence::GenericSequence;
 use generic_array::ArrayLength;
 use generic_array::GenericArray;
+use nom::bytes::complete::tag;
+use nom::multi::count;
+use nom::sequence::preceded;
+use nom::IResult;
 
+use crate::parsers::nom_utils::NomCustomError;
+#[cfg(test)]
+use crate::semirings::TropicalWeight;
 use crate::semirings::{
-    DivideType, Semiring, SemiringProperties, WeaklyDivisibleSemiring, WeightQuantize,
+    DivideType, ReverseBack, SemimoduleWeight, Semiring, SemiringProperties, SerializableSemiring,
+    WeaklyDivisibleSemiring, WeightQuantize,
 };
 
-/// Cartesian power semiring: W ^ n.
+/// Cartesian power semiring: W ^ n. All the operations are done component-wise.
+/// The number of components `n` is given by a type-level integer, e.g. `typenum::U3`.
 pub struct PowerWeight<W, N>
 where
     W: Semiring,
@@ -26,11 +34,17 @@ where
 
 impl<W, N> fmt::Display for PowerWeight<W, N>
 where
-    W: Semiring,
+    W: SerializableSemiring,
     N: ArrayLength<W>,
 {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        self.weights.as_slice().fmt(f)
+        for (idx, w) in self.weights.iter().enumerate() {
+            if idx > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{}", w)?;
+        }
+        Ok(())
     }
 }
 
@@ -103,92 +117,237 @@ where
 {
 }
 
-//impl<W, N> Semiring for PowerWeight<W, N>
-//where
-//    W: Semiring,
-//    N: ArrayLength<W>,
-//{
-//    type Type = GenericArray<W, N>;
-//    type ReverseSemiring<P> = PowerWeight<W::ReverseSemiring, P>;
-//
-//    fn zero() -> Self {
-//        Self {
-//            weights: GenericArray::clone_from_slice(&[W::zero()]),
-//        }
-//    }
-//
-//    fn one() -> Self {
-//        Self {
-//            weights: GenericArray::clone_from_slice(&[W::one()]),
-//        }
-//    }
-//
-//    fn new(value: <Self as Semiring>::Type) -> Self {
-//        Self { weights: value }
-//    }
-//
-//    fn plus_assign<P: AsRef<Self>>(&mut self, rhs: P) -> Result<()> {
-//        for i in 0..self.weights.len() {
-//            self.weights[i].plus_assign(&rhs.as_ref().weights[i])?;
-//        }
-//        Ok(())
-//    }
-//
-//    fn times_assign<P: AsRef<Self>>(&mut self, rhs: P) -> Result<()> {
-//        for i in 0..self.weights.len() {
-//            self.weights[i].times_assign(&rhs.as_ref().weights[i])?;
-//        }
-//        Ok(())
-//    }
-//
-//    fn value(&self) -> <Self as Semiring>::Type {
-//        self.weights.clone()
-//    }
-//
-//    fn set_value(&mut self, value: <Self as Semiring>::Type) {
-//        self.weights = value;
-//    }
-//
-//    fn reverse(&self) -> Self::ReverseSemiring {
-//        let mut rw = Vec::with_capacity(self.weights.len());
-//        for i in 0..self.weights.len() {
-//            rw.push(self.weights[i].reverse());
-//        }
-//        PowerWeight::new(GenericArray::clone_from(rw))
-//    }
-//
-//    fn properties() -> SemiringProperties {
-//        W::properties()
-//            & (SemiringProperties::LEFT_SEMIRING
-//                | SemiringProperties::RIGHT_SEMIRING
-//                | SemiringProperties::COMMUTATIVE
-//                | SemiringProperties::IDEMPOTENT)
-//    }
-//}
-//
-//impl<W, N> WeaklyDivisibleSemiring for PowerWeight<W, N>
-//where
-//    W: WeaklyDivisibleSemiring,
-//    N: ArrayLength<W>,
-//{
-//    fn divide(&self, rhs: &Self, divide_type: DivideType) -> Result<Self> {
-//        let mut mul = self.clone();
-//        for i in 0..self.weights.len() {
-//            mul.weights[i] = self.weights[i].divide(&rhs.weights[i], divide_type)?;
-//        }
-//        Ok(mul)
-//    }
-//}
-//
-//impl<W, N> WeightQuantize for PowerWeight<W, N>
-//where
-//    W: WeightQuantize,
-//    N: ArrayLength<W>,
-//{
-//    fn quantize_assign(&mut self, delta: f32) -> Result<()> {
-//        for i in 0..self.weights.len() {
-//            unsafe { self.weights.get_unchecked_mut(i).quantize_assign(delta)? };
-//        }
-//        Ok(())
-//    }
-//}
+impl<W, N> Semiring for PowerWeight<W, N>
+where
+    W: Semiring,
+    W::ReverseWeight: Semiring<ReverseWeight = W>,
+    N: ArrayLength<W> + ArrayLength<W::ReverseWeight> + 'static,
+{
+    type Type = GenericArray<W, N>;
+    type ReverseWeight = PowerWeight<W::ReverseWeight, N>;
+
+    fn zero() -> Self {
+        Self {
+            weights: GenericArray::generate(|_| W::zero()),
+        }
+    }
+
+    fn one() -> Self {
+        Self {
+            weights: GenericArray::generate(|_| W::one()),
+        }
+    }
+
+    fn new(value: <Self as Semiring>::Type) -> Self {
+        Self { weights: value }
+    }
+
+    fn plus_assign<P: Borrow<Self>>(&mut self, rhs: P) -> Result<()> {
+        for (w, w_rhs) in self.weights.iter_mut().zip(rhs.borrow().weights.iter()) {
+            w.plus_assign(w_rhs)?;
+        }
+        Ok(())
+    }
+
+    fn times_assign<P: Borrow<Self>>(&mut self, rhs: P) -> Result<()> {
+        for (w, w_rhs) in self.weights.iter_mut().zip(rhs.borrow().weights.iter()) {
+            w.times_assign(w_rhs)?;
+        }
+        Ok(())
+    }
+
+    fn approx_equal<P: Borrow<Self>>(&self, rhs: P, delta: f32) -> bool {
+        self.weights
+            .iter()
+            .zip(rhs.borrow().weights.iter())
+            .all(|(w, w_rhs)| w.approx_equal(w_rhs, delta))
+    }
+
+    fn value(&self) -> &<Self as Semiring>::Type {
+        &self.weights
+    }
+
+    fn take_value(self) -> <Self as Semiring>::Type {
+        self.weights
+    }
+
+    fn set_value(&mut self, value: <Self as Semiring>::Type) {
+        self.weights = value;
+    }
+
+    fn reverse(&self) -> Result<Self::ReverseWeight> {
+        let weights = self
+            .weights
+            .iter()
+            .map(|w| w.reverse())
+            .collect::<Result<Vec<_>>>()?;
+        Ok(PowerWeight::new(weights.into_iter().collect()))
+    }
+
+    fn properties() -> SemiringProperties {
+        W::properties()
+            & (SemiringProperties::LEFT_SEMIRING
+                | SemiringProperties::RIGHT_SEMIRING
+                | SemiringProperties::COMMUTATIVE
+                | SemiringProperties::IDEMPOTENT)
+    }
+}
+
+impl<W, N> ReverseBack<PowerWeight<W, N>> for <PowerWeight<W, N> as Semiring>::ReverseWeight
+where
+    W: Semiring,
+    W::ReverseWeight: Semiring<ReverseWeight = W>,
+    N: ArrayLength<W> + ArrayLength<W::ReverseWeight> + 'static,
+{
+    fn reverse_back(&self) -> Result<PowerWeight<W, N>> {
+        let weights = self
+            .weights
+            .iter()
+            .map(|w| w.reverse_back())
+            .collect::<Result<Vec<_>>>()?;
+        Ok(PowerWeight::new(weights.into_iter().collect()))
+    }
+}
+
+impl<W, N> From<GenericArray<W, N>> for PowerWeight<W, N>
+where
+    W: Semiring,
+    W::ReverseWeight: Semiring<ReverseWeight = W>,
+    N: ArrayLength<W> + ArrayLength<W::ReverseWeight> + 'static,
+{
+    fn from(weights: GenericArray<W, N>) -> Self {
+        Self::new(weights)
+    }
+}
+
+impl<W, N> SemimoduleWeight<W> for PowerWeight<W, N>
+where
+    W: Semiring,
+    W::ReverseWeight: Semiring<ReverseWeight = W>,
+    N: ArrayLength<W> + ArrayLength<W::ReverseWeight> + 'static,
+{
+    type ReverseModule = PowerWeight<W::ReverseWeight, N>;
+
+    fn scalar_times(&self, scalar: &W) -> Result<Self> {
+        let mut res = self.clone();
+        for w in res.weights.iter_mut() {
+            w.times_assign(scalar)?;
+        }
+        Ok(res)
+    }
+
+    fn reverse_module(&self) -> Result<Self::ReverseModule> {
+        self.reverse()
+    }
+}
+
+impl<W, N> WeaklyDivisibleSemiring for PowerWeight<W, N>
+where
+    W: WeaklyDivisibleSemiring,
+    W::ReverseWeight: Semiring<ReverseWeight = W>,
+    N: ArrayLength<W> + ArrayLength<W::ReverseWeight> + 'static,
+{
+    fn divide_assign(&mut self, rhs: &Self, divide_type: DivideType) -> Result<()> {
+        for (w, w_rhs) in self.weights.iter_mut().zip(rhs.weights.iter()) {
+            w.divide_assign(w_rhs, divide_type)?;
+        }
+        Ok(())
+    }
+}
+
+impl<W, N> WeightQuantize for PowerWeight<W, N>
+where
+    W: WeightQuantize,
+    W::ReverseWeight: Semiring<ReverseWeight = W>,
+    N: ArrayLength<W> + ArrayLength<W::ReverseWeight> + 'static,
+{
+    fn quantize_assign(&mut self, delta: f32) -> Result<()> {
+        for w in self.weights.iter_mut() {
+            w.quantize_assign(delta)?;
+        }
+        Ok(())
+    }
+}
+
+impl<W, N> SerializableSemiring for PowerWeight<W, N>
+where
+    W: SerializableSemiring,
+    W::ReverseWeight: Semiring<ReverseWeight = W>,
+    N: ArrayLength<W> + ArrayLength<W::ReverseWeight> + 'static,
+{
+    fn weight_type() -> String {
+        format!("{}_^{}", W::weight_type(), N::to_usize())
+    }
+
+    fn parse_binary(i: &[u8]) -> IResult<&[u8], Self, NomCustomError<&[u8]>> {
+        let (i, weights) = count(W::parse_binary, N::to_usize())(i)?;
+        Ok((i, Self::new(weights.into_iter().collect())))
+    }
+
+    fn write_binary<F: Write>(&self, file: &mut F) -> Result<()> {
+        for w in self.weights.iter() {
+            w.write_binary(file)?;
+        }
+        Ok(())
+    }
+
+    fn parse_text(i: &str) -> IResult<&str, Self> {
+        if N::to_usize() == 0 {
+            return Ok((i, Self::new(GenericArray::generate(|_| W::zero()))));
+        }
+        let (i, first) = W::parse_text(i)?;
+        let (i, others) = count(preceded(tag(","), W::parse_text), N::to_usize() - 1)(i)?;
+        let weights = std::iter::once(first).chain(others).collect();
+        Ok((i, Self::new(weights)))
+    }
+}
+
+test_semiring_serializable!(
+    tests_power_weight_serializable,
+    PowerWeight::<TropicalWeight, generic_array::typenum::U3>,
+    PowerWeight::new(GenericArray::clone_from_slice(&[TropicalWeight::new(0.2), TropicalWeight::new(1.7), TropicalWeight::one()]))
+    PowerWeight::<TropicalWeight, generic_array::typenum::U3>::zero()
+);
+
+test_semiring_serializable!(
+    tests_empty_power_weight_serializable,
+    PowerWeight::<TropicalWeight, generic_array::typenum::U0>,
+    PowerWeight::<TropicalWeight, generic_array::typenum::U0>::one()
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use generic_array::typenum::U2;
+
+    use crate::semirings::ProbabilityWeight;
+
+    type Power2 = PowerWeight<ProbabilityWeight, U2>;
+
+    fn pow(w1: f32, w2: f32) -> Power2 {
+        PowerWeight::new(GenericArray::clone_from_slice(&[
+            ProbabilityWeight::new(w1),
+            ProbabilityWeight::new(w2),
+        ]))
+    }
+
+    #[test]
+    fn test_power_weight() -> Result<()> {
+        assert_eq!(pow(0.5, 1.0).plus(pow(0.25, 2.0))?, pow(0.75, 3.0));
+        assert_eq!(pow(0.5, 1.0).times(pow(0.25, 2.0))?, pow(0.125, 2.0));
+        assert_eq!(pow(0.5, 1.0).times(Power2::one())?, pow(0.5, 1.0));
+        assert_eq!(pow(0.5, 1.0).plus(Power2::zero())?, pow(0.5, 1.0));
+        assert_eq!(
+            pow(0.125, 2.0).divide(&pow(0.25, 2.0), DivideType::DivideAny)?,
+            pow(0.5, 1.0)
+        );
+        assert_eq!(
+            pow(0.5, 1.0).scalar_times(&ProbabilityWeight::new(2.0))?,
+            pow(1.0, 2.0)
+        );
+        assert_eq!(pow(0.5, 1.0).reverse()?.reverse_back()?, pow(0.5, 1.0));
+        assert_eq!(Power2::weight_type(), "probability_^2");
+        Ok(())
+    }
+}
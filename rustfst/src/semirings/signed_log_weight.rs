@@ -0,0 +1,292 @@
+use std::borrow::Borrow;
+use std::f32;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::io::Write;
+
+use anyhow::Result;
+use nom::branch::alt;
+use nom::bytes::complete::{tag, tag_no_case};
+use nom::combinator::map;
+use nom::number::complete::float;
+use nom::IResult;
+use ordered_float::OrderedFloat;
+
+use crate::parsers::nom_utils::NomCustomError;
+use crate::parsers::parse_bin_f32;
+use crate::parsers::write_bin_f32;
+use crate::semirings::{
+    DivideType, ReverseBack, Semiring, SemiringProperties, SerializableSemiring,
+    WeaklyDivisibleSemiring, WeightQuantize,
+};
+use crate::KDELTA;
+
+/// Signed log semiring: the log semiring extended to negative numbers. A weight is
+/// a pair `(positive, -log(|x|))` representing the real number `x`.
+#[derive(Clone, Debug, PartialOrd, Copy)]
+pub struct SignedLogWeight {
+    value: (bool, f32),
+}
+
+impl Semiring for SignedLogWeight {
+    type Type = (bool, f32);
+    type ReverseWeight = SignedLogWeight;
+
+    fn zero() -> Self {
+        Self {
+            value: (true, f32::INFINITY),
+        }
+    }
+
+    fn one() -> Self {
+        Self { value: (true, 0.0) }
+    }
+
+    fn new(value: <Self as Semiring>::Type) -> Self {
+        SignedLogWeight { value }
+    }
+
+    fn plus_assign<P: Borrow<Self>>(&mut self, rhs: P) -> Result<()> {
+        let (s1, f1) = self.value;
+        let (s2, f2) = rhs.borrow().value;
+        self.value = if f1.eq(&f32::INFINITY) {
+            (s2, f2)
+        } else if f2.eq(&f32::INFINITY) {
+            (s1, f1)
+        } else if f1 == f2 {
+            if s1 == s2 {
+                (s1, f1 - 2f32.ln())
+            } else {
+                (true, f32::INFINITY)
+            }
+        } else {
+            // The sign is the one of the weight with the highest absolute value.
+            let (s, f_min, f_max) = if f1 < f2 { (s1, f1, f2) } else { (s2, f2, f1) };
+            let x = (f_min - f_max).exp();
+            if s1 == s2 {
+                (s, f_min - x.ln_1p())
+            } else {
+                (s, f_min - (-x).ln_1p())
+            }
+        };
+        Ok(())
+    }
+
+    fn times_assign<P: Borrow<Self>>(&mut self, rhs: P) -> Result<()> {
+        let (s1, f1) = self.value;
+        let (s2, f2) = rhs.borrow().value;
+        self.value = if f1.eq(&f32::INFINITY) || f2.eq(&f32::INFINITY) {
+            (true, f32::INFINITY)
+        } else {
+            (s1 == s2, f1 + f2)
+        };
+        Ok(())
+    }
+
+    fn approx_equal<P: Borrow<Self>>(&self, rhs: P, delta: f32) -> bool {
+        let (s1, f1) = self.value;
+        let (s2, f2) = rhs.borrow().value;
+        // Zero has two representations, one for each sign.
+        (s1 == s2 || f1.eq(&f32::INFINITY)) && f1 <= (f2 + delta) && f2 <= (f1 + delta)
+    }
+
+    fn value(&self) -> &Self::Type {
+        &self.value
+    }
+
+    fn take_value(self) -> Self::Type {
+        self.value
+    }
+
+    fn set_value(&mut self, value: <Self as Semiring>::Type) {
+        self.value = value
+    }
+
+    fn reverse(&self) -> Result<Self::ReverseWeight> {
+        Ok(*self)
+    }
+
+    fn properties() -> SemiringProperties {
+        SemiringProperties::LEFT_SEMIRING
+            | SemiringProperties::RIGHT_SEMIRING
+            | SemiringProperties::COMMUTATIVE
+    }
+}
+
+impl SignedLogWeight {
+    /// Whether the weight represents a positive number (or zero).
+    pub fn is_positive(&self) -> bool {
+        self.value.0
+    }
+}
+
+impl ReverseBack<SignedLogWeight> for SignedLogWeight {
+    fn reverse_back(&self) -> Result<SignedLogWeight> {
+        Ok(*self)
+    }
+}
+
+impl AsRef<SignedLogWeight> for SignedLogWeight {
+    fn as_ref(&self) -> &SignedLogWeight {
+        self
+    }
+}
+
+impl Default for SignedLogWeight {
+    fn default() -> Self {
+        Self::one()
+    }
+}
+
+impl PartialEq for SignedLogWeight {
+    fn eq(&self, other: &Self) -> bool {
+        self.approx_equal(other, KDELTA)
+    }
+}
+
+impl Eq for SignedLogWeight {}
+
+impl Hash for SignedLogWeight {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let (s, f) = self.value;
+        // Zero is equal to itself whatever its sign.
+        if !f.is_infinite() {
+            s.hash(state);
+        }
+        OrderedFloat(f).hash(state)
+    }
+}
+
+impl fmt::Display for SignedLogWeight {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let sign = if self.value.0 { 1 } else { -1 };
+        write!(f, "{},{}", sign, self.value.1)?;
+        Ok(())
+    }
+}
+
+impl WeaklyDivisibleSemiring for SignedLogWeight {
+    fn divide_assign(&mut self, rhs: &Self, _divide_type: DivideType) -> Result<()> {
+        let (s1, f1) = self.value;
+        let (s2, f2) = rhs.value;
+        if f2.eq(&f32::INFINITY) {
+            bail!("SignedLogWeight: division by zero")
+        }
+        self.value = if f1.eq(&f32::INFINITY) {
+            (true, f32::INFINITY)
+        } else {
+            (s1 == s2, f1 - f2)
+        };
+        Ok(())
+    }
+}
+
+impl WeightQuantize for SignedLogWeight {
+    fn quantize_assign(&mut self, delta: f32) -> Result<()> {
+        let v = self.value.1;
+        if v.is_infinite() {
+            return Ok(());
+        }
+        self.value.1 = ((v / delta) + 0.5).floor() * delta;
+        Ok(())
+    }
+}
+
+impl SerializableSemiring for SignedLogWeight {
+    fn weight_type() -> String {
+        "signed_log".to_string()
+    }
+
+    fn parse_binary(i: &[u8]) -> IResult<&[u8], Self, NomCustomError<&[u8]>> {
+        let (i, sign) = parse_bin_f32(i)?;
+        let (i, weight) = parse_bin_f32(i)?;
+        Ok((i, Self::new((sign > 0.0, weight))))
+    }
+
+    fn write_binary<F: Write>(&self, file: &mut F) -> Result<()> {
+        write_bin_f32(file, if self.value.0 { 1.0 } else { -1.0 })?;
+        write_bin_f32(file, self.value.1)
+    }
+
+    fn parse_text(i: &str) -> IResult<&str, Self> {
+        let (i, sign) = float(i)?;
+        let (i, _) = tag(",")(i)?;
+        let (i, f) = alt((map(tag_no_case("infinity"), |_| f32::INFINITY), float))(i)?;
+        Ok((i, Self::new((sign > 0.0, f))))
+    }
+}
+
+test_semiring_serializable!(
+    tests_signed_log_weight_serializable,
+    SignedLogWeight,
+    SignedLogWeight::one() SignedLogWeight::zero() SignedLogWeight::new((true, 0.3))
+    SignedLogWeight::new((false, 0.3)) SignedLogWeight::new((false, -1.2))
+);
+
+impl From<f32> for SignedLogWeight {
+    /// Converts a real number into its signed log representation.
+    fn from(f: f32) -> Self {
+        Self::new((f >= 0.0, -f.abs().ln()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::algorithms::shortest_distance;
+    use crate::fst_impls::VectorFst;
+    use crate::fst_traits::MutableFst;
+    use crate::Tr;
+
+    #[test]
+    fn test_signed_log_weight() -> Result<()> {
+        let three = SignedLogWeight::from(3.0);
+        let minus_one = SignedLogWeight::from(-1.0);
+        assert_eq!(three.plus(minus_one)?, SignedLogWeight::from(2.0));
+        assert_eq!(minus_one.plus(three)?, SignedLogWeight::from(2.0));
+        assert_eq!(
+            minus_one.plus(SignedLogWeight::from(-3.0))?,
+            SignedLogWeight::from(-4.0)
+        );
+        assert_eq!(
+            minus_one.plus(SignedLogWeight::from(1.0))?,
+            SignedLogWeight::zero()
+        );
+        assert_eq!(three.times(minus_one)?, SignedLogWeight::from(-3.0));
+        assert_eq!(minus_one.times(minus_one)?, SignedLogWeight::one());
+        assert_eq!(
+            three.times(SignedLogWeight::zero())?,
+            SignedLogWeight::zero()
+        );
+        assert_eq!(
+            three.divide(&minus_one, DivideType::DivideAny)?,
+            SignedLogWeight::from(-3.0)
+        );
+        assert!(three
+            .divide(&SignedLogWeight::zero(), DivideType::DivideAny)
+            .is_err());
+        assert_eq!(
+            SignedLogWeight::new((false, f32::INFINITY)),
+            SignedLogWeight::zero()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_signed_log_weight_shortest_distance() -> Result<()> {
+        // 2 * 3 + (-4) = 2.
+        let mut fst = VectorFst::<SignedLogWeight>::new();
+        let s0 = fst.add_state();
+        let s1 = fst.add_state();
+        let s2 = fst.add_state();
+        fst.set_start(s0)?;
+        fst.set_final(s2, SignedLogWeight::one())?;
+        fst.add_tr(s0, Tr::new(1, 1, SignedLogWeight::from(2.0), s1))?;
+        fst.add_tr(s1, Tr::new(2, 2, SignedLogWeight::from(3.0), s2))?;
+        fst.add_tr(s0, Tr::new(3, 3, SignedLogWeight::from(-4.0), s2))?;
+        let distance = shortest_distance(&fst, true)?;
+        assert!(distance[s0 as usize].approx_equal(SignedLogWeight::from(2.0), 1e-3));
+        Ok(())
+    }
+}
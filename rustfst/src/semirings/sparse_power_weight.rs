@@ -0,0 +1,358 @@
+use std::borrow::Borrow;
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::fmt;
+use std::io::Write;
+
+use anyhow::Result;
+use nom::bytes::complete::tag;
+use nom::combinator::map_res;
+use nom::multi::{count, many0};
+use nom::sequence::tuple;
+use nom::IResult;
+
+use crate::parsers::nom_utils::{num, NomCustomError};
+use crate::parsers::{parse_bin_i32, write_bin_i32};
+#[cfg(test)]
+use crate::semirings::TropicalWeight;
+use crate::semirings::{
+    DivideType, ReverseBack, SemimoduleWeight, Semiring, SemiringProperties, SerializableSemiring,
+    WeaklyDivisibleSemiring, WeightQuantize,
+};
+
+/// Sparse cartesian power semiring: W ^ n where n doesn't need to be known in advance.
+/// Only the components which differ from a default value are stored, keyed by their index.
+/// All the operations are done component-wise, including on the default values.
+#[derive(Debug, Eq, PartialOrd, PartialEq, Clone, Default, Hash)]
+pub struct SparsePowerWeight<W: Semiring> {
+    weight: (W, BTreeMap<usize, W>),
+}
+
+impl<W: Semiring> AsRef<Self> for SparsePowerWeight<W> {
+    fn as_ref(&self) -> &SparsePowerWeight<W> {
+        self
+    }
+}
+
+impl<W: Semiring> SparsePowerWeight<W> {
+    /// Default value of the components.
+    pub fn default_value(&self) -> &W {
+        &self.weight.0
+    }
+
+    /// Value of the component at `index`.
+    pub fn get(&self, index: usize) -> &W {
+        self.weight.1.get(&index).unwrap_or(&self.weight.0)
+    }
+
+    /// Sets the value of the component at `index`.
+    pub fn set(&mut self, index: usize, weight: W) {
+        if &weight == self.default_value() {
+            self.weight.1.remove(&index);
+        } else {
+            self.weight.1.insert(index, weight);
+        }
+    }
+
+    /// Iterates over the components that differ from the default value.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &W)> + '_ {
+        self.weight.1.iter().map(|(idx, w)| (*idx, w))
+    }
+
+    fn combine<F>(&mut self, rhs: &Self, mut f: F) -> Result<()>
+    where
+        F: FnMut(&mut W, &W) -> Result<()>,
+    {
+        for idx in rhs.weight.1.keys() {
+            if !self.weight.1.contains_key(idx) {
+                self.weight.1.insert(*idx, self.weight.0.clone());
+            }
+        }
+        for (idx, w) in self.weight.1.iter_mut() {
+            f(w, rhs.get(*idx))?;
+        }
+        f(&mut self.weight.0, &rhs.weight.0)?;
+        self.prune();
+        Ok(())
+    }
+
+    fn map<W2, F>(&self, mut f: F) -> Result<SparsePowerWeight<W2>>
+    where
+        W2: Semiring,
+        F: FnMut(&W) -> Result<W2>,
+    {
+        let weights = self
+            .weight
+            .1
+            .iter()
+            .map(|(idx, w)| Ok((*idx, f(w)?)))
+            .collect::<Result<_>>()?;
+        Ok(SparsePowerWeight::new((f(&self.weight.0)?, weights)))
+    }
+
+    fn prune(&mut self) {
+        let default = &self.weight.0;
+        self.weight.1.retain(|_, w| w != default);
+    }
+}
+
+impl<W: Semiring> Semiring for SparsePowerWeight<W> {
+    type Type = (W, BTreeMap<usize, W>);
+    type ReverseWeight = SparsePowerWeight<W::ReverseWeight>;
+
+    fn zero() -> Self {
+        Self {
+            weight: (W::zero(), BTreeMap::new()),
+        }
+    }
+
+    fn one() -> Self {
+        Self {
+            weight: (W::one(), BTreeMap::new()),
+        }
+    }
+
+    fn new(value: <Self as Semiring>::Type) -> Self {
+        let mut w = Self { weight: value };
+        w.prune();
+        w
+    }
+
+    fn plus_assign<P: Borrow<Self>>(&mut self, rhs: P) -> Result<()> {
+        self.combine(rhs.borrow(), |w, w_rhs| w.plus_assign(w_rhs))
+    }
+
+    fn times_assign<P: Borrow<Self>>(&mut self, rhs: P) -> Result<()> {
+        self.combine(rhs.borrow(), |w, w_rhs| w.times_assign(w_rhs))
+    }
+
+    fn approx_equal<P: Borrow<Self>>(&self, rhs: P, delta: f32) -> bool {
+        let rhs = rhs.borrow();
+        self.default_value()
+            .approx_equal(rhs.default_value(), delta)
+            && self
+                .weight
+                .1
+                .keys()
+                .chain(rhs.weight.1.keys())
+                .all(|idx| self.get(*idx).approx_equal(rhs.get(*idx), delta))
+    }
+
+    fn value(&self) -> &<Self as Semiring>::Type {
+        &self.weight
+    }
+
+    fn take_value(self) -> <Self as Semiring>::Type {
+        self.weight
+    }
+
+    fn set_value(&mut self, value: <Self as Semiring>::Type) {
+        self.weight = value;
+        self.prune();
+    }
+
+    fn reverse(&self) -> Result<Self::ReverseWeight> {
+        self.map(|w| w.reverse())
+    }
+
+    fn properties() -> SemiringProperties {
+        W::properties()
+            & (SemiringProperties::LEFT_SEMIRING
+                | SemiringProperties::RIGHT_SEMIRING
+                | SemiringProperties::COMMUTATIVE
+                | SemiringProperties::IDEMPOTENT)
+    }
+}
+
+impl<W: Semiring> ReverseBack<SparsePowerWeight<W>>
+    for <SparsePowerWeight<W> as Semiring>::ReverseWeight
+{
+    fn reverse_back(&self) -> Result<SparsePowerWeight<W>> {
+        self.map(|w| w.reverse_back())
+    }
+}
+
+impl<W: Semiring> SemimoduleWeight<W> for SparsePowerWeight<W> {
+    type ReverseModule = SparsePowerWeight<W::ReverseWeight>;
+
+    fn scalar_times(&self, scalar: &W) -> Result<Self> {
+        self.map(|w| w.times(scalar))
+    }
+
+    fn reverse_module(&self) -> Result<Self::ReverseModule> {
+        self.reverse()
+    }
+}
+
+impl<W: WeaklyDivisibleSemiring> WeaklyDivisibleSemiring for SparsePowerWeight<W> {
+    fn divide_assign(&mut self, rhs: &Self, divide_type: DivideType) -> Result<()> {
+        self.combine(rhs, |w, w_rhs| w.divide_assign(w_rhs, divide_type))
+    }
+}
+
+impl<W: WeightQuantize> WeightQuantize for SparsePowerWeight<W> {
+    fn quantize_assign(&mut self, delta: f32) -> Result<()> {
+        self.weight.0.quantize_assign(delta)?;
+        for w in self.weight.1.values_mut() {
+            w.quantize_assign(delta)?;
+        }
+        self.prune();
+        Ok(())
+    }
+}
+
+impl<W: SerializableSemiring> fmt::Display for SparsePowerWeight<W> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.default_value())?;
+        for (idx, w) in self.iter() {
+            write!(f, ",{},{}", idx, w)?;
+        }
+        Ok(())
+    }
+}
+
+impl<W: SerializableSemiring> SerializableSemiring for SparsePowerWeight<W> {
+    /// The binary layout (default value, number of components, then `(index, weight)` pairs)
+    /// differs from the one of OpenFST's `SparsePowerWeight`, hence a distinct name.
+    fn weight_type() -> String {
+        format!("{}_sparse_power", W::weight_type())
+    }
+
+    fn parse_binary(i: &[u8]) -> IResult<&[u8], Self, NomCustomError<&[u8]>> {
+        let (i, default) = W::parse_binary(i)?;
+        let (i, n) = map_res(parse_bin_i32, usize::try_from)(i)?;
+        let (i, weights) = count(
+            |i| {
+                let (i, idx) = map_res(parse_bin_i32, usize::try_from)(i)?;
+                let (i, w) = W::parse_binary(i)?;
+                Ok((i, (idx, w)))
+            },
+            n,
+        )(i)?;
+        Ok((i, Self::new((default, weights.into_iter().collect()))))
+    }
+
+    fn write_binary<F: Write>(&self, file: &mut F) -> Result<()> {
+        self.default_value().write_binary(file)?;
+        let n = i32::try_from(self.weight.1.len())
+            .map_err(|_| format_err!("SparsePowerWeight : too many components to serialize"))?;
+        write_bin_i32(file, n)?;
+        for (idx, w) in self.iter() {
+            let idx = i32::try_from(idx).map_err(|_| {
+                format_err!("SparsePowerWeight : index {} doesn't fit in an i32", idx)
+            })?;
+            write_bin_i32(file, idx)?;
+            w.write_binary(file)?;
+        }
+        Ok(())
+    }
+
+    fn parse_text(i: &str) -> IResult<&str, Self> {
+        let (i, default) = W::parse_text(i)?;
+        let (i, weights) = many0(tuple((tag(","), num, tag(","), W::parse_text)))(i)?;
+        let weights = weights.into_iter().map(|(_, idx, _, w)| (idx, w)).collect();
+        Ok((i, Self::new((default, weights))))
+    }
+}
+
+test_semiring_serializable!(
+    tests_sparse_power_weight_serializable,
+    SparsePowerWeight::<TropicalWeight>,
+    SparsePowerWeight::<TropicalWeight>::zero()
+    SparsePowerWeight::new((TropicalWeight::one(), vec![(2, TropicalWeight::new(0.2)), (7, TropicalWeight::new(1.7))].into_iter().collect()))
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::algorithms::expected_values;
+    use crate::algorithms::union::union;
+    use crate::fst_impls::VectorFst;
+    use crate::semirings::ProbabilityWeight;
+    use crate::utils::acceptor;
+
+    fn sparse(default: f32, weights: &[(usize, f32)]) -> SparsePowerWeight<ProbabilityWeight> {
+        SparsePowerWeight::new((
+            ProbabilityWeight::new(default),
+            weights
+                .iter()
+                .map(|(idx, w)| (*idx, ProbabilityWeight::new(*w)))
+                .collect(),
+        ))
+    }
+
+    #[test]
+    fn test_sparse_power_weight() -> Result<()> {
+        let w1 = sparse(0.0, &[(1, 0.5), (3, 1.0)]);
+        let w2 = sparse(0.0, &[(3, 2.0), (8, 0.25)]);
+        assert_eq!(w1.plus(&w2)?, sparse(0.0, &[(1, 0.5), (3, 3.0), (8, 0.25)]));
+        assert_eq!(w1.times(&w2)?, sparse(0.0, &[(3, 2.0)]));
+        assert_eq!(w1.times(sparse(1.0, &[]))?, w1);
+        assert_eq!(
+            w1.plus(sparse(1.0, &[]))?,
+            sparse(1.0, &[(1, 1.5), (3, 2.0)])
+        );
+        assert_eq!(
+            w1.scalar_times(&ProbabilityWeight::new(2.0))?,
+            sparse(0.0, &[(1, 1.0), (3, 2.0)])
+        );
+        assert_eq!(w1.get(3), &ProbabilityWeight::new(1.0));
+        assert_eq!(w1.get(5), &ProbabilityWeight::zero());
+
+        // Components equal to the default value aren't stored.
+        let mut w = sparse(0.0, &[(1, 0.0), (2, 0.5)]);
+        assert_eq!(w.iter().count(), 1);
+        w.set(2, ProbabilityWeight::zero());
+        assert_eq!(w, SparsePowerWeight::zero());
+        assert_eq!(
+            SparsePowerWeight::<ProbabilityWeight>::weight_type(),
+            "probability_sparse_power"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_sparse_power_weight_write_binary_index_overflow() {
+        let w = sparse(0.0, &[(i32::MAX as usize + 1, 0.5)]);
+        let mut buffer = vec![];
+        assert!(w.write_binary(&mut buffer).is_err());
+    }
+
+    #[test]
+    fn test_sparse_power_weight_parse_binary_negative() -> Result<()> {
+        // Negative number of components.
+        let mut buffer = vec![];
+        ProbabilityWeight::one().write_binary(&mut buffer)?;
+        write_bin_i32(&mut buffer, -1)?;
+        assert!(SparsePowerWeight::<ProbabilityWeight>::parse_binary(&buffer).is_err());
+
+        // Negative index.
+        let mut buffer = vec![];
+        ProbabilityWeight::one().write_binary(&mut buffer)?;
+        write_bin_i32(&mut buffer, 1)?;
+        write_bin_i32(&mut buffer, -3)?;
+        ProbabilityWeight::new(0.5).write_binary(&mut buffer)?;
+        assert!(SparsePowerWeight::<ProbabilityWeight>::parse_binary(&buffer).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_sparse_power_weight_expected_values() -> Result<()> {
+        let mut fst: VectorFst<ProbabilityWeight> = acceptor(&[1], ProbabilityWeight::new(0.25));
+        let other: VectorFst<ProbabilityWeight> =
+            acceptor(&[2, 1, 1], ProbabilityWeight::new(0.75));
+        union(&mut fst, &other)?;
+
+        // Expected number of occurrences of each label.
+        let res = expected_values(&fst, |tr| {
+            let mut feature = SparsePowerWeight::zero();
+            if tr.ilabel != 0 {
+                feature.set(tr.ilabel as usize, ProbabilityWeight::one());
+            }
+            feature
+        })?;
+        assert!(res.approx_equal(sparse(0.0, &[(1, 1.75), (2, 0.75)]), 1e-3));
+        Ok(())
+    }
+}
@@ -1,11 +1,15 @@
 use std::fmt::Display;
 use std::marker::PhantomData;
 use std::path::Path;
+use std::sync::Arc;
 
 use anyhow::{format_err, Result};
 use serde::{Deserialize, Serialize};
 
-use crate::algorithms::determinize::{determinize_with_config, DeterminizeConfig, DeterminizeType};
+use crate::algorithms::determinize::{
+    determinize_with_config, DeterminizeConfig, DeterminizeFst, DeterminizeType,
+};
+use crate::fst_impls::VectorFst;
 use crate::fst_properties::FstProperties;
 use crate::fst_traits::{AllocableFst, MutableFst, SerializableFst};
 use crate::semirings::SerializableSemiring;
@@ -94,3 +98,41 @@ where
     }
     Ok(())
 }
+
+pub fn test_determinize_lazy<W>(test_data: &FstTestData<W, VectorFst<W>>) -> Result<()>
+where
+    W: SerializableSemiring + WeaklyDivisibleSemiring + WeightQuantize + 'static,
+{
+    for determinize_data in &test_data.determinize {
+        let config = DeterminizeConfig::default().with_det_type(determinize_data.det_type);
+        let fst_res: Result<VectorFst<W>> = DeterminizeFst::<_, VectorFst<_>, _>::new_with_config(
+            Arc::new(test_data.raw.clone()),
+            config,
+        )
+        .and_then(|fst| fst.compute());
+        match (&determinize_data.result, fst_res) {
+            (Ok(fst_expected), Ok(ref fst_determinized)) => {
+                test_isomorphic_fst(
+                    fst_expected,
+                    fst_determinized,
+                    format!(
+                        "Determinize lazy fail for det_type = {:?} ",
+                        determinize_data.det_type
+                    ),
+                );
+            }
+            (Ok(_fst_expected), Err(_)) => panic!(
+                "Determinize lazy fail for det_type {:?}. Got Err. Expected Ok",
+                determinize_data.det_type
+            ),
+            (Err(_), Ok(_fst_determinized)) => panic!(
+                "Determinize lazy fail for det_type {:?}. Got Ok. Expected Err, \n{}",
+                determinize_data.det_type, _fst_determinized
+            ),
+            (Err(_), Err(_)) => {
+                // Ok
+            }
+        };
+    }
+    Ok(())
+}
@@ -2,7 +2,8 @@ use std::fmt::Display;
 
 use anyhow::Result;
 
-use crate::algorithms::invert;
+use crate::algorithms::{invert, InvertFst};
+use crate::fst_impls::VectorFst;
 use crate::fst_traits::{MutableFst, SerializableFst};
 use crate::semirings::WeaklyDivisibleSemiring;
 use crate::semirings::{SerializableSemiring, WeightQuantize};
@@ -20,3 +21,13 @@ where
     test_eq_fst(&test_data.invert, &fst_invert, "Invert");
     Ok(())
 }
+
+pub fn test_invert_lazy<W>(test_data: &FstTestData<W, VectorFst<W>>) -> Result<()>
+where
+    W: SerializableSemiring + WeightQuantize,
+{
+    let fst_invert: VectorFst<_> =
+        InvertFst::<_, VectorFst<_>, _>::new(&test_data.raw).compute()?;
+    test_eq_fst(&test_data.invert, &fst_invert, "Invert lazy");
+    Ok(())
+}
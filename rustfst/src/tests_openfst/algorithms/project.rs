@@ -2,7 +2,8 @@ use std::fmt::Display;
 
 use anyhow::Result;
 
-use crate::algorithms::{project, ProjectType};
+use crate::algorithms::{project, ProjectFst, ProjectType};
+use crate::fst_impls::VectorFst;
 use crate::fst_traits::{MutableFst, SerializableFst};
 use crate::semirings::WeaklyDivisibleSemiring;
 use crate::semirings::{SerializableSemiring, WeightQuantize};
@@ -41,3 +42,33 @@ where
 
     Ok(())
 }
+
+pub fn test_project_output_lazy<W>(test_data: &FstTestData<W, VectorFst<W>>) -> Result<()>
+where
+    W: SerializableSemiring + WeightQuantize,
+{
+    let fst_project_output: VectorFst<_> =
+        ProjectFst::<_, VectorFst<_>, _>::new(&test_data.raw, ProjectType::ProjectOutput)
+            .compute()?;
+    test_eq_fst(
+        &test_data.project_output,
+        &fst_project_output,
+        "Project Output lazy",
+    );
+    Ok(())
+}
+
+pub fn test_project_input_lazy<W>(test_data: &FstTestData<W, VectorFst<W>>) -> Result<()>
+where
+    W: SerializableSemiring + WeightQuantize,
+{
+    let fst_project_input: VectorFst<_> =
+        ProjectFst::<_, VectorFst<_>, _>::new(&test_data.raw, ProjectType::ProjectInput)
+            .compute()?;
+    test_eq_fst(
+        &test_data.project_input,
+        &fst_project_input,
+        "Project Input lazy",
+    );
+    Ok(())
+}
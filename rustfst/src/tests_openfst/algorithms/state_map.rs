@@ -2,8 +2,11 @@ use std::fmt::Display;
 
 use anyhow::Result;
 
+use crate::algorithms::fst_convert_from_ref;
+use crate::algorithms::state_map::{state_map, StateMapFst, TrSumMapper, TrUniqueMapper};
 use crate::algorithms::tr_sum;
 use crate::algorithms::tr_unique;
+use crate::fst_impls::VectorFst;
 use crate::fst_traits::{MutableFst, SerializableFst};
 use crate::semirings::{SerializableSemiring, WeightQuantize};
 use crate::tests_openfst::utils::test_eq_fst;
@@ -26,6 +29,23 @@ where
     Ok(())
 }
 
+pub fn test_state_map_tr_sum_mapper<W, F>(test_data: &FstTestData<W, F>) -> Result<()>
+where
+    F: SerializableFst<W> + MutableFst<W> + Display,
+    W: SerializableSemiring + WeightQuantize,
+{
+    let mut fst_state_map = test_data.raw.clone();
+    state_map(&mut fst_state_map, &TrSumMapper {})?;
+
+    test_eq_fst(
+        &test_data.state_map_tr_sum,
+        &fst_state_map,
+        "StateMap : TrSumMapper",
+    );
+
+    Ok(())
+}
+
 pub fn test_state_map_tr_unique<W, F>(test_data: &FstTestData<W, F>) -> Result<()>
 where
     F: SerializableFst<W> + MutableFst<W> + Display,
@@ -42,3 +62,52 @@ where
 
     Ok(())
 }
+
+pub fn test_state_map_tr_unique_mapper<W, F>(test_data: &FstTestData<W, F>) -> Result<()>
+where
+    F: SerializableFst<W> + MutableFst<W> + Display,
+    W: SerializableSemiring + WeightQuantize,
+{
+    let mut fst_state_map = test_data.raw.clone();
+    state_map(&mut fst_state_map, &TrUniqueMapper {})?;
+
+    test_eq_fst(
+        &test_data.state_map_tr_unique,
+        &fst_state_map,
+        "StateMap : TrUniqueMapper",
+    );
+
+    Ok(())
+}
+
+pub fn test_state_map_tr_sum_lazy<W>(test_data: &FstTestData<W, VectorFst<W>>) -> Result<()>
+where
+    W: SerializableSemiring + WeightQuantize,
+{
+    let fst_state_map: VectorFst<_> =
+        fst_convert_from_ref(&StateMapFst::new(test_data.raw.clone(), TrSumMapper {}));
+
+    test_eq_fst(
+        &test_data.state_map_tr_sum,
+        &fst_state_map,
+        "StateMap lazy : TrSum",
+    );
+
+    Ok(())
+}
+
+pub fn test_state_map_tr_unique_lazy<W>(test_data: &FstTestData<W, VectorFst<W>>) -> Result<()>
+where
+    W: SerializableSemiring + WeightQuantize,
+{
+    let fst_state_map: VectorFst<_> =
+        fst_convert_from_ref(&StateMapFst::new(test_data.raw.clone(), TrUniqueMapper {}));
+
+    test_eq_fst(
+        &test_data.state_map_tr_unique,
+        &fst_state_map,
+        "StateMap lazy : TrUnique",
+    );
+
+    Ok(())
+}
@@ -8,6 +8,8 @@ use crate::algorithms::tr_mappers::{
     IdentityTrMapper, InputEpsilonMapper, InvertWeightMapper, OutputEpsilonMapper, PlusMapper,
     QuantizeMapper, RmWeightMapper, TimesMapper,
 };
+use crate::algorithms::TrMapFst;
+use crate::fst_impls::VectorFst;
 use crate::fst_traits::{MutableFst, SerializableFst};
 use crate::semirings::SerializableSemiring;
 use crate::semirings::WeaklyDivisibleSemiring;
@@ -177,3 +179,36 @@ where
     );
     Ok(())
 }
+
+pub fn test_tr_map_rmweight_lazy<W>(test_data: &FstTestData<W, VectorFst<W>>) -> Result<()>
+where
+    W: SerializableSemiring + WeightQuantize,
+{
+    // TrMap RmWeightMapper lazy
+    let fst_tr_map_rmweight: VectorFst<_> =
+        TrMapFst::<_, VectorFst<_>, _, _>::new(&test_data.raw, RmWeightMapper {}).compute()?;
+
+    test_eq_fst(
+        &test_data.tr_map_rmweight,
+        &fst_tr_map_rmweight,
+        "TrMap RmWeight lazy",
+    );
+    Ok(())
+}
+
+pub fn test_tr_map_quantize_lazy<W>(test_data: &FstTestData<W, VectorFst<W>>) -> Result<()>
+where
+    W: SerializableSemiring + WeightQuantize,
+{
+    // TrMap QuantizeMapper lazy
+    let fst_tr_map_quantize: VectorFst<_> =
+        TrMapFst::<_, VectorFst<_>, _, _>::new(&test_data.raw, QuantizeMapper::default())
+            .compute()?;
+
+    test_eq_fst(
+        &test_data.tr_map_quantize,
+        &fst_tr_map_quantize,
+        "TrMap Quantize lazy",
+    );
+    Ok(())
+}
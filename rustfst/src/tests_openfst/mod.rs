@@ -68,11 +68,15 @@ use crate::tests_openfst::io::vector_fst_text_serialization::{
 use self::algorithms::{
     compose::test_compose,
     connect::test_connect,
-    determinize::{test_determinize, DeterminizeOperationResult, DeterminizeTestData},
+    determinize::{
+        test_determinize, test_determinize_lazy, DeterminizeOperationResult, DeterminizeTestData,
+    },
     encode::{test_encode, test_encode_decode, EncodeOperationResult, EncodeTestData},
-    inverse::test_invert,
+    inverse::{test_invert, test_invert_lazy},
     minimize::{test_minimize, MinimizeOperationResult, MinimizeTestData},
-    project::{test_project_input, test_project_output},
+    project::{
+        test_project_input, test_project_input_lazy, test_project_output, test_project_output_lazy,
+    },
     properties::{parse_fst_properties, test_fst_properties},
     push::{test_push, PushOperationResult, PushTestData},
     replace::{test_replace, test_replace_lazy, ReplaceOperationResult, ReplaceTestData},
@@ -82,11 +86,15 @@ use self::algorithms::{
         test_shortest_distance, ShorestDistanceOperationResult, ShortestDistanceTestData,
     },
     shortest_path::{test_shortest_path, ShorestPathOperationResult, ShortestPathTestData},
-    state_map::{test_state_map_tr_sum, test_state_map_tr_unique},
+    state_map::{
+        test_state_map_tr_sum, test_state_map_tr_sum_lazy, test_state_map_tr_sum_mapper,
+        test_state_map_tr_unique, test_state_map_tr_unique_lazy, test_state_map_tr_unique_mapper,
+    },
     topsort::test_topsort,
     tr_map::{
         test_tr_map_identity, test_tr_map_input_epsilon, test_tr_map_invert,
-        test_tr_map_output_epsilon, test_tr_map_plus, test_tr_map_quantize, test_tr_map_rmweight,
+        test_tr_map_output_epsilon, test_tr_map_plus, test_tr_map_quantize,
+        test_tr_map_quantize_lazy, test_tr_map_rmweight, test_tr_map_rmweight_lazy,
         test_tr_map_times, TrMapWithWeightOperationResult, TrMapWithWeightTestData,
     },
     tr_sort::{test_trsort_ilabel, test_trsort_olabel},
@@ -632,6 +640,18 @@ macro_rules! test_fst {
                 Ok(())
             }
 
+            #[test]
+            fn test_state_map_tr_unique_mapper_openfst() -> Result<()> {
+                do_run!(test_state_map_tr_unique_mapper, $fst_name);
+                Ok(())
+            }
+
+            #[test]
+            fn test_state_map_tr_sum_mapper_openfst() -> Result<()> {
+                do_run!(test_state_map_tr_sum_mapper, $fst_name);
+                Ok(())
+            }
+
             #[test]
             fn test_shortest_path_openfst() -> Result<()> {
                 do_run!(test_shortest_path, $fst_name);
@@ -758,6 +778,54 @@ macro_rules! test_fst {
                 Ok(())
             }
 
+            #[test]
+            fn test_determinize_lazy_openfst() -> Result<()> {
+                do_run!(test_determinize_lazy, $fst_name);
+                Ok(())
+            }
+
+            #[test]
+            fn test_tr_map_rmweight_lazy_openfst() -> Result<()> {
+                do_run!(test_tr_map_rmweight_lazy, $fst_name);
+                Ok(())
+            }
+
+            #[test]
+            fn test_tr_map_quantize_lazy_openfst() -> Result<()> {
+                do_run!(test_tr_map_quantize_lazy, $fst_name);
+                Ok(())
+            }
+
+            #[test]
+            fn test_invert_lazy_openfst() -> Result<()> {
+                do_run!(test_invert_lazy, $fst_name);
+                Ok(())
+            }
+
+            #[test]
+            fn test_project_output_lazy_openfst() -> Result<()> {
+                do_run!(test_project_output_lazy, $fst_name);
+                Ok(())
+            }
+
+            #[test]
+            fn test_project_input_lazy_openfst() -> Result<()> {
+                do_run!(test_project_input_lazy, $fst_name);
+                Ok(())
+            }
+
+            #[test]
+            fn test_state_map_tr_sum_lazy_openfst() -> Result<()> {
+                do_run!(test_state_map_tr_sum_lazy, $fst_name);
+                Ok(())
+            }
+
+            #[test]
+            fn test_state_map_tr_unique_lazy_openfst() -> Result<()> {
+                do_run!(test_state_map_tr_unique_lazy, $fst_name);
+                Ok(())
+            }
+
             #[test]
             fn test_fst_into_iterator_const_openfst() -> Result<()> {
                 do_run!(test_fst_into_iterator_const, $fst_name);
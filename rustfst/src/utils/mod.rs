@@ -1,7 +1,10 @@
 mod epsilon_machine;
 mod fst_to_labels;
 mod labels_to_fst;
+mod string_compiler;
 
 pub use self::epsilon_machine::epsilon_machine;
 pub use self::fst_to_labels::decode_linear_fst;
 pub use self::labels_to_fst::{acceptor, transducer};
+pub(crate) use self::string_compiler::string_to_labels;
+pub use self::string_compiler::{compile_string, cross, print_string, TokenType};
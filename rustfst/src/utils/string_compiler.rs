@@ -0,0 +1,246 @@
+use anyhow::Result;
+
+use crate::fst_path::FstPath;
+use crate::fst_traits::{Fst, MutableFst};
+use crate::semirings::Semiring;
+use crate::utils::{acceptor, decode_linear_fst, transducer};
+use crate::{Label, SymbolTable, EPS_LABEL};
+
+/// How a string is split into labels.
+#[derive(Debug, Clone, Copy)]
+pub enum TokenType<'a> {
+    /// One label per byte, equal to the value of the byte.
+    Byte,
+    /// One label per Unicode character, equal to its code point.
+    Utf8,
+    /// One label per whitespace-separated symbol, looked up in the `SymbolTable`.
+    Symbols(&'a SymbolTable),
+}
+
+pub(crate) fn string_to_labels(text: &str, token_type: TokenType) -> Result<Vec<Label>> {
+    match token_type {
+        TokenType::Byte => text
+            .bytes()
+            .map(|b| {
+                if b == 0 {
+                    bail!("Byte 0 can't be compiled as it is reserved for epsilon")
+                }
+                Ok(b as Label)
+            })
+            .collect(),
+        TokenType::Utf8 => text
+            .chars()
+            .map(|c| {
+                if c == '\0' {
+                    bail!("Character U+0000 can't be compiled as it is reserved for epsilon")
+                }
+                Ok(c as u32 as Label)
+            })
+            .collect(),
+        TokenType::Symbols(symt) => text
+            .split_whitespace()
+            .map(|sym| {
+                symt.get_label(sym)
+                    .ok_or_else(|| format_err!("Symbol {:?} not found in the SymbolTable", sym))
+            })
+            .collect(),
+    }
+}
+
+fn labels_to_string(labels: &[Label], token_type: TokenType) -> Result<String> {
+    let labels = labels.iter().copied().filter(|l| *l != EPS_LABEL);
+    match token_type {
+        TokenType::Byte => {
+            let bytes = labels
+                .map(|l| {
+                    if l > u8::MAX as Label {
+                        bail!("Label {} is not a byte", l)
+                    }
+                    Ok(l as u8)
+                })
+                .collect::<Result<Vec<_>>>()?;
+            Ok(String::from_utf8(bytes)?)
+        }
+        TokenType::Utf8 => labels
+            .map(|l| {
+                std::char::from_u32(l as _)
+                    .ok_or_else(|| format_err!("Label {} is not a Unicode code point", l))
+            })
+            .collect(),
+        TokenType::Symbols(symt) => {
+            let symbols = labels
+                .map(|l| {
+                    symt.get_symbol(l)
+                        .ok_or_else(|| format_err!("Label {} not found in the SymbolTable", l))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            Ok(symbols.join(" "))
+        }
+    }
+}
+
+/// Compiles a string into a linear acceptor, splitting it into labels according to `token_type`.
+///
+/// # Example
+///
+/// ```
+/// # use anyhow::Result;
+/// # use rustfst::fst_impls::VectorFst;
+/// # use rustfst::semirings::{Semiring, TropicalWeight};
+/// # use rustfst::utils::{acceptor, compile_string, TokenType};
+/// # fn main() -> Result<()> {
+/// let fst : VectorFst<TropicalWeight> = compile_string("hé", TokenType::Utf8, TropicalWeight::one())?;
+/// assert_eq!(fst, acceptor(&[104, 233], TropicalWeight::one()));
+///
+/// let fst : VectorFst<TropicalWeight> = compile_string("hé", TokenType::Byte, TropicalWeight::one())?;
+/// assert_eq!(fst, acceptor(&[104, 195, 169], TropicalWeight::one()));
+/// # Ok(())
+/// # }
+/// ```
+pub fn compile_string<W: Semiring, F: MutableFst<W>>(
+    text: &str,
+    token_type: TokenType,
+    weight: W,
+) -> Result<F> {
+    Ok(acceptor(&string_to_labels(text, token_type)?, weight))
+}
+
+/// Builds the linear transducer mapping the string of the linear FST `input` to the string
+/// of the linear FST `output`. Its weight is the product of the weights of both FSTs.
+///
+/// Fails if one of the FSTs has more than one path.
+///
+/// # Example
+///
+/// ```
+/// # use anyhow::Result;
+/// # use rustfst::fst_impls::VectorFst;
+/// # use rustfst::semirings::{Semiring, TropicalWeight};
+/// # use rustfst::utils::{compile_string, cross, transducer, TokenType};
+/// # fn main() -> Result<()> {
+/// let input : VectorFst<TropicalWeight> = compile_string("ab", TokenType::Byte, TropicalWeight::one())?;
+/// let output : VectorFst<TropicalWeight> = compile_string("c", TokenType::Byte, TropicalWeight::new(2.0))?;
+///
+/// let fst : VectorFst<TropicalWeight> = cross(&input, &output)?;
+/// assert_eq!(fst, transducer(&[97, 98], &[99], TropicalWeight::new(2.0)));
+/// # Ok(())
+/// # }
+/// ```
+pub fn cross<W, F1, F2, F3>(input: &F1, output: &F2) -> Result<F3>
+where
+    W: Semiring,
+    F1: Fst<W>,
+    F2: Fst<W>,
+    F3: MutableFst<W>,
+{
+    let ipath = decode_linear_fst(input)?;
+    let opath = decode_linear_fst(output)?;
+    let mut fst: F3 = transducer(
+        &ipath.ilabels,
+        &opath.olabels,
+        ipath.weight.times(&opath.weight)?,
+    );
+    if let Some(symt) = input.input_symbols() {
+        fst.set_input_symbols(symt.clone());
+    }
+    if let Some(symt) = output.output_symbols() {
+        fst.set_output_symbols(symt.clone());
+    }
+    Ok(fst)
+}
+
+/// Turns the output labels of a path, for instance returned by `decode_linear_fst`,
+/// back into a string according to `token_type`. Epsilon labels are skipped.
+///
+/// # Example
+///
+/// ```
+/// # use anyhow::Result;
+/// # use rustfst::fst_impls::VectorFst;
+/// # use rustfst::semirings::{Semiring, TropicalWeight};
+/// # use rustfst::utils::{compile_string, decode_linear_fst, print_string, TokenType};
+/// # fn main() -> Result<()> {
+/// let fst : VectorFst<TropicalWeight> = compile_string("hé", TokenType::Byte, TropicalWeight::one())?;
+/// let path = decode_linear_fst(&fst)?;
+/// assert_eq!(print_string(&path, TokenType::Byte)?, "hé");
+/// # Ok(())
+/// # }
+/// ```
+pub fn print_string<W: Semiring>(fst_path: &FstPath<W>, token_type: TokenType) -> Result<String> {
+    labels_to_string(&fst_path.olabels, token_type)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::fst_impls::VectorFst;
+    use crate::semirings::TropicalWeight;
+    use crate::symt;
+    use crate::SymbolTable;
+
+    #[test]
+    fn test_compile_string_symbols() -> Result<()> {
+        let symt = symt!["hello", "world"];
+        let fst: VectorFst<TropicalWeight> = compile_string(
+            " hello  world ",
+            TokenType::Symbols(&symt),
+            TropicalWeight::new(1.5),
+        )?;
+        assert_eq!(fst, acceptor(&[1, 2], TropicalWeight::new(1.5)));
+
+        let path = decode_linear_fst(&fst)?;
+        assert_eq!(
+            print_string(&path, TokenType::Symbols(&symt))?,
+            "hello world"
+        );
+
+        assert!(compile_string::<TropicalWeight, VectorFst<_>>(
+            "hello there",
+            TokenType::Symbols(&symt),
+            TropicalWeight::one()
+        )
+        .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_compile_string_errors() -> Result<()> {
+        for token_type in &[TokenType::Byte, TokenType::Utf8] {
+            assert!(compile_string::<TropicalWeight, VectorFst<_>>(
+                "a\0b",
+                *token_type,
+                TropicalWeight::one()
+            )
+            .is_err());
+        }
+
+        let path = FstPath::new(vec![], vec![0xc3], TropicalWeight::one());
+        assert!(print_string(&path, TokenType::Byte).is_err());
+        let path = FstPath::new(vec![], vec![300], TropicalWeight::one());
+        assert!(print_string(&path, TokenType::Byte).is_err());
+        assert_eq!(print_string(&path, TokenType::Utf8)?, "Ĭ");
+        let path = FstPath::new(vec![], vec![0xd800], TropicalWeight::one());
+        assert!(print_string(&path, TokenType::Utf8).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_cross() -> Result<()> {
+        let input: VectorFst<TropicalWeight> =
+            compile_string("abc", TokenType::Utf8, TropicalWeight::new(1.0))?;
+        let output: VectorFst<TropicalWeight> =
+            compile_string("dé", TokenType::Utf8, TropicalWeight::new(2.0))?;
+        let fst: VectorFst<TropicalWeight> = cross(&input, &output)?;
+
+        let path = decode_linear_fst(&fst)?;
+        assert_eq!(path.weight, TropicalWeight::new(3.0));
+        assert_eq!(print_string(&path, TokenType::Utf8)?, "dé");
+        assert_eq!(labels_to_string(&path.ilabels, TokenType::Utf8)?, "abc");
+
+        let mut not_linear = input.clone();
+        not_linear.set_final(0, TropicalWeight::one())?;
+        assert!(cross::<_, _, _, VectorFst<_>>(&not_linear, &output).is_err());
+        Ok(())
+    }
+}